@@ -0,0 +1,396 @@
+//! Logging in with twitch's OAuth device code flow, and keeping the token.
+//!
+//! `--login` shows a code to enter on twitch's website and waits for it, then saves the token to
+//! `$XDG_DATA_HOME/twitch-tui/token.json` (or `~/.local/share/twitch-tui/token.json`), readable
+//! only by the user. On startup, [`load`] validates the saved token, refreshing it if it's expired,
+//! and [`token()`] is attached to GQL requests, IRC and PubSub. `--logout` revokes it and deletes
+//! the file.
+//!
+//! Tokens only work with the client ID they were made for, so the `Client-Id` from the config's
+//! `headers` is used. These requests are never recorded as fixtures, since they contain tokens.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+#[cfg(unix)]
+use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use curl::easy::{Easy, List};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use simd_json::{from_slice, to_vec_pretty};
+
+use crate::config::config;
+use crate::error::{Error, ErrorKind};
+use crate::log::warn;
+use crate::request::check_status;
+use crate::utils::data_dir;
+
+/// What the token is allowed to do
+const SCOPES: &str = "chat:read chat:edit user:read:follows";
+
+/// Refresh tokens that expire within this many seconds
+const REFRESH_BEFORE: i64 = 60 * 60;
+
+/// A saved login
+#[derive(Serialize, Deserialize)]
+pub struct Token {
+	pub access_token: String,
+	refresh_token: Option<String>,
+	/// Unix time the access token expires at, if it does
+	expires_at: Option<i64>,
+	/// Who's logged in
+	pub login: String,
+	pub user_id: String,
+}
+
+/// The token being used, set by [`set`]
+static TOKEN: OnceLock<Option<Token>> = OnceLock::new();
+
+/// The current token, if we're logged in.
+pub fn token() -> Option<&'static Token> {
+	TOKEN.get().and_then(Option::as_ref)
+}
+
+/// Sets the token returned by [`token()`]. This only works once, and has to be called before
+/// anything makes a request.
+pub fn set(token: Option<Token>) {
+	let _ = TOKEN.set(token);
+}
+
+/// Where the token is saved
+fn path() -> Option<PathBuf> {
+	data_dir().map(|dir| dir.join("token.json"))
+}
+
+/// The client ID tokens are made for
+fn client_id() -> Result<&'static str, Error> {
+	config().client_id().ok_or_else(|| {
+		Error::new(
+			"Logging in",
+			ErrorKind::Other("`headers` needs a `Client-Id` to log in".to_owned()),
+		)
+	})
+}
+
+/// Sends a request to the auth endpoint, POSTing `form` if it's given. Returns the status and body
+/// whether it was successful or not, since some errors are expected.
+fn send(
+	path: &str,
+	operation: &str,
+	form: Option<&[(&str, &str)]>,
+	access_token: Option<&str>,
+) -> Result<(u32, Vec<u8>), Error> {
+	let curl_error = |e| Error::new(operation, ErrorKind::Curl(e));
+
+	let mut easy = Easy::new();
+	easy.url(&[&config().endpoints.auth, path].concat())
+		.map_err(curl_error)?;
+
+	if let Some(access_token) = access_token {
+		let mut list = List::new();
+		list.append(&["Authorization: OAuth ", access_token].concat())
+			.map_err(curl_error)?;
+		easy.http_headers(list).map_err(curl_error)?;
+	}
+
+	let data = form
+		.map(|form| {
+			form.iter()
+				.map(|(name, value)| [name, "=", &easy.url_encode(value.as_bytes())].concat())
+				.collect::<Vec<_>>()
+				.join("&")
+		})
+		.unwrap_or_default();
+
+	if form.is_some() {
+		easy.post(true).map_err(curl_error)?;
+		easy.post_field_size(data.len() as u64)
+			.map_err(curl_error)?;
+	}
+
+	let mut data = data.as_bytes();
+	let mut vec = Vec::new();
+
+	{
+		let mut transfer = easy.transfer();
+
+		let _ = transfer.read_function(|slice| Ok(data.read(slice).unwrap_or(0)));
+		let _ = transfer.write_function(|slice| {
+			vec.extend_from_slice(slice);
+			Ok(slice.len())
+		});
+
+		transfer.perform().map_err(curl_error)?;
+	}
+
+	let status = easy.response_code().map_err(curl_error)?;
+
+	Ok((status, vec))
+}
+
+/// Parses a successful JSON response.
+fn parse<D: DeserializeOwned>(operation: &str, status: u32, body: Vec<u8>) -> Result<D, Error> {
+	let (_, mut body) = check_status(operation, status, body)?;
+
+	from_slice(&mut body).map_err(|e| Error::new(operation, ErrorKind::Json(e)))
+}
+
+/// Response to starting the device code flow
+#[derive(Deserialize)]
+struct DeviceCode {
+	device_code: String,
+	user_code: String,
+	verification_uri: String,
+	/// Seconds to wait between polls
+	interval: u64,
+	/// Seconds until the code expires
+	expires_in: u64,
+}
+
+/// Response to getting or refreshing a token
+#[derive(Deserialize)]
+struct TokenResponse {
+	access_token: String,
+	refresh_token: Option<String>,
+	expires_in: Option<i64>,
+	// Ignore `scope` and `token_type`
+}
+
+/// Response to validating a token
+#[derive(Deserialize)]
+struct Validation {
+	login: String,
+	user_id: String,
+	expires_in: Option<i64>,
+	// Ignore `client_id` and `scopes`
+}
+
+/// Error response from the auth server
+#[derive(Deserialize)]
+struct AuthError {
+	message: String,
+	// Ignore `status`
+}
+
+/// Logs in with the device code flow, telling the user where to go on stdout. Saves the token.
+pub fn login() -> Result<Token, Error> {
+	let client_id = client_id()?;
+
+	let (status, body) = send(
+		"/device",
+		"Starting login",
+		Some(&[("client_id", client_id), ("scopes", SCOPES)]),
+		None,
+	)?;
+	let device_code: DeviceCode = parse("Starting login", status, body)?;
+
+	println!(
+		"Go to {} and enter the code {}",
+		device_code.verification_uri, device_code.user_code
+	);
+
+	// Poll until the user has entered the code, or it expires
+	let mut interval = Duration::from_secs(device_code.interval.max(1));
+	let expires_at = Instant::now() + Duration::from_secs(device_code.expires_in);
+	while Instant::now() + interval < expires_at {
+		sleep(interval);
+
+		let (status, body) = send(
+			"/token",
+			"Logging in",
+			Some(&[
+				("client_id", client_id),
+				("scopes", SCOPES),
+				("device_code", &device_code.device_code),
+				("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+			]),
+			None,
+		)?;
+
+		if status == 400 {
+			match from_slice::<AuthError>(&mut body.clone()) {
+				// The user hasn't entered it yet
+				Ok(error) if error.message == "authorization_pending" => continue,
+				// We're polling too often, wait 5 seconds longer from now on (RFC 8628 §3.5)
+				Ok(error) if error.message == "slow_down" => {
+					interval += Duration::from_secs(5);
+					continue;
+				}
+				_ => {}
+			}
+		}
+
+		let token = with_user(parse("Logging in", status, body)?)?;
+
+		save(&token)?;
+
+		return Ok(token);
+	}
+
+	Err(Error::new(
+		"Logging in",
+		ErrorKind::Other("The code expired before it was entered".to_owned()),
+	))
+}
+
+/// Makes a [`Token`] from a new access token, looking up who it's for.
+fn with_user(response: TokenResponse) -> Result<Token, Error> {
+	let validation = validate(&response.access_token)?.ok_or_else(|| {
+		Error::new(
+			"Validating login",
+			ErrorKind::Other("New token is invalid".to_owned()),
+		)
+	})?;
+
+	Ok(Token {
+		access_token: response.access_token,
+		refresh_token: response.refresh_token,
+		expires_at: response
+			.expires_in
+			.or(validation.expires_in)
+			.map(|expires_in| Utc::now().timestamp() + expires_in),
+		login: validation.login,
+		user_id: validation.user_id,
+	})
+}
+
+/// Checks an access token, returning `None` if it's invalid.
+fn validate(access_token: &str) -> Result<Option<Validation>, Error> {
+	let (status, body) = send("/validate", "Validating login", None, Some(access_token))?;
+
+	if status == 401 {
+		Ok(None)
+	} else {
+		parse("Validating login", status, body).map(Some)
+	}
+}
+
+/// Gets a new access token with the refresh token, and saves it.
+fn refresh(token: &Token) -> Result<Token, Error> {
+	let refresh_token = token.refresh_token.as_deref().ok_or_else(|| {
+		Error::new(
+			"Refreshing login",
+			ErrorKind::Other("Login expired, log in again with `--login`".to_owned()),
+		)
+	})?;
+
+	let (status, body) = send(
+		"/token",
+		"Refreshing login",
+		Some(&[
+			("client_id", client_id()?),
+			("grant_type", "refresh_token"),
+			("refresh_token", refresh_token),
+		]),
+		None,
+	)?;
+
+	let token = with_user(parse("Refreshing login", status, body)?)?;
+
+	save(&token)?;
+
+	Ok(token)
+}
+
+/// Reads the saved token, if there is one, and makes sure it's still valid.
+///
+/// If it can't be checked (i.e. there's no network) it's used anyway.
+pub fn load() -> Result<Option<Token>, Error> {
+	let Some(path) = path() else {
+		return Ok(None);
+	};
+
+	let mut bytes = match fs::read(&path) {
+		Ok(bytes) => bytes,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+		Err(e) => return Err(Error::new("Reading login", ErrorKind::Io(e))),
+	};
+
+	let token = from_slice::<Token>(&mut bytes)
+		.map_err(|e| Error::new("Reading login", ErrorKind::Json(e)))?;
+
+	if token
+		.expires_at
+		.is_some_and(|expires_at| expires_at - Utc::now().timestamp() < REFRESH_BEFORE)
+	{
+		return refresh(&token).map(Some);
+	}
+
+	match validate(&token.access_token) {
+		Ok(Some(_)) => Ok(Some(token)),
+		// Revoked or expired early
+		Ok(None) => refresh(&token).map(Some),
+		Err(e) => {
+			warn(["Couldn't validate login, using it anyway: ", &e.to_string()].concat());
+
+			Ok(Some(token))
+		}
+	}
+}
+
+/// Saves a token so only the user can read it.
+fn save(token: &Token) -> Result<(), Error> {
+	let io_error = |e| Error::new("Saving login", ErrorKind::Io(e));
+
+	let path = path().ok_or_else(|| {
+		Error::new(
+			"Saving login",
+			ErrorKind::Other("Nowhere to save it, set `HOME` or `XDG_DATA_HOME`".to_owned()),
+		)
+	})?;
+
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir).map_err(io_error)?;
+	}
+
+	let json = to_vec_pretty(token).map_err(|e| Error::new("Saving login", ErrorKind::Json(e)))?;
+
+	let mut options = OpenOptions::new();
+	options.write(true).create(true).truncate(true);
+	#[cfg(unix)]
+	options.mode(0o600);
+
+	let mut file = options.open(&path).map_err(io_error)?;
+
+	// `mode` only applies to new files
+	#[cfg(unix)]
+	file.set_permissions(fs::Permissions::from_mode(0o600))
+		.map_err(io_error)?;
+
+	file.write_all(&json).map_err(io_error)
+}
+
+/// Revokes the saved token and deletes it. Does nothing if we aren't logged in.
+pub fn logout() -> Result<(), Error> {
+	let Some(path) = path() else {
+		return Ok(());
+	};
+
+	let mut bytes = match fs::read(&path) {
+		Ok(bytes) => bytes,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+		Err(e) => return Err(Error::new("Reading login", ErrorKind::Io(e))),
+	};
+
+	// Delete it even if it's invalid, so a broken file can be fixed by logging out
+	if let Ok(token) = from_slice::<Token>(&mut bytes) {
+		let (status, body) = send(
+			"/revoke",
+			"Logging out",
+			Some(&[("client_id", client_id()?), ("token", &token.access_token)]),
+			None,
+		)?;
+
+		// 400 means it's already invalid
+		if status != 400 {
+			check_status("Logging out", status, body)?;
+		}
+	}
+
+	fs::remove_file(&path).map_err(|e| Error::new("Deleting login", ErrorKind::Io(e)))
+}
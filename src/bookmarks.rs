@@ -0,0 +1,115 @@
+//! Channels and categories saved with the bookmark key, which the search box suggests.
+//!
+//! They're saved to `$XDG_DATA_HOME/twitch-tui/bookmarks.json` (or
+//! `~/.local/share/twitch-tui/bookmarks.json`), oldest first.
+
+use serde::{Deserialize, Serialize};
+
+use crate::log::warn;
+use crate::structs::Node;
+use crate::utils::{load_data, save_data};
+
+/// Where they're saved, in [`crate::utils::data_dir`]
+const FILE: &str = "bookmarks.json";
+
+/// A saved channel or category
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum Bookmark {
+	/// Property is the channel's login
+	Channel(String),
+	/// Property is the category's name
+	Game(String),
+}
+impl Bookmark {
+	/// The channel `node` is from (see [`Node::channel`]), or it's category if it isn't from one
+	pub fn of(node: &Node) -> Option<Self> {
+		node.channel()
+			.map(|login| Self::Channel(login.to_owned()))
+			.or_else(|| node.game().map(|name| Self::Game(name.to_owned())))
+	}
+
+	/// The channel's login or the category's name
+	pub fn name(&self) -> &str {
+		match self {
+			Self::Channel(name) | Self::Game(name) => name,
+		}
+	}
+}
+
+/// Saved channels and categories, oldest first
+#[derive(Default)]
+pub struct Bookmarks(Vec<Bookmark>);
+impl Bookmarks {
+	/// Reads the saved bookmarks. There aren't any if they haven't been saved, or if they can't be
+	/// read (the reason is in the log).
+	pub fn load() -> Self {
+		Self(load_data(FILE, "bookmarks").unwrap_or_default())
+	}
+
+	/// Saves `bookmark`, or forgets it if it's already saved.
+	pub fn toggle(&mut self, bookmark: Bookmark) {
+		if self.contains(&bookmark) {
+			self.0.retain(|x| *x != bookmark);
+		} else {
+			self.0.push(bookmark);
+		}
+
+		if let Err(e) = save_data(FILE, "bookmarks", &self.0) {
+			warn(["Couldn't save bookmarks: ", &e.to_string()].concat());
+		}
+	}
+
+	pub fn contains(&self, bookmark: &Bookmark) -> bool {
+		self.0.contains(bookmark)
+	}
+
+	/// Bookmarks with names containing `text` (ignoring case), newest first
+	pub fn matching<'a>(&'a self, text: &'a str) -> impl Iterator<Item = &'a Bookmark> {
+		let text = text.to_lowercase();
+
+		self.0
+			.iter()
+			.rev()
+			.filter(move |bookmark| bookmark.name().to_lowercase().contains(&text))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use twitch_curses::{ClipSource, VideoType};
+
+	use super::*;
+
+	#[test]
+	fn bookmarks_what_items_are_from() {
+		assert_eq!(
+			Bookmark::of(&Node::Videos {
+				login: "mockstreamer".to_owned(),
+				kind: VideoType::Archive,
+			}),
+			Some(Bookmark::Channel("mockstreamer".to_owned()))
+		);
+		assert_eq!(
+			Bookmark::of(&Node::Clips(ClipSource::Category("Minecraft".to_owned()))),
+			Some(Bookmark::Game("Minecraft".to_owned()))
+		);
+		assert_eq!(Bookmark::of(&Node::Video("6001".to_owned())), None);
+	}
+
+	#[test]
+	fn matches_names_newest_first() {
+		let bookmarks = Bookmarks(vec![
+			Bookmark::Channel("mockstreamer".to_owned()),
+			Bookmark::Game("Minecraft".to_owned()),
+			Bookmark::Channel("quietstreamer".to_owned()),
+		]);
+
+		assert_eq!(
+			bookmarks.matching("STREAMER").collect::<Vec<_>>(),
+			[
+				&Bookmark::Channel("quietstreamer".to_owned()),
+				&Bookmark::Channel("mockstreamer".to_owned()),
+			]
+		);
+	}
+}
@@ -0,0 +1,168 @@
+//! Command line argument parsing.
+
+use std::env;
+use std::path::PathBuf;
+use std::process::exit;
+
+use crate::config::QUALITIES;
+use crate::fixtures::Mode;
+use crate::structs::HomePage;
+
+/// Shown for `--help` and after argument errors
+const USAGE: &str = "\
+Usage: twitch-tui [OPTIONS] [CHANNEL]
+
+Arguments:
+  [CHANNEL]              Play this channel straight away, then exit
+
+Options:
+  -c, --config <PATH>    Read the config from this file
+  -g, --game <NAME>      Start on this category
+  -s, --search <QUERY>   Start on the results of this search
+  -q, --quality <QUAL>   Quality to use, can be given more than once (i.e. `-q 720p60 -q best`)
+      --chat-only        Only show chat for streams, don't start the player
+      --home <PAGE>      Home page to use instead of the config's, one of `personal`, `shelves`,
+                         `following`, `directory`, `game:<NAME>` or `search:<QUERY>`
+      --login            Log in to twitch, then exit
+      --logout           Log out of twitch, then exit
+      --record <DIR>     Save every response to fixture files in this directory
+      --replay <DIR>     Serve responses from fixture files in this directory, without the network
+  -h, --help             Print this message
+  -V, --version          Print the version";
+
+/// Parsed command line arguments
+#[derive(Default)]
+pub struct Args {
+	/// Config file path, overrides the default
+	pub config: Option<PathBuf>,
+	/// Channel login to play directly
+	pub channel: Option<String>,
+	/// Category to start on
+	pub game: Option<String>,
+	/// Search to start on
+	pub search: Option<String>,
+	/// Qualities to seed the quality list with, in priority order
+	pub quality: Vec<String>,
+	/// Don't launch the player for streams
+	pub chat_only: bool,
+	/// Overrides `home_page` from the config
+	pub home: Option<HomePage>,
+	/// Record or replay responses
+	pub fixtures: Option<Mode>,
+	/// Log in instead of starting the UI
+	pub login: bool,
+	/// Log out instead of starting the UI
+	pub logout: bool,
+}
+impl Args {
+	/// Parses the program's arguments, exiting with a usage message if they're invalid.
+	pub fn parse() -> Self {
+		match Self::try_parse(env::args().skip(1)) {
+			Ok(args) => args,
+			Err(e) => {
+				eprintln!("{e}\n\n{USAGE}");
+				exit(2)
+			}
+		}
+	}
+
+	/// Parses the given arguments (without the program name).
+	fn try_parse<I: Iterator<Item = String>>(mut iter: I) -> Result<Self, String> {
+		let mut args = Args::default();
+
+		while let Some(arg) = iter.next() {
+			// Allow `--flag=value` as well as `--flag value`
+			let (flag, mut inline_value) = match arg.split_once('=') {
+				Some((flag, value)) if flag.starts_with("--") => {
+					(flag.to_owned(), Some(value.to_owned()))
+				}
+				_ => (arg, None),
+			};
+
+			// Gets the value for the current flag
+			let mut value = || {
+				inline_value
+					.take()
+					.or_else(|| iter.next())
+					.ok_or_else(|| [&flag, " needs a value"].concat())
+			};
+
+			match &*flag {
+				"-c" | "--config" => args.config = Some(value()?.into()),
+				"-g" | "--game" => args.game = Some(value()?),
+				"-s" | "--search" => args.search = Some(value()?),
+				"-q" | "--quality" => {
+					let quality = value()?.to_lowercase();
+
+					if !QUALITIES.contains(&&*quality) {
+						return Err(["Unknown quality: ", &quality].concat());
+					}
+
+					args.quality.push(quality)
+				}
+				"--chat-only" => {
+					if cfg!(feature = "chat") {
+						args.chat_only = true
+					} else {
+						return Err("--chat-only needs the `chat` feature".to_owned());
+					}
+				}
+				"--home" => args.home = Some(parse_home_page(&value()?)?),
+				"--login" => args.login = true,
+				"--logout" => args.logout = true,
+				"--record" | "--replay" if args.fixtures.is_some() => {
+					return Err("Only one of --record and --replay can be given".to_owned())
+				}
+				"--record" => args.fixtures = Some(Mode::Record(value()?.into())),
+				"--replay" => args.fixtures = Some(Mode::Replay(value()?.into())),
+				"-h" | "--help" => {
+					println!("{USAGE}");
+					exit(0)
+				}
+				"-V" | "--version" => {
+					println!("twitch-tui {}", env!("CARGO_PKG_VERSION"));
+					exit(0)
+				}
+				_ if flag.starts_with('-') => return Err(["Unknown option: ", &flag].concat()),
+				_ if args.channel.is_none() => args.channel = Some(flag.to_lowercase()),
+				_ => return Err(["Unexpected argument: ", &flag].concat()),
+			}
+
+			if inline_value.is_some() {
+				return Err([&flag, " doesn't take a value"].concat());
+			}
+		}
+
+		if args.login && args.logout {
+			return Err("Only one of --login and --logout can be given".to_owned());
+		}
+
+		// These all decide where we start, so only one makes sense
+		if [
+			args.channel.is_some(),
+			args.game.is_some(),
+			args.search.is_some(),
+		]
+		.into_iter()
+		.filter(|&x| x)
+		.count() > 1
+		{
+			return Err("Only one of CHANNEL, --game and --search can be given".to_owned());
+		}
+
+		Ok(args)
+	}
+}
+
+/// Parses a `--home` value.
+fn parse_home_page(string: &str) -> Result<HomePage, String> {
+	match string.split_once(':') {
+		Some(("game", name)) if !name.is_empty() => Ok(HomePage::Game(name.to_owned())),
+		Some(("search", query)) if !query.is_empty() => Ok(HomePage::Search(query.to_owned())),
+		None if string == "personal" => Ok(HomePage::PersonalSection),
+		None if string == "shelves" => Ok(HomePage::Shelves),
+		None if string == "following" => Ok(HomePage::Following),
+		None if string == "directory" => Ok(HomePage::Directory),
+		_ => Err(["Unknown home page: ", string].concat()),
+	}
+}
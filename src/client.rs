@@ -0,0 +1,765 @@
+//! [`Client`], which sends requests to twitch and returns [`crate::models`].
+
+use curl::easy::{Easy, List};
+
+use crate::error::{Error, ErrorKind};
+use crate::gql;
+use crate::models::{
+	Category, Channel, ChannelOverview, Clip, ClipQuality, Page, SearchResults, SearchSuggestion,
+	Shelf, Stream, Video,
+};
+use crate::options::{
+	ClipPeriod, ClipSort, ClipSource, PersonalSectionType, SearchIndex, StreamOptions, VideoSort,
+	VideoType,
+};
+use crate::request::{execute, execute_batch, Operation};
+
+/// Twitch's GQL API
+pub const GQL: &str = "https://gql.twitch.tv/gql";
+/// Where stream and VOD playlists come from
+pub const USHER: &str = "https://usher.ttvnw.net";
+/// The webapp's client ID, which works without logging in
+pub const WEB_CLIENT_ID: &str = "kimne78kx3ncx6brgo4mv6wki5h1ko";
+
+/// How to make a [`Client`]
+pub struct ClientOptions {
+	/// GQL API URL
+	pub gql: String,
+	/// Playlist server URL, without a trailing slash
+	pub usher: String,
+	/// HTTP headers for GQL requests, in the form `Name:value`. Twitch needs a `Client-Id`.
+	pub headers: Vec<String>,
+	/// OAuth token to make requests as someone, it needs to be for the `Client-Id`
+	pub token: Option<String>,
+	/// How many streams to get per page in a category, at most 100
+	pub page_size: u32,
+	/// How many videos to get per page of a channel's videos, at most 100
+	pub video_page_size: u32,
+	/// How many clips to get per page of clips, at most 100
+	pub clip_page_size: u32,
+}
+impl Default for ClientOptions {
+	fn default() -> Self {
+		Self {
+			gql: GQL.to_owned(),
+			usher: USHER.to_owned(),
+			headers: vec![
+				["Client-Id:", WEB_CLIENT_ID].concat(),
+				// Required for some requests, it can be anything
+				"X-Device-Id:A".to_owned(),
+			],
+			token: None,
+			page_size: 30,
+			video_page_size: 30,
+			clip_page_size: 30,
+		}
+	}
+}
+impl ClientOptions {
+	/// A handle for GQL requests to `gql`, with the headers and token set. [`Client`] sends
+	/// everything on one of these.
+	pub fn gql_handle(&self) -> Result<Easy, Error> {
+		let curl_error = |e| Error::new("Creating a client", ErrorKind::Curl(e));
+
+		let mut easy = Easy::new();
+		easy.url(&self.gql).map_err(curl_error)?;
+		easy.post(true).map_err(curl_error)?;
+
+		let mut list = List::new();
+		for header in &self.headers {
+			list.append(header).map_err(curl_error)?;
+		}
+		if let Some(token) = &self.token {
+			list.append(&["Authorization:OAuth ", token].concat())
+				.map_err(curl_error)?;
+		}
+		easy.http_headers(list).map_err(curl_error)?;
+
+		Ok(easy)
+	}
+}
+
+/// A connection to twitch. Requests are sent one at a time on the same connection.
+pub struct Client {
+	/// Handle for GQL requests, with the headers set
+	easy: Easy,
+	usher: String,
+	/// Did we get a token? Some requests need one.
+	logged_in: bool,
+	page_size: u32,
+	video_page_size: u32,
+	clip_page_size: u32,
+}
+impl Client {
+	pub fn new(options: ClientOptions) -> Result<Self, Error> {
+		Ok(Self {
+			easy: options.gql_handle()?,
+			usher: options.usher,
+			logged_in: options.token.is_some(),
+			page_size: options.page_size,
+			video_page_size: options.video_page_size,
+			clip_page_size: options.clip_page_size,
+		})
+	}
+
+	/// The handle requests are sent on, i.e. to watch their progress. Changing it's URL or headers
+	/// will break the client.
+	pub fn handle(&mut self) -> &mut Easy {
+		&mut self.easy
+	}
+
+	/// The sections on the left of the webapp. `context_channel` is needed for
+	/// [`PersonalSectionType::SimilarSection`], which is channels similar to it.
+	pub fn personal_sections(
+		&mut self,
+		sections: &[PersonalSectionType],
+		context_channel: Option<&str>,
+	) -> Result<Vec<Shelf>, Error> {
+		execute::<gql::PersonalSections>(
+			&mut self.easy,
+			&gql::PersonalSectionsVariables {
+				input: gql::PersonalSectionsInput {
+					sectionInputs: sections.iter().map(|&section| section.into()).collect(),
+					recommendationContext: Default::default(),
+					contextChannelName: context_channel.map(str::to_owned),
+				},
+				creatorAnniversariesExperimentEnabled: false,
+			},
+		)
+		.map(|data| data.personalSections.into_iter().map(Shelf::from).collect())
+	}
+
+	/// The webapp's home page, with streams, categories and clips.
+	pub fn shelves(&mut self) -> Result<Vec<Shelf>, Error> {
+		execute::<gql::Shelves>(&mut self.easy, &Default::default()).map(|data| {
+			data.shelves
+				.edges
+				.into_iter()
+				.map(|edge| Shelf::from(edge.node))
+				.collect()
+		})
+	}
+
+	/// Searches for channels, categories and videos.
+	pub fn search(&mut self, query: &str) -> Result<SearchResults, Error> {
+		self.search_in(query, &[], None)
+	}
+
+	/// Searches for only some kinds of results, or all of them if `indexes` is empty. `cursor` is
+	/// a [`crate::SearchSection::cursor`], to get more of that section (it's `indexes` should
+	/// only have that section's kind).
+	pub fn search_in(
+		&mut self,
+		query: &str,
+		indexes: &[SearchIndex],
+		cursor: Option<&str>,
+	) -> Result<SearchResults, Error> {
+		execute::<gql::SearchResultsPage_SearchResults>(
+			&mut self.easy,
+			&gql::SearchResultsVariables {
+				query: query.to_owned(),
+				options: (!indexes.is_empty()).then(|| {
+					gql::SearchResultsPage_SearchResultsOptions {
+						targets: Some(
+							indexes
+								.iter()
+								.map(|&index| gql::Target {
+									index: index.into(),
+									cursor: cursor.map(str::to_owned),
+								})
+								.collect(),
+						),
+					}
+				}),
+				requestID: None,
+			},
+		)
+		.map(|data| data.searchFor.into())
+	}
+
+	/// Live streams in a category. `name` is [`crate::Category::name`], and `cursor` is the last
+	/// page's [`Page::cursor`] to get the next one.
+	pub fn category_streams(
+		&mut self,
+		name: &str,
+		options: &StreamOptions,
+		cursor: Option<&str>,
+	) -> Result<Page<Stream>, Error> {
+		execute::<gql::DirectoryPage_Game>(
+			&mut self.easy,
+			&gql::DirectoryPage_GameVariables {
+				// Needs to be `Some` to get colour
+				imageWidth: Some(0),
+				name: name.to_owned(),
+				options: gql::DirectoryPage_GameOptions {
+					sort: options.sort.into(),
+					recommendationsContext: None,
+					requestID: None,
+					freeformTags: Some(options.tags.clone()).filter(|tags| !tags.is_empty()),
+					tags: Some(options.tag_ids.clone()).filter(|tags| !tags.is_empty()),
+					broadcasterLanguages: Some(options.languages.clone())
+						.filter(|languages| !languages.is_empty()),
+				},
+				sortTypeIsRecency: true,
+				limit: self.page_size,
+				cursor: cursor.map(str::to_owned),
+			},
+		)?
+		.game
+		.ok_or_else(|| {
+			Error::new(
+				gql::DirectoryPage_Game::OPERATION_NAME,
+				ErrorKind::Other(["There's no category called ", name].concat()),
+			)
+		})
+		.map(|category| category.streams.into())
+	}
+
+	/// Every channel the logged in user follows. All of them are loaded, so live ones can be
+	/// found.
+	pub fn followed_channels(&mut self) -> Result<Vec<Channel>, Error> {
+		let not_logged_in = || {
+			Error::new(
+				gql::FollowedChannels::OPERATION_NAME,
+				ErrorKind::Other("Log in with `--login` to see followed channels".to_owned()),
+			)
+		};
+
+		if !self.logged_in {
+			return Err(not_logged_in());
+		}
+
+		let mut channels = Vec::new();
+		let mut cursor = None;
+
+		// Keep going until we have all of them
+		loop {
+			let follows = execute::<gql::FollowedChannels>(
+				&mut self.easy,
+				&gql::FollowedChannelsVariables {
+					// The most twitch allows
+					first: 100,
+					cursor,
+				},
+			)?
+			.currentUser
+			.ok_or_else(not_logged_in)?
+			.follows;
+
+			cursor = follows
+				.edges
+				.last()
+				.and_then(|edge| edge.cursor.clone())
+				.filter(|_| follows.pageInfo.hasNextPage);
+
+			channels.extend(
+				follows
+					.edges
+					.into_iter()
+					.map(|edge| Channel::from(edge.node)),
+			);
+
+			if cursor.is_none() {
+				return Ok(channels);
+			}
+		}
+	}
+
+	/// A channel's page: their info, first few videos of each type, most viewed clips and
+	/// schedule. These are sent in one batch.
+	pub fn channel(&mut self, login: &str) -> Result<ChannelOverview, Error> {
+		let page = gql::ChannelPageVariables {
+			login: login.to_owned(),
+			first: 10,
+		};
+		let clips = gql::ClipsPageVariables {
+			first: page.first,
+			..clips_variables(
+				&ClipSource::Channel(login.to_owned()),
+				ClipPeriod::AllTime,
+				ClipSort::Views,
+				None,
+			)
+		};
+		let schedule = gql::ChannelPageVariables {
+			login: login.to_owned(),
+			first: page.first,
+		};
+
+		let (page, clips, schedule) = execute_batch::<(
+			gql::ChannelPage,
+			gql::ClipsPage,
+			gql::ChannelSchedule,
+		)>(&mut self.easy, &(page, clips, schedule))?;
+
+		let user = page.user.ok_or_else(|| {
+			Error::new(
+				gql::ChannelPage::OPERATION_NAME,
+				ErrorKind::Other(["There's no channel called ", login].concat()),
+			)
+		})?;
+
+		let channel = Channel {
+			description: user
+				.description
+				.filter(|description| !description.is_empty()),
+			followers: Some(user.followers.totalCount),
+			schedule: schedule
+				.user
+				.and_then(|user| user.channel.schedule)
+				.and_then(|schedule| schedule.segments)
+				.unwrap_or_default()
+				.into_iter()
+				.map(Into::into)
+				.collect(),
+			..Channel::from(user.user)
+		};
+
+		Ok(ChannelOverview {
+			channel,
+			archives: Page::from(user.archives).items,
+			highlights: Page::from(user.highlights).items,
+			uploads: Page::from(user.uploads).items,
+			clips: clips
+				.user
+				.map(|owner| Page::from(owner.clips).items)
+				.unwrap_or_default(),
+		})
+	}
+
+	/// A channel's videos of one type. `login` is [`crate::Channel::login`], and `cursor` is the
+	/// last page's [`Page::cursor`] to get the next one.
+	pub fn channel_videos(
+		&mut self,
+		login: &str,
+		kind: VideoType,
+		sort: VideoSort,
+		cursor: Option<&str>,
+	) -> Result<Page<Video>, Error> {
+		execute::<gql::ChannelVideos>(
+			&mut self.easy,
+			&gql::ChannelVideosVariables {
+				login: login.to_owned(),
+				first: self.video_page_size,
+				broadcastType: kind.into(),
+				sort: sort.into(),
+				cursor: cursor.map(str::to_owned),
+			},
+		)?
+		.user
+		.ok_or_else(|| {
+			Error::new(
+				gql::ChannelVideos::OPERATION_NAME,
+				ErrorKind::Other(["There's no channel called ", login].concat()),
+			)
+		})
+		.map(|user| user.videos.into())
+	}
+
+	/// A channel's most viewed clips from the last week. `login` is [`crate::Channel::login`], and
+	/// `cursor` is the last page's [`Page::cursor`] to get the next one.
+	pub fn channel_clips(
+		&mut self,
+		login: &str,
+		cursor: Option<&str>,
+	) -> Result<Page<Clip>, Error> {
+		self.clips(
+			&ClipSource::Channel(login.to_owned()),
+			ClipPeriod::Week,
+			ClipSort::Views,
+			cursor,
+		)
+	}
+
+	/// The most viewed clips from the last week in a category. `name` is
+	/// [`crate::Category::name`], and `cursor` is the last page's [`Page::cursor`] to get the next
+	/// one.
+	pub fn category_clips(
+		&mut self,
+		name: &str,
+		cursor: Option<&str>,
+	) -> Result<Page<Clip>, Error> {
+		self.clips(
+			&ClipSource::Category(name.to_owned()),
+			ClipPeriod::Week,
+			ClipSort::Views,
+			cursor,
+		)
+	}
+
+	/// A channel's or category's clips. `cursor` is the last page's [`Page::cursor`] to get the
+	/// next one.
+	pub fn clips(
+		&mut self,
+		source: &ClipSource,
+		period: ClipPeriod,
+		sort: ClipSort,
+		cursor: Option<&str>,
+	) -> Result<Page<Clip>, Error> {
+		let data = execute::<gql::ClipsPage>(
+			&mut self.easy,
+			&gql::ClipsPageVariables {
+				first: self.clip_page_size,
+				..clips_variables(source, period, sort, cursor)
+			},
+		)?;
+
+		let missing = match source {
+			ClipSource::Channel(_) => "There's no channel called ",
+			ClipSource::Category(_) => "There's no category called ",
+		};
+
+		// Only the one we asked for is returned
+		data.user
+			.or(data.game)
+			.ok_or_else(|| {
+				Error::new(
+					gql::ClipsPage::OPERATION_NAME,
+					ErrorKind::Other([missing, source.name()].concat()),
+				)
+			})
+			.map(|owner| owner.clips.into())
+	}
+
+	/// Categories with the most viewers, only ones with all of `tags` if it isn't empty. `cursor`
+	/// is the last page's [`Page::cursor`] to get the next one.
+	pub fn top_categories(
+		&mut self,
+		tags: &[String],
+		cursor: Option<&str>,
+	) -> Result<Page<Category>, Error> {
+		execute::<gql::TopCategories>(
+			&mut self.easy,
+			&gql::TopCategoriesVariables {
+				first: 30,
+				tags: Some(tags.to_vec()).filter(|tags| !tags.is_empty()),
+				cursor: cursor.map(str::to_owned),
+			},
+		)
+		.map(|data| data.games.into())
+	}
+
+	/// What twitch suggests searching for, given what's been typed so far.
+	pub fn search_suggestions(&mut self, fragment: &str) -> Result<Vec<SearchSuggestion>, Error> {
+		execute::<gql::SearchSuggestions>(
+			&mut self.easy,
+			&gql::SearchSuggestionsVariables {
+				queryFragment: fragment.to_owned(),
+			},
+		)
+		.map(|data| {
+			data.searchSuggestions
+				.map(|suggestions| suggestions.edges)
+				.unwrap_or_default()
+				.into_iter()
+				.map(|edge| SearchSuggestion::from(edge.node))
+				.collect()
+		})
+	}
+
+	/// URL of a channel's live stream master playlist. Fails if they aren't live.
+	pub fn stream_playback_url(&mut self, login: &str) -> Result<String, Error> {
+		let token = self
+			.playback_access_token(login, "")?
+			.streamPlaybackAccessToken
+			.ok_or_else(|| no_token(login))?;
+
+		Ok([
+			&self.usher,
+			"/api/channel/hls/",
+			login,
+			".m3u8?allow_source=true&sig=",
+			&token.signature,
+			"&token=",
+			&self.easy.url_encode(token.value.as_bytes()),
+		]
+		.concat())
+	}
+
+	/// URL of a VOD's master playlist.
+	pub fn video_playback_url(&mut self, id: &str) -> Result<String, Error> {
+		let token = self
+			.playback_access_token("", id)?
+			.videoPlaybackAccessToken
+			.ok_or_else(|| no_token(id))?;
+
+		Ok([
+			&self.usher,
+			"/vod/",
+			id,
+			".m3u8?allow_source=true&sig=",
+			&token.signature,
+			"&token=",
+			&self.easy.url_encode(token.value.as_bytes()),
+		]
+		.concat())
+	}
+
+	/// URL of a clip's video, at the best quality.
+	pub fn clip_playback_url(&mut self, slug: &str) -> Result<String, Error> {
+		self.clip_qualities(slug)?
+			.into_iter()
+			.next()
+			.map(|quality| quality.url)
+			.ok_or_else(|| {
+				Error::new(
+					gql::VideoAccessToken_Clip::OPERATION_NAME,
+					ErrorKind::Other("Clip has no qualities".to_owned()),
+				)
+			})
+	}
+
+	/// Every quality of a clip's video, best first.
+	pub fn clip_qualities(&mut self, slug: &str) -> Result<Vec<ClipQuality>, Error> {
+		let clip = execute::<gql::VideoAccessToken_Clip>(
+			&mut self.easy,
+			&gql::VideoAccessToken_ClipVariables {
+				slug: slug.to_owned(),
+			},
+		)?
+		.clip
+		.ok_or_else(|| {
+			Error::new(
+				gql::VideoAccessToken_Clip::OPERATION_NAME,
+				ErrorKind::Other(["There's no clip called ", slug].concat()),
+			)
+		})?;
+
+		let token = clip.playbackAccessToken;
+
+		Ok(clip
+			.videoQualities
+			.into_iter()
+			.map(|quality| ClipQuality {
+				quality: quality.quality,
+				url: [
+					&quality.sourceURL,
+					"?sig=",
+					&token.signature,
+					"&token=",
+					// It's already urlencoded once, but needs to be again. Luckily we just need to
+					// replace `%`s
+					&token.value.replace('%', "%25"),
+				]
+				.concat(),
+			})
+			.collect())
+	}
+
+	/// Gets the token for a stream (if `login` isn't empty) or a VOD (if `vod_id` isn't empty).
+	fn playback_access_token(
+		&mut self,
+		login: &str,
+		vod_id: &str,
+	) -> Result<gql::PlaybackAccessTokenData, Error> {
+		execute::<gql::PlaybackAccessToken>(
+			&mut self.easy,
+			&gql::PlaybackAccessTokenVariables {
+				isLive: !login.is_empty(),
+				isVod: !vod_id.is_empty(),
+				login: login.to_owned(),
+				playerType: "",
+				vodID: vod_id.to_owned(),
+			},
+		)
+	}
+}
+
+/// Variables to get `source`'s clips, with the page size left at `0`
+fn clips_variables(
+	source: &ClipSource,
+	period: ClipPeriod,
+	sort: ClipSort,
+	cursor: Option<&str>,
+) -> gql::ClipsPageVariables {
+	let (login, name) = match source {
+		ClipSource::Channel(login) => (login.clone(), String::new()),
+		ClipSource::Category(name) => (String::new(), name.clone()),
+	};
+
+	gql::ClipsPageVariables {
+		login,
+		name,
+		isChannel: matches!(source, ClipSource::Channel(_)),
+		first: 0,
+		period: period.into(),
+		sort: sort.into(),
+		cursor: cursor.map(str::to_owned),
+	}
+}
+
+/// Error for when twitch doesn't give us a playback token, i.e. when a channel isn't live
+fn no_token(what: &str) -> Error {
+	Error::new(
+		gql::PlaybackAccessToken::OPERATION_NAME,
+		ErrorKind::Other(["Couldn't get a playback token for ", what].concat()),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
+
+	use super::*;
+	use crate::fixtures::{with_mode, Mode};
+	use crate::models::ShelfItem;
+
+	/// Runs `f` with a client that replays the recordings in `tests/fixtures`.
+	fn replay<T, F: FnOnce(&mut Client) -> Result<T, Error>>(f: F) -> Result<T, Error> {
+		let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+		let mut client =
+			Client::new(ClientOptions::default()).expect("Should be able to make a client");
+
+		with_mode(Mode::Replay(dir), || f(&mut client))
+	}
+
+	#[test]
+	fn replays_category_streams() {
+		let page = replay(|client| {
+			client.category_streams("Just Chatting", &StreamOptions::default(), None)
+		})
+		.expect("Should have a recording that parses");
+
+		assert_eq!(page.items.len(), 10);
+		// There's another page after the last stream
+		assert_eq!(page.cursor.as_deref(), Some("10"));
+
+		let stream = &page.items[0];
+		assert_eq!(stream.channel.login, "mockstreamer1");
+		assert_eq!(stream.channel.colour.as_deref(), Some("9146FF"));
+		assert!(stream.channel.partner);
+		assert_eq!(stream.tags, ["Testing"]);
+		assert!(stream.started_at.is_some());
+		assert_eq!(
+			stream
+				.category
+				.as_ref()
+				.map(|category| category.name.as_str()),
+			Some("Just Chatting")
+		);
+	}
+
+	#[test]
+	fn streams_without_broadcaster_or_game() {
+		let page =
+			replay(|client| client.category_streams("Mystery", &StreamOptions::default(), None))
+				.expect("Should parse streams without a broadcaster or game");
+
+		assert_eq!(page.items.len(), 1);
+		assert_eq!(page.cursor, None);
+
+		let stream = &page.items[0];
+		assert_eq!(stream.title, "Who's streaming?");
+		assert_eq!(stream.channel.login, "");
+		assert!(stream.category.is_none());
+	}
+
+	#[test]
+	fn missing_category_is_an_error() {
+		let Err(error) =
+			replay(|client| client.category_streams("Nowhere", &StreamOptions::default(), None))
+		else {
+			panic!("A `null` category should be an error");
+		};
+
+		assert_eq!(error.operation, "DirectoryPage_Game");
+		assert!(matches!(
+			error.kind,
+			ErrorKind::Other(message) if message == "There's no category called Nowhere"
+		));
+	}
+
+	#[test]
+	fn replays_search() {
+		let results = replay(|client| client.search("mock")).expect("Should have a recording");
+
+		let logins: Vec<_> = results
+			.channels
+			.items
+			.iter()
+			.map(|channel| channel.login.as_str())
+			.collect();
+		assert_eq!(logins, ["mockstreamer", "quietstreamer"]);
+		assert_eq!(results.channels.total, Some(2));
+
+		// The stream is from the channel it's on
+		let channel = &results.channels.items[0];
+		let stream = channel.stream.as_ref().expect("They should be live");
+		assert_eq!(stream.channel.login, "mockstreamer");
+		assert_eq!(stream.title, "Testing the mock server");
+		assert_eq!(channel.schedule[0].title, "Tomorrow's stream");
+		assert_eq!(channel.schedule[0].ends_at, None);
+		assert_eq!(
+			channel
+				.latest_video
+				.as_ref()
+				.map(|video| video.length_seconds),
+			Some(5025)
+		);
+		assert_eq!(
+			channel.top_clip.as_ref().map(|clip| clip.slug.as_str()),
+			Some("MockClipSlug")
+		);
+		assert!(results.channels.items[1].stream.is_none());
+
+		assert_eq!(results.categories.items[0].tags, ["IRL"]);
+		assert_eq!(results.videos.items[0].id, "6001");
+		assert_eq!(results.related_streams.score, 4);
+		assert_eq!(
+			results.related_streams.items[0].channel.login,
+			"quietstreamer"
+		);
+	}
+
+	#[test]
+	fn replays_shelves() {
+		let shelves = replay(|client| client.shelves()).expect("Should have a recording");
+
+		assert!(shelves.iter().all(|shelf| !shelf.title.is_empty()));
+		assert!(shelves
+			.iter()
+			.flat_map(|shelf| &shelf.items)
+			.any(|item| matches!(
+				item,
+				ShelfItem::Clip(clip) if clip.slug == "MockClipSlug"
+					&& clip.curator.as_ref().is_some_and(|curator| curator.login == "clipper")
+			)));
+
+		let sections = replay(|client| {
+			client.personal_sections(&[PersonalSectionType::RecommendedSection], None)
+		})
+		.expect("Should have a recording");
+
+		let logins: Vec<_> = sections
+			.iter()
+			.flat_map(|section| &section.items)
+			.filter_map(|item| match item {
+				ShelfItem::Stream(stream) => Some(stream.channel.login.as_str()),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(logins, ["mockstreamer", "quietstreamer"]);
+	}
+
+	#[test]
+	fn builds_usher_urls() {
+		let (stream, video) = replay(|client| {
+			let results = client.search("mock")?;
+
+			Ok((
+				results.channels.items[0].playback_url(client)?,
+				results.videos.items[0].playback_url(client)?,
+			))
+		})
+		.expect("Should have recorded tokens");
+
+		assert_eq!(
+			stream,
+			"https://usher.ttvnw.net/api/channel/hls/mockstreamer.m3u8?allow_source=true&sig=streamsignature&token=%7B%22channel%22%3A%22mockstreamer%22%7D"
+		);
+		assert_eq!(
+			video,
+			"https://usher.ttvnw.net/vod/6001.m3u8?allow_source=true&sig=vodsignature&token=%7B%22vod_id%22%3A%226001%22%7D"
+		);
+	}
+}
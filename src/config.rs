@@ -1,293 +1,719 @@
+//! Runtime configuration.
+//!
+//! Settings are read from a JSON file on startup, by default
+//! `$XDG_CONFIG_HOME/twitch-tui/config.json` (or `~/.config/twitch-tui/config.json`). Every key is
+//! optional, anything that's missing uses the default value shown in [`Config::default`], so an
+//! empty object (`{}`) is a valid config file.
+//!
+//! Once loaded, the config can be accessed from anywhere with [`config()`].
+
 // For some enum variants
 #![allow(dead_code)]
-// Some of the `Default`s invoke this
-#![allow(clippy::derivable_impls)]
 
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::{env, fs, io};
+
+use chrono::format::{Item, StrftimeItems};
 use ratatui::layout::Alignment;
 use ratatui::widgets::BorderType;
+use serde::Deserialize;
+use simd_json::from_slice;
+use twitch_curses::client;
+use twitch_curses::options::{
+	ClipPeriod, ClipSort, PersonalSectionType, SearchIndex, StreamOptions, StreamSort, VideoSort,
+	VideoType,
+};
 
-use crate::structs::*;
-
-/// Program and args used to play videos and streams
-pub const PLAYER: &[&str] = &["ffplay", "-autoexit"];
-
-/// Quality of the streams/videos played, first item is prioritised.
-/// The first item can be changed at runtime with +/-.
-/// Case-insensitive (lower case) for clips and VODs.
-/// If there are no items it will default to `best`.
-/// Should be one of: audio_only, worst, 160p, 360p, 480p, 720p, 720p60, 936p60, 1080p60, best
-pub const QUALITY: &[&str] = &["best"];
-
-/// HTTP headers for requests.
-pub const HEADERS: &[&str] = &[
-	// This is required, this ID is from the webapp
-	"Client-Id:kimne78kx3ncx6brgo4mv6wki5h1ko",
-	// This header is required for some requests, it can be anything
-	"X-Device-Id:A",
-	// The language-locale for recommendations and title localization
-	// You can find more info
-	// [on mozilla's docs](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Accept-Language)
-	"Accept-Language:en",
-	// You can add more, but they probably won't have any effect
-];
+use crate::keys::KeyConfig;
+use crate::structs::HomePage;
+use crate::theme::{Theme, ThemeConfig};
+
+/// The loaded config, set by [`set`].
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// Get the current config. If [`set`] hasn't been called, this is the default config.
+pub fn config() -> &'static Config {
+	CONFIG.get_or_init(Config::default)
+}
+
+/// The default config, pointed at mock servers started with `options`. For tests, which pass it
+/// to whatever they're testing instead of [`set`]ting it, so each can choose how the servers
+/// behave.
+#[cfg(test)]
+pub fn mock(options: twitch_curses::mock::Options) -> Config {
+	let env = twitch_curses::mock::start(options);
+
+	let mut config = Config::default();
+	config
+		.endpoints
+		.apply_env(|var| {
+			env.iter()
+				.find(|(name, _)| *name == var)
+				.map(|(_, value)| value.clone())
+		})
+		.expect("The mock server's endpoints should be valid");
 
-/// Show download progress?
-pub const DOWNLOAD_PROGRESS: bool = true;
+	config
+}
+
+/// Sets the config returned by [`config()`]. This only works once, and has to be called before
+/// anything reads the config.
+pub fn set(config: Config) {
+	let _ = CONFIG.set(config);
+}
+
+/// Where the config file is read from if `--config` isn't passed.
+pub fn default_path() -> Option<PathBuf> {
+	env::var_os("XDG_CONFIG_HOME")
+		.filter(|dir| !dir.is_empty())
+		.map(PathBuf::from)
+		.or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+		.map(|dir| dir.join("twitch-tui").join("config.json"))
+}
 
-/// The request used for the home page.
-/// Usually either `Shelves` (the main home page) or `PersonalSection` (The bit on the left on the
-/// webapp). It could also be a category (`Game("Just Chatting")`) or a search (`Search("Lol")`).
+/// Reads, parses and validates the config file, then applies endpoints from the environment (see
+/// [`Endpoints::read_env`]).
 ///
-/// I recommend setting this to `PersonalSection` if you don't usually use the home page or you
-/// want quicker load times, since it's only ~9kb, and `Shelves` is ~1mb (~100x larger).
-pub const HOME_PAGE: HomePage = HomePage::PersonalSection;
+/// If `path` is `None` the default path is used, and it's fine if it doesn't exist. If the user
+/// explicitly gave us a path, it has to exist.
+pub fn load(path: Option<&Path>) -> Result<Config, ConfigError> {
+	let mut config = read(path)?;
 
-/// How to display dates.
-/// `None` means to show a relative date (i.e. "18 hours ago"),
-/// You can use i.e. `Some("%c")` to show an absolute date with the specified format.
-/// You can see documentation for this formatting [here](https://docs.rs/chrono/latest/chrono/format/strftime/index.html)
-pub const DATE_FORMAT: Option<&str> = None;
+	config.endpoints.read_env().map_err(ConfigError::Env)?;
 
-// ----------------
-// The following settings are for the program's style.
-// ----------------
+	Ok(config)
+}
 
-/// Where the title is at the top of the screen.
-/// Can be `Left`, `Center` or `Right`.
-pub const TITLE_ALIGNMENT: Alignment = Alignment::Left;
+/// Reads, parses and validates the config file.
+fn read(path: Option<&Path>) -> Result<Config, ConfigError> {
+	let (path, required) = match path {
+		Some(path) => (path.to_owned(), true),
+		None => match default_path() {
+			Some(path) => (path, false),
+			// Nowhere to look, just use the defaults
+			None => return Ok(Config::default()),
+		},
+	};
 
-/// The style of the UI's borders.
-/// Can be `Plain`, `Thick`, `Double` or `Rounded`.
-pub const BORDER_TYPE: BorderType = BorderType::Plain;
+	let mut bytes = match fs::read(&path) {
+		Ok(bytes) => bytes,
+		Err(e) if !required && e.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+		Err(e) => return Err(ConfigError::Io(path, e)),
+	};
 
-// ----------------
-// The following settings are for API request options, changing some of these could cause the
-// server to return errors, which may cause this program to panic. Edit them at your own risk.
-// ----------------
+	let config = from_slice::<Config>(&mut bytes)
+		.map_err(|e| ConfigError::Parse(path.clone(), e.to_string()))?;
 
-// These 3 are for the reccommended section (The column on the left on the twitch website), which
-// isn't fetched by default. You can set it above.
+	config
+		.validate()
+		.map_err(|message| ConfigError::Invalid(path, message))?;
 
-impl Default for RecommendationContext {
-	fn default() -> Self {
-		// Most of these fields are `Option<&str>`
-		// If you want to set one, use `Some("foo")`
-		Self {
-			platform: None,
-			clientApp: None,
-			location: None,
-			referrerDomain: None,
-			// These two are `Option<u16>`
-			// You should use `Some(69)`
-			viewportHeight: None,
-			viewportWidth: None,
-			channelName: None,
-			categoryName: None,
-			lastChannelName: None,
-			lastCategoryName: None,
-			pageviewContent: None,
-			pageviewContentType: None,
-			pageviewLocation: None,
-			pageviewMedium: None,
-			previousPageviewContent: None,
-			previousPageviewContentType: None,
-			previousPageviewLocation: None,
-			previousPageviewMedium: None,
-		}
-	}
+	Ok(config)
 }
 
-enum PersonalSectionType {
-	RecommendedSection,
-	SimilarSection,
+/// Why the config file couldn't be loaded.
+#[derive(Debug)]
+pub enum ConfigError {
+	/// The file couldn't be read
+	Io(PathBuf, io::Error),
+	/// The file isn't valid JSON, or has unknown/mistyped keys
+	Parse(PathBuf, String),
+	/// The file parsed, but has a value that doesn't make sense
+	Invalid(PathBuf, String),
+	/// An environment variable has a value that doesn't make sense
+	Env(String),
 }
-impl From<PersonalSectionType> for &str {
-	fn from(val: PersonalSectionType) -> Self {
-		match val {
-			PersonalSectionType::RecommendedSection => "RECOMMENDED_SECTION",
-			PersonalSectionType::SimilarSection => "SIMILAR_SECTION",
+impl Display for ConfigError {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			ConfigError::Io(path, e) => write!(f, "Couldn't read {}: {e}", path.display()),
+			ConfigError::Parse(path, e) => write!(f, "Couldn't parse {}: {e}", path.display()),
+			ConfigError::Invalid(path, e) => write!(f, "Invalid config in {}: {e}", path.display()),
+			ConfigError::Env(e) => write!(f, "Invalid environment variable: {e}"),
 		}
 	}
 }
 
-impl Default for PersonalSectionsInput {
+/// Every runtime setting. The JSON keys are the same as the field names.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+	/// Program and args used to play videos and streams
+	pub player: Vec<String>,
+
+	/// Quality of the streams/videos played, first item is prioritised.
+	/// The first item can be changed at runtime with +/-.
+	/// Case-insensitive (lower case) for clips and VODs.
+	/// If there are no items it will default to `best`.
+	/// Should be one of: audio_only, worst, 160p, 360p, 480p, 720p, 720p60, 936p60, 1080p60, best
+	pub quality: Vec<String>,
+
+	/// HTTP headers for requests, in the form `Name:value`.
+	pub headers: Vec<String>,
+
+	/// Show how much has been downloaded in the status bar while something loads? This is a
+	/// percentage for playlists, or the size so far for GQL requests (which don't say how long
+	/// they are).
+	pub download_progress: bool,
+
+	/// The request used for the home page.
+	/// Usually either `"Shelves"` (the main home page) or `"PersonalSection"` (The bit on the left
+	/// on the webapp). It could also be a category (`{"Game": "Just Chatting"}`), a search
+	/// (`{"Search": "Lol"}`), the channels you follow (`"Following"`, which needs you to log
+	/// in) or the top categories (`"Directory"`).
+	///
+	/// I recommend using `PersonalSection` if you don't usually use the home page or you want
+	/// quicker load times, since it's only ~9kb, and `Shelves` is ~1mb (~100x larger).
+	pub home_page: HomePage,
+
+	/// How many searches to remember for the search box, which can be recalled with the up and
+	/// down keys. `0` means they aren't saved.
+	pub search_history: usize,
+
+	/// Show twitch's suggestions under the search box while typing.
+	pub search_suggestions: bool,
+
+	/// How to display dates.
+	/// `null` means to show a relative date (i.e. "18 hours ago"),
+	/// You can use i.e. `"%c"` to show an absolute date with the specified format.
+	/// You can see documentation for this formatting [here](https://docs.rs/chrono/latest/chrono/format/strftime/index.html)
+	pub date_format: Option<String>,
+
+	/// Where the title is at the top of the screen.
+	/// Can be `"Left"`, `"Center"` or `"Right"`.
+	#[serde(with = "AlignmentDef")]
+	pub title_alignment: Alignment,
+
+	/// The style of the UI's borders.
+	/// Can be `"Plain"`, `"Thick"`, `"Double"` or `"Rounded"`.
+	#[serde(with = "BorderTypeDef")]
+	pub border_type: BorderType,
+
+	/// Colours and styles, i.e. `{"name": "light", "styles": {"highlight": {"fg": "black", "bg":
+	/// "yellow"}}}`. The style names are in `src/theme.rs`.
+	pub theme: ThemeConfig,
+
+	/// Keybindings, each action maps to a list of key sequences, i.e.
+	/// `{"browser": {"Down": ["j", "ctrl+n"], "Top": ["gg"]}, "chat": {"Quit": ["Esc"]}}`.
+	/// Actions that aren't given keep their default keys, see `src/keys.rs` for them.
+	pub keys: KeyConfig,
+
+	/// API request options, changing some of these could cause the server to return errors.
+	/// Edit them at your own risk.
+	pub requests: RequestOptions,
+
+	/// Servers to talk to instead of twitch's, i.e. `{"gql": "http://localhost:8080/gql"}`. These
+	/// can also be set with environment variables, see [`Endpoints::read_env`].
+	pub endpoints: Endpoints,
+}
+impl Default for Config {
 	fn default() -> Self {
-		// `sectionInputs` is the `Vec` of sections in the personal section
 		Self {
-			sectionInputs: vec![PersonalSectionType::RecommendedSection.into()],
-			recommendationContext: RecommendationContext::default(),
-			// Add `PersonalSectionType::SimilarSection.into()` to `sectionInputs` if you want to
-			// use this.
-			// `Some("someone")`
-			contextChannelName: None,
+			player: vec!["ffplay".to_owned(), "-autoexit".to_owned()],
+			quality: vec!["best".to_owned()],
+			headers: vec![
+				// This is required, this ID is from the webapp
+				"Client-Id:kimne78kx3ncx6brgo4mv6wki5h1ko".to_owned(),
+				// This header is required for some requests, it can be anything
+				"X-Device-Id:A".to_owned(),
+				// The language-locale for recommendations and title localization
+				// You can find more info
+				// [on mozilla's docs](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Accept-Language)
+				"Accept-Language:en".to_owned(),
+				// You can add more, but they probably won't have any effect
+			],
+			download_progress: true,
+			home_page: HomePage::PersonalSection,
+			search_history: 100,
+			search_suggestions: true,
+			date_format: None,
+			title_alignment: Alignment::Left,
+			border_type: BorderType::Plain,
+			theme: ThemeConfig::default(),
+			keys: KeyConfig::default(),
+			requests: RequestOptions::default(),
+			endpoints: Endpoints::default(),
 		}
 	}
 }
+impl Config {
+	/// Checks for values that would parse, but not work.
+	fn validate(&self) -> Result<(), String> {
+		if self.player.first().map_or(true, String::is_empty) {
+			return Err("`player` needs at least a program name".to_owned());
+		}
 
-impl Default for PersonalSectionsVariables {
-	fn default() -> Self {
-		// I have no clue what `creatorAnniversariesExperimentEnabled` is.
-		Self {
-			input: PersonalSectionsInput::default(),
-			creatorAnniversariesExperimentEnabled: false,
+		for quality in &self.quality {
+			if !QUALITIES.contains(&&*quality.to_lowercase()) {
+				return Err(["Unknown quality `", quality, "` in `quality`"].concat());
+			}
+		}
+
+		for header in &self.headers {
+			if !header.contains(':') {
+				return Err(["Header `", header, "` should be in the form `Name:value`"].concat());
+			}
 		}
-	}
-}
 
-// The next 2 are for the main home page items, enabled by default
+		if let Some(date_format) = &self.date_format {
+			if StrftimeItems::new(date_format).any(|item| matches!(item, Item::Error)) {
+				return Err(["`date_format` `", date_format, "` isn't a valid format"].concat());
+			}
+		}
 
-impl Default for ShelvesContext {
-	fn default() -> Self {
-		// If you want to use this, you'll need to set `context: Some(ShelvesContext::default())` in
-		// `ShelvesVariables`  below
-		Self {
-			clientApp: None,
-			location: None,
-			referrerDomain: None,
-			viewportHeight: None,
-			viewportWidth: None,
+		if let HomePage::Game(name) | HomePage::Search(name) = &self.home_page {
+			if name.is_empty() {
+				return Err("`home_page` needs a name".to_owned());
+			}
 		}
+
+		Theme::from_config(&self.theme)?;
+
+		self.keys.validate()?;
+
+		if !(1..=100).contains(&self.requests.game_limit) {
+			return Err("`requests.game_limit` should be between 1 and 100".to_owned());
+		}
+
+		if !(1..=100).contains(&self.requests.video_limit) {
+			return Err("`requests.video_limit` should be between 1 and 100".to_owned());
+		}
+
+		if !(1..=100).contains(&self.requests.clip_limit) {
+			return Err("`requests.clip_limit` should be between 1 and 100".to_owned());
+		}
+
+		self.endpoints.validate()
+	}
+
+	/// The `Client-Id` from `headers`
+	pub fn client_id(&self) -> Option<&str> {
+		self.headers.iter().find_map(|header| {
+			header
+				.split_once(':')
+				.filter(|(name, _)| name.eq_ignore_ascii_case("Client-Id"))
+				.map(|(_, value)| value.trim())
+		})
 	}
 }
 
-impl Default for ShelvesVariables {
+/// Where requests and connections go. Plain `http://` and `ws://` URLs (and IRC without TLS) work
+/// too, for local stand-in servers.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Endpoints {
+	/// GQL API URL
+	pub gql: String,
+	/// Where VOD playlists come from, without a trailing slash
+	pub usher: String,
+	/// IRC server for chat
+	pub irc_host: String,
+	pub irc_port: u16,
+	/// Connect to IRC with TLS?
+	pub irc_tls: bool,
+	/// PubSub websocket URL, for chat events
+	pub pubsub: String,
+	/// OAuth server, for logging in
+	pub auth: String,
+}
+impl Default for Endpoints {
 	fn default() -> Self {
-		// Examples in comments:
 		Self {
-			// `Some(69)`
-			imageWidth: None,
-			itemsPerRow: 0,
-			// `Some(true)`
-			langWeightedCCU: None,
-			platform: "",
-			requestID: "",
-			// `Some(ShelvesContext::default())`
-			context: None,
-			// `Some(true)`
-			verbose: None,
+			gql: client::GQL.to_owned(),
+			usher: client::USHER.to_owned(),
+			irc_host: "irc.chat.twitch.tv".to_owned(),
+			irc_port: 6697,
+			irc_tls: true,
+			pubsub: "wss://pubsub-edge.twitch.tv/v1".to_owned(),
+			auth: "https://id.twitch.tv/oauth2".to_owned(),
 		}
 	}
 }
+impl Endpoints {
+	/// Overrides endpoints with any of these environment variables that are set: `TWITCH_TUI_GQL`,
+	/// `TWITCH_TUI_USHER`, `TWITCH_TUI_IRC_HOST`, `TWITCH_TUI_IRC_PORT`, `TWITCH_TUI_IRC_TLS`
+	/// (`true` or `false`), `TWITCH_TUI_PUBSUB` and `TWITCH_TUI_AUTH`.
+	fn read_env(&mut self) -> Result<(), String> {
+		self.apply_env(|var| env::var(var).ok())
+	}
 
-// The next 2 are for categories, i.e. a game page
+	/// Same as [`Endpoints::read_env`], but gets variables from `var` instead of the environment.
+	fn apply_env<F: Fn(&str) -> Option<String>>(&mut self, var: F) -> Result<(), String> {
+		for (name, endpoint) in [
+			("TWITCH_TUI_GQL", &mut self.gql),
+			("TWITCH_TUI_USHER", &mut self.usher),
+			("TWITCH_TUI_IRC_HOST", &mut self.irc_host),
+			("TWITCH_TUI_PUBSUB", &mut self.pubsub),
+			("TWITCH_TUI_AUTH", &mut self.auth),
+		] {
+			if let Some(value) = var(name) {
+				*endpoint = value;
+			}
+		}
 
-enum StreamSort {
-	Relevance,
-	ViewerCount,
-}
-impl From<StreamSort> for &str {
-	fn from(val: StreamSort) -> Self {
-		match val {
-			StreamSort::Relevance => "RELEVANCE",
-			StreamSort::ViewerCount => "VIEWER_COUNT",
+		if let Some(port) = var("TWITCH_TUI_IRC_PORT") {
+			self.irc_port = port.parse().map_err(|_| {
+				[
+					"`TWITCH_TUI_IRC_PORT` should be a port number, not `",
+					&port,
+					"`",
+				]
+				.concat()
+			})?;
 		}
-	}
-}
 
-impl Default for DirectoryPage_GameOptions {
-	fn default() -> Self {
-		// Category section options.
-		// Alternate examples in comments:
-		Self {
-			// `StreamSort::ViewerCount.into()`
-			sort: StreamSort::Relevance.into(),
-			// `Some(RecommendationContext::default())`
-			recommendationsContext: None,
-			// `Some("foo")`
-			requestID: None,
-			// `Some(vec!["English"])`
-			freeformTags: None,
-			// `Some(vec!["English"])`
-			tags: None,
+		if let Some(tls) = var("TWITCH_TUI_IRC_TLS") {
+			self.irc_tls = match &*tls {
+				"true" => true,
+				"false" => false,
+				_ => {
+					return Err([
+						"`TWITCH_TUI_IRC_TLS` should be `true` or `false`, not `",
+						&tls,
+						"`",
+					]
+					.concat())
+				}
+			};
 		}
+
+		self.validate()
 	}
-}
 
-impl Default for DirectoryPage_GameVariables {
-	fn default() -> Self {
-		// Category section.
-		// Alternate examples in comments:
-		Self {
-			// `Some(69)` / None
-			// Needs to be `Some` to get colour.
-			imageWidth: Some(0),
-			// This will be set by the program
-			name: "".into(),
-			// `DirectoryPage_GameOptions { freeformTags: Some(vec!["English"]), .. }`
-			options: DirectoryPage_GameOptions::default(),
-			// `false`
-			sortTypeIsRecency: true,
-			// `69`
-			limit: 30,
+	/// Checks the URLs have schemes we can use.
+	fn validate(&self) -> Result<(), String> {
+		for (name, url, schemes) in [
+			("gql", &self.gql, ["http://", "https://"]),
+			("usher", &self.usher, ["http://", "https://"]),
+			("pubsub", &self.pubsub, ["ws://", "wss://"]),
+			("auth", &self.auth, ["http://", "https://"]),
+		] {
+			if !schemes.iter().any(|scheme| url.starts_with(scheme)) {
+				return Err([
+					"`endpoints.",
+					name,
+					"` should start with `",
+					schemes[0],
+					"` or `",
+					schemes[1],
+					"`",
+				]
+				.concat());
+			}
 		}
+
+		if self.irc_host.is_empty() {
+			return Err("`endpoints.irc_host` can't be empty".to_owned());
+		}
+
+		Ok(())
 	}
 }
 
-enum SearchIndex {
-	// Live channels with query as a tag
-	ChannelWithTag,
-	// Live channels that match the search
-	Channel,
-	// Category matching query
-	Game,
-	// Past videos
-	Vod,
+/// Every quality that `quality` can contain.
+pub const QUALITIES: &[&str] = &[
+	"audio_only",
+	"worst",
+	"160p",
+	"360p",
+	"480p",
+	"720p",
+	"720p60",
+	"936p60",
+	"1080p60",
+	"best",
+];
+
+/// Lets us deserialize [`Alignment`]
+#[derive(Deserialize)]
+#[serde(remote = "Alignment")]
+enum AlignmentDef {
+	Left,
+	Center,
+	Right,
 }
-impl From<SearchIndex> for &str {
-	fn from(val: SearchIndex) -> Self {
-		match val {
-			SearchIndex::ChannelWithTag => "CHANNEL_WITH_TAG",
-			SearchIndex::Channel => "CHANNEL",
-			SearchIndex::Game => "GAME",
-			SearchIndex::Vod => "VOD",
-		}
-	}
+
+/// Lets us deserialize [`BorderType`]
+#[derive(Deserialize)]
+#[serde(remote = "BorderType")]
+enum BorderTypeDef {
+	Plain,
+	Rounded,
+	Double,
+	Thick,
 }
 
-// The next 2 are for the search page
+// ----------------
+// The following settings are for API request options, changing some of these could cause the
+// server to return errors, which may cause this program to panic. Edit them at your own risk.
+// ----------------
 
-impl Default for SearchResultsPage_SearchResultsOptions {
+/// The options for requests that are worth changing. Everything else is set by
+/// [`twitch_curses::Client`].
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RequestOptions {
+	/// Sections in the personal section, `"RecommendedSection"` and/or `"SimilarSection"`
+	pub personal_sections: Vec<PersonalSectionType>,
+	/// Needed for `SimilarSection`, i.e. `"someone"`
+	pub context_channel_name: Option<String>,
+	/// How streams in a category are sorted to start with, `"Relevance"`, `"ViewerCount"`,
+	/// `"ViewerCountAsc"` or `"Recent"`
+	pub game_sort: StreamSort,
+	/// Only show streams with these tags in categories, i.e. `["English"]`
+	pub game_tags: Option<Vec<String>>,
+	/// Same as above, these can be changed while browsing a category
+	pub game_freeform_tags: Option<Vec<String>>,
+	/// Only show streams in these languages in categories to start with, i.e. `["EN"]`
+	pub game_languages: Option<Vec<String>>,
+	/// How many streams to get per category
+	pub game_limit: u32,
+	/// Filter search to results of certain types, i.e. `["ChannelWithTag"]`.
+	/// `null` means all of them.
+	pub search_targets: Option<Vec<SearchIndex>>,
+	/// Which videos a channel's video list starts on, `"Archive"`, `"Highlight"`, `"Upload"` or
+	/// `"Premiere"`
+	pub video_type: VideoType,
+	/// How a channel's videos are sorted, `"Time"` or `"Views"`
+	pub video_sort: VideoSort,
+	/// How many videos to get at a time
+	pub video_limit: u32,
+	/// How far back clip lists go, `"Day"`, `"Week"`, `"Month"` or `"AllTime"`
+	pub clip_period: ClipPeriod,
+	/// How clip lists are sorted, `"Views"` or `"Time"`
+	pub clip_sort: ClipSort,
+	/// How many clips to get at a time
+	pub clip_limit: u32,
+	/// Only show top categories with these tags to start with, i.e. `["FPS"]`
+	pub directory_tags: Vec<String>,
+}
+impl Default for RequestOptions {
 	fn default() -> Self {
-		// Filter search to results of certain types (see `SearchIndex` above)
-		// To use this, you neeed to set
-		// `options: Some(SearchResultsPage_SearchResultsOptions::default())`
-		// in `SearchResultsPage_SearchResultsVariables` below.
 		Self {
-			// `Some(vec![Target { index: SearchIndex::ChannelWithTag.into() },])`
-			targets: None,
+			personal_sections: vec![PersonalSectionType::RecommendedSection],
+			context_channel_name: None,
+			game_sort: StreamSort::Relevance,
+			game_tags: None,
+			game_freeform_tags: None,
+			game_languages: None,
+			game_limit: 30,
+			search_targets: None,
+			video_type: VideoType::Archive,
+			video_sort: VideoSort::Time,
+			video_limit: 30,
+			clip_period: ClipPeriod::Week,
+			clip_sort: ClipSort::Views,
+			clip_limit: 30,
+			directory_tags: Vec::new(),
 		}
 	}
 }
 
-impl Default for SearchResultsVariables {
-	fn default() -> Self {
-		// Search results
-		// Alternate examples in comments
-		Self {
-			// This is set by the program
-			query: "".into(),
-			// `Some(SearchResultsPage_SearchResultsOptions::default())`
-			options: None,
-			// `Some("lol")`
-			requestID: None,
+impl RequestOptions {
+	/// What a category's streams start with, these can be changed while browsing it
+	pub fn stream_options(&self) -> StreamOptions {
+		StreamOptions {
+			sort: self.game_sort,
+			tags: self.game_freeform_tags.clone().unwrap_or_default(),
+			languages: self.game_languages.clone().unwrap_or_default(),
+			tag_ids: self.game_tags.clone().unwrap_or_default(),
 		}
 	}
+
+	/// What a search looks for when it isn't narrowed, empty for everything
+	pub fn search_indexes(&self) -> &[SearchIndex] {
+		self.search_targets.as_deref().unwrap_or_default()
+	}
 }
 
-// The next 1 is for VODs
+/// Describes a search's scope for titles, `None` being everything in `search_targets`
+pub fn describe_scope(scope: Option<SearchIndex>) -> String {
+	match (scope, config().requests.search_indexes()) {
+		(Some(index), _) => index.name().to_owned(),
+		(None, []) => "everything".to_owned(),
+		(None, targets) => targets
+			.iter()
+			.map(|index| index.name())
+			.collect::<Vec<_>>()
+			.join(", "),
+	}
+}
 
-impl Default for PlaybackAccessTokenVariables {
-	fn default() -> Self {
-		// Used for VODs
-		Self {
-			// Doesn't matter, but `false` is slightly more performant
-			isLive: false,
-			// Must be `true`
-			isVod: true,
-			login: "",
-			playerType: "",
-			// Set by the program
-			vodID: String::new(),
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::keys::Action;
+
+	/// Validates the default config with `change` applied.
+	fn validate(change: impl FnOnce(&mut Config)) -> Result<(), String> {
+		let mut config = Config::default();
+		change(&mut config);
+		config.validate()
+	}
+
+	/// Applies `vars` to the default endpoints.
+	fn apply_env(vars: &[(&str, &str)]) -> Result<Endpoints, String> {
+		let mut endpoints = Endpoints::default();
+		endpoints
+			.apply_env(|var| {
+				vars.iter()
+					.find(|(name, _)| *name == var)
+					.map(|(_, value)| (*value).to_owned())
+			})
+			.map(|_| endpoints)
+	}
+
+	#[test]
+	fn default_is_valid() {
+		assert_eq!(validate(|_| ()), Ok(()));
+	}
+
+	#[test]
+	fn rejects_empty_player() {
+		assert!(validate(|config| config.player.clear()).is_err());
+		assert!(validate(|config| config.player = vec![String::new()]).is_err());
+	}
+
+	#[test]
+	fn rejects_unknown_quality() {
+		assert_eq!(
+			validate(|config| config.quality = vec!["720p".to_owned(), "4k".to_owned()]),
+			Err("Unknown quality `4k` in `quality`".to_owned())
+		);
+		// Qualities are case-insensitive
+		assert_eq!(
+			validate(|config| config.quality = vec!["Best".to_owned()]),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn rejects_header_without_colon() {
+		assert_eq!(
+			validate(|config| config.headers.push("X-Device-Id A".to_owned())),
+			Err("Header `X-Device-Id A` should be in the form `Name:value`".to_owned())
+		);
+	}
+
+	#[test]
+	fn rejects_bad_date_format() {
+		assert!(validate(|config| config.date_format = Some("%Q".to_owned())).is_err());
+		assert_eq!(
+			validate(|config| config.date_format = Some("%Y-%m-%d".to_owned())),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn rejects_empty_home_page_name() {
+		assert!(validate(|config| config.home_page = HomePage::Game(String::new())).is_err());
+		assert!(validate(|config| config.home_page = HomePage::Search(String::new())).is_err());
+	}
+
+	#[test]
+	fn rejects_conflicting_keys() {
+		let mut config = Config::default();
+		config.keys.browser.insert(
+			Action::Refresh,
+			vec!["g"
+				.to_owned()
+				.try_into()
+				.expect("Should be a valid binding")],
+		);
+
+		assert!(config.validate().is_err());
+	}
+
+	#[test]
+	fn rejects_out_of_range_limits() {
+		for limit in [0, 101] {
+			assert_eq!(
+				validate(|config| config.requests.game_limit = limit),
+				Err("`requests.game_limit` should be between 1 and 100".to_owned())
+			);
+			assert_eq!(
+				validate(|config| config.requests.video_limit = limit),
+				Err("`requests.video_limit` should be between 1 and 100".to_owned())
+			);
+			assert_eq!(
+				validate(|config| config.requests.clip_limit = limit),
+				Err("`requests.clip_limit` should be between 1 and 100".to_owned())
+			);
 		}
+
+		assert_eq!(validate(|config| config.requests.game_limit = 100), Ok(()));
+	}
+
+	#[test]
+	fn rejects_bad_endpoint_schemes() {
+		assert_eq!(
+			validate(|config| config.endpoints.gql = "gql.twitch.tv/gql".to_owned()),
+			Err("`endpoints.gql` should start with `http://` or `https://`".to_owned())
+		);
+		assert_eq!(
+			validate(|config| config.endpoints.pubsub = "http://localhost:8081".to_owned()),
+			Err("`endpoints.pubsub` should start with `ws://` or `wss://`".to_owned())
+		);
+		assert!(validate(|config| config.endpoints.irc_host.clear()).is_err());
+	}
+
+	#[test]
+	fn env_overrides_endpoints() {
+		let endpoints = apply_env(&[
+			("TWITCH_TUI_GQL", "http://127.0.0.1:8080/gql"),
+			("TWITCH_TUI_IRC_HOST", "localhost"),
+			("TWITCH_TUI_IRC_PORT", "6667"),
+			("TWITCH_TUI_IRC_TLS", "false"),
+		])
+		.expect("Should accept valid variables");
+
+		assert_eq!(endpoints.gql, "http://127.0.0.1:8080/gql");
+		assert_eq!(endpoints.irc_host, "localhost");
+		assert_eq!(endpoints.irc_port, 6667);
+		assert!(!endpoints.irc_tls);
+		// Unset variables keep the default
+		assert_eq!(endpoints.usher, Endpoints::default().usher);
+	}
+
+	#[test]
+	fn env_rejects_bad_irc_port() {
+		for port in ["irc", "-1", "65536", ""] {
+			assert_eq!(
+				apply_env(&[("TWITCH_TUI_IRC_PORT", port)]).err(),
+				Some(
+					[
+						"`TWITCH_TUI_IRC_PORT` should be a port number, not `",
+						port,
+						"`"
+					]
+					.concat()
+				)
+			);
+		}
+	}
+
+	#[test]
+	fn env_rejects_bad_irc_tls() {
+		for tls in ["yes", "1", "TRUE"] {
+			assert_eq!(
+				apply_env(&[("TWITCH_TUI_IRC_TLS", tls)]).err(),
+				Some(
+					[
+						"`TWITCH_TUI_IRC_TLS` should be `true` or `false`, not `",
+						tls,
+						"`"
+					]
+					.concat()
+				)
+			);
+		}
+	}
+
+	#[test]
+	fn env_endpoints_are_validated() {
+		assert!(apply_env(&[("TWITCH_TUI_USHER", "usher.ttvnw.net")]).is_err());
+		assert!(apply_env(&[("TWITCH_TUI_IRC_HOST", "")]).is_err());
 	}
 }
@@ -0,0 +1,108 @@
+//! The error type for anything that can fail while talking to twitch or starting the player.
+
+use std::fmt::{self, Display, Formatter};
+use std::io;
+
+use serde::Deserialize;
+
+/// An item of a GQL response's `errors` array
+#[derive(Deserialize, Debug, Clone)]
+pub struct GqlError {
+	pub message: String,
+	// Ignore `path` and `locations`
+}
+
+/// What went wrong
+#[derive(Debug)]
+pub enum ErrorKind {
+	/// Couldn't connect, or the transfer failed
+	Curl(curl::Error),
+	/// The server responded with an unsuccessful status, holds the start of the body
+	Status(String),
+	/// The response wasn't the JSON we expected
+	Json(simd_json::Error),
+	/// The server returned GQL errors instead of data
+	Gql(Vec<GqlError>),
+	/// Couldn't start a program
+	Io(io::Error),
+	/// Anything else, i.e. a response with missing data or a chat connection error
+	Other(String),
+}
+impl Display for ErrorKind {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		match self {
+			ErrorKind::Curl(e) => write!(f, "Request failed: {e}"),
+			ErrorKind::Status(body) => write!(f, "Unexpected response: {body}"),
+			ErrorKind::Json(e) => write!(f, "Invalid response: {e}"),
+			ErrorKind::Gql(errors) => write!(
+				f,
+				"Server returned errors: {}",
+				errors
+					.iter()
+					.map(|error| &*error.message)
+					.collect::<Vec<_>>()
+					.join(", ")
+			),
+			ErrorKind::Io(e) => write!(f, "{e}"),
+			ErrorKind::Other(message) => f.write_str(message),
+		}
+	}
+}
+
+/// An error, with what we were doing at the time.
+#[derive(Debug)]
+pub struct Error {
+	/// What we were trying to do, usually the GQL operation name
+	pub operation: String,
+	/// HTTP status of the response, if we got one
+	pub status: Option<u32>,
+	pub kind: ErrorKind,
+}
+impl Error {
+	pub fn new<S: Into<String>>(operation: S, kind: ErrorKind) -> Self {
+		Self {
+			operation: operation.into(),
+			status: None,
+			kind,
+		}
+	}
+
+	/// Adds the HTTP status to this error
+	pub fn with_status(mut self, status: u32) -> Self {
+		self.status = Some(status);
+		self
+	}
+
+	/// Lines describing this error, to show in the UI
+	pub fn lines(&self) -> Vec<String> {
+		let mut lines = vec![["While: ", &self.operation].concat()];
+
+		if let Some(status) = self.status {
+			lines.push(["HTTP status: ", &status.to_string()].concat());
+		}
+
+		lines.push(String::new());
+
+		if let ErrorKind::Gql(errors) = &self.kind {
+			// One line each, since there can be a few
+			lines.push("Server returned errors:".to_owned());
+			lines.extend(errors.iter().map(|error| ["- ", &error.message].concat()));
+		} else {
+			lines.push(self.kind.to_string());
+		}
+
+		lines
+	}
+}
+impl Display for Error {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		write!(f, "{}: {}", self.operation, self.kind)?;
+
+		if let Some(status) = self.status {
+			write!(f, " (HTTP {status})")?;
+		}
+
+		Ok(())
+	}
+}
+impl std::error::Error for Error {}
@@ -0,0 +1,185 @@
+//! Recording responses to files and replaying them, so the browser can run without a network.
+//!
+//! Every HTTP request goes through [`serve`]. When recording, each request's body (or URL for a
+//! GET), status and response are saved to `<dir>/<operation>-<hash>.json`, where the hash is of the
+//! request. Replaying reads those files back instead of using curl, so the same requests get the
+//! same responses. JSON is stored as JSON so fixtures can be edited, anything else as a string.
+
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+use simd_json::{from_slice, to_vec, to_vec_pretty, OwnedValue};
+
+use crate::error::{Error, ErrorKind};
+use crate::log::warn;
+
+/// Where responses come from
+pub enum Mode {
+	/// Send requests to twitch like normal
+	Live,
+	/// Send requests, saving the responses to this directory
+	Record(PathBuf),
+	/// Serve responses from this directory, nothing is sent
+	Replay(PathBuf),
+}
+
+static MODE: OnceLock<Mode> = OnceLock::new();
+
+/// The mode set at startup, [`Mode::Live`] if it wasn't set
+fn mode() -> &'static Mode {
+	MODE.get_or_init(|| Mode::Live)
+}
+
+/// Sets the mode, should only be called once at startup.
+pub fn set(mode: Mode) {
+	let _ = MODE.set(mode);
+}
+
+thread_local! {
+	/// Set by [`with_mode`], used instead of [`MODE`] on this thread
+	static THREAD_MODE: RefCell<Option<Mode>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with requests on this thread using `mode` instead of the one from [`set`]. Tests run in
+/// parallel, so they use this to replay fixtures without affecting each other.
+pub fn with_mode<T, F: FnOnce() -> T>(mode: Mode, f: F) -> T {
+	let previous = THREAD_MODE.with(|thread_mode| thread_mode.replace(Some(mode)));
+	let result = f();
+	THREAD_MODE.with(|thread_mode| *thread_mode.borrow_mut() = previous);
+
+	result
+}
+
+/// A recorded request
+#[derive(Serialize, Deserialize)]
+struct Fixture {
+	operation: String,
+	request: OwnedValue,
+	status: u32,
+	response: OwnedValue,
+}
+
+/// Gets the response to `request`, calling `fetch` to actually send it unless we're replaying.
+pub fn serve<F: FnOnce() -> Result<(u32, Vec<u8>), Error>>(
+	operation: &str,
+	request: &[u8],
+	fetch: F,
+) -> Result<(u32, Vec<u8>), Error> {
+	// Taken out while it's used, so the cell isn't borrowed during the request
+	match THREAD_MODE.with(RefCell::take) {
+		Some(mode) => {
+			let result = serve_with(&mode, operation, request, fetch);
+			THREAD_MODE.with(|thread_mode| *thread_mode.borrow_mut() = Some(mode));
+
+			result
+		}
+		None => serve_with(mode(), operation, request, fetch),
+	}
+}
+
+/// [`serve`] in a specific mode.
+fn serve_with<F: FnOnce() -> Result<(u32, Vec<u8>), Error>>(
+	mode: &Mode,
+	operation: &str,
+	request: &[u8],
+	fetch: F,
+) -> Result<(u32, Vec<u8>), Error> {
+	match mode {
+		Mode::Live => fetch(),
+		Mode::Record(dir) => {
+			let (status, body) = fetch()?;
+
+			// A failed recording shouldn't stop the browser from working
+			if let Err(e) = record(
+				&path(dir, operation, request),
+				operation,
+				request,
+				status,
+				&body,
+			) {
+				warn(["Couldn't record ", operation, ": ", &e.to_string()].concat());
+			}
+
+			Ok((status, body))
+		}
+		Mode::Replay(dir) => replay(&path(dir, operation, request), operation),
+	}
+}
+
+/// Saves a request and it's response.
+fn record(
+	path: &Path,
+	operation: &str,
+	request: &[u8],
+	status: u32,
+	body: &[u8],
+) -> Result<(), Error> {
+	let json = to_vec_pretty(&Fixture {
+		operation: operation.to_owned(),
+		request: to_value(request),
+		status,
+		response: to_value(body),
+	})
+	.map_err(|e| Error::new(operation, ErrorKind::Json(e)))?;
+
+	if let Some(dir) = path.parent() {
+		fs::create_dir_all(dir).map_err(|e| Error::new(operation, ErrorKind::Io(e)))?;
+	}
+
+	fs::write(path, json).map_err(|e| Error::new(operation, ErrorKind::Io(e)))
+}
+
+/// Reads a recorded response.
+fn replay(path: &Path, operation: &str) -> Result<(u32, Vec<u8>), Error> {
+	let mut json = fs::read(path).map_err(|e| {
+		Error::new(
+			operation,
+			ErrorKind::Other(
+				[
+					"No fixture at ",
+					&path.to_string_lossy(),
+					" (",
+					&e.to_string(),
+					")",
+				]
+				.concat(),
+			),
+		)
+	})?;
+
+	let fixture =
+		from_slice::<Fixture>(&mut json).map_err(|e| Error::new(operation, ErrorKind::Json(e)))?;
+
+	let body = match fixture.response {
+		OwnedValue::String(string) => string.into_bytes(),
+		value => to_vec(&value).map_err(|e| Error::new(operation, ErrorKind::Json(e)))?,
+	};
+
+	Ok((fixture.status, body))
+}
+
+/// Parses `bytes` as JSON if they are, otherwise keeps them as a string.
+fn to_value(bytes: &[u8]) -> OwnedValue {
+	// Parsing changes the buffer
+	from_slice(&mut bytes.to_vec())
+		.unwrap_or_else(|_| OwnedValue::String(String::from_utf8_lossy(bytes).into_owned()))
+}
+
+/// Where the fixture for `request` is kept
+fn path(dir: &Path, operation: &str, request: &[u8]) -> PathBuf {
+	// FNV-1a, which (unlike `DefaultHasher`) won't change between rust versions
+	let hash = request.iter().fold(0xcbf29ce484222325_u64, |hash, &byte| {
+		(hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+	});
+
+	// Operations can be descriptions or a batch's names
+	let operation = operation
+		.chars()
+		.map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+		.collect::<String>();
+
+	dir.join([&operation, "-", &format!("{hash:016x}"), ".json"].concat())
+}
@@ -0,0 +1,865 @@
+//! The queries [`crate::Client`] sends to twitch's GQL API, and the JSON they return. These are
+//! converted to the types in [`crate::models`] before they're returned.
+
+// For the JSON stuff:
+#![allow(non_snake_case)]
+#![allow(non_camel_case_types)]
+
+use serde::{Deserialize, Serialize};
+
+use crate::request::Operation;
+
+pub struct PersonalSections;
+impl Operation for PersonalSections {
+	type Variables = PersonalSectionsVariables;
+	type Response = PersonalSectionsData;
+
+	const SHA256HASH: Option<&'static str> =
+		Some("f8cc9b91bb629f2d09dd8299d9f07c4daefe019236a19fc12fa2b14eb95c359e");
+	const OPERATION_NAME: &'static str = "PersonalSections";
+	const QUERY: &'static str = include_str!("queries/PersonalSections.graphql");
+}
+
+pub struct Shelves;
+impl Operation for Shelves {
+	type Variables = ShelvesVariables;
+	type Response = ShelvesData;
+
+	const SHA256HASH: Option<&'static str> =
+		Some("41858598cc637cf9e6153818f5a4d274a08e8743e4a85903cdfe39c464152404");
+	const OPERATION_NAME: &'static str = "Shelves";
+	const QUERY: &'static str = include_str!("queries/Shelves.graphql");
+}
+
+pub struct DirectoryPage_Game;
+impl Operation for DirectoryPage_Game {
+	type Variables = DirectoryPage_GameVariables;
+	type Response = DirectoryPage_GameData;
+
+	const SHA256HASH: Option<&'static str> =
+		Some("df4bb6cc45055237bfaf3ead608bbafb79815c7100b6ee126719fac3762ddf8b");
+	const OPERATION_NAME: &'static str = "DirectoryPage_Game";
+	const QUERY: &'static str = include_str!("queries/DirectoryPage_Game.graphql");
+}
+
+pub struct SearchResultsPage_SearchResults;
+impl Operation for SearchResultsPage_SearchResults {
+	type Variables = SearchResultsVariables;
+	type Response = SearchResultsData;
+
+	const SHA256HASH: Option<&'static str> =
+		Some("6ea6e6f66006485e41dbe3ebd69d5674c5b22896ce7b595d7fce6411a3790138");
+	const OPERATION_NAME: &'static str = "SearchResultsPage_SearchResults";
+	const QUERY: &'static str = include_str!("queries/SearchResultsPage_SearchResults.graphql");
+}
+
+pub struct FollowedChannels;
+impl Operation for FollowedChannels {
+	type Variables = FollowedChannelsVariables;
+	type Response = FollowedChannelsData;
+
+	const SHA256HASH: Option<&'static str> = None;
+	const OPERATION_NAME: &'static str = "FollowedChannels";
+	const QUERY: &'static str = include_str!("queries/FollowedChannels.graphql");
+}
+
+pub struct ChannelPage;
+impl Operation for ChannelPage {
+	type Variables = ChannelPageVariables;
+	type Response = ChannelPageData;
+
+	const SHA256HASH: Option<&'static str> = None;
+	const OPERATION_NAME: &'static str = "ChannelPage";
+	const QUERY: &'static str = include_str!("queries/ChannelPage.graphql");
+}
+
+pub struct ChannelSchedule;
+impl Operation for ChannelSchedule {
+	type Variables = ChannelPageVariables;
+	type Response = ChannelScheduleData;
+
+	const SHA256HASH: Option<&'static str> = None;
+	const OPERATION_NAME: &'static str = "ChannelSchedule";
+	const QUERY: &'static str = include_str!("queries/ChannelSchedule.graphql");
+}
+
+pub struct ChannelVideos;
+impl Operation for ChannelVideos {
+	type Variables = ChannelVideosVariables;
+	type Response = ChannelVideosData;
+
+	const SHA256HASH: Option<&'static str> = None;
+	const OPERATION_NAME: &'static str = "ChannelVideos";
+	const QUERY: &'static str = include_str!("queries/ChannelVideos.graphql");
+}
+
+pub struct ClipsPage;
+impl Operation for ClipsPage {
+	type Variables = ClipsPageVariables;
+	type Response = ClipsPageData;
+
+	const SHA256HASH: Option<&'static str> = None;
+	const OPERATION_NAME: &'static str = "ClipsPage";
+	const QUERY: &'static str = include_str!("queries/ClipsPage.graphql");
+}
+
+pub struct TopCategories;
+impl Operation for TopCategories {
+	type Variables = TopCategoriesVariables;
+	type Response = TopCategoriesData;
+
+	const SHA256HASH: Option<&'static str> = None;
+	const OPERATION_NAME: &'static str = "TopCategories";
+	const QUERY: &'static str = include_str!("queries/TopCategories.graphql");
+}
+
+pub struct SearchSuggestions;
+impl Operation for SearchSuggestions {
+	type Variables = SearchSuggestionsVariables;
+	type Response = SearchSuggestionsData;
+
+	const SHA256HASH: Option<&'static str> = None;
+	const OPERATION_NAME: &'static str = "SearchSuggestions";
+	const QUERY: &'static str = include_str!("queries/SearchSuggestions.graphql");
+}
+
+pub struct PlaybackAccessToken;
+impl Operation for PlaybackAccessToken {
+	type Variables = PlaybackAccessTokenVariables;
+	type Response = PlaybackAccessTokenData;
+
+	const SHA256HASH: Option<&'static str> =
+		Some("0828119ded1c13477966434e15800ff57ddacf13ba1911c129dc2200705b0712");
+	const OPERATION_NAME: &'static str = "PlaybackAccessToken";
+	const QUERY: &'static str = include_str!("queries/PlaybackAccessToken.graphql");
+}
+
+pub struct VideoAccessToken_Clip;
+impl Operation for VideoAccessToken_Clip {
+	type Variables = VideoAccessToken_ClipVariables;
+	type Response = VideoAccessToken_ClipData;
+
+	const SHA256HASH: Option<&'static str> =
+		Some("36b89d2507fce29e5ca551df756d27c1cfe079e2609642b4390aa4c35796eb11");
+	const OPERATION_NAME: &'static str = "VideoAccessToken_Clip";
+	const QUERY: &'static str = include_str!("queries/VideoAccessToken_Clip.graphql");
+}
+
+// Request JSON
+
+/// What the personal section's recommendations are for. Twitch is fine with all of these being
+/// `null`.
+#[derive(Serialize, Default)]
+pub struct RecommendationContext {
+	pub platform: Option<&'static str>,
+	pub clientApp: Option<&'static str>,
+	pub location: Option<&'static str>,
+	pub referrerDomain: Option<&'static str>,
+	pub viewportHeight: Option<u16>,
+	pub viewportWidth: Option<u16>,
+	pub channelName: Option<&'static str>,
+	pub categoryName: Option<&'static str>,
+	pub lastChannelName: Option<&'static str>,
+	pub lastCategoryName: Option<&'static str>,
+	pub pageviewContent: Option<&'static str>,
+	pub pageviewContentType: Option<&'static str>,
+	pub pageviewLocation: Option<&'static str>,
+	pub pageviewMedium: Option<&'static str>,
+	pub previousPageviewContent: Option<&'static str>,
+	pub previousPageviewContentType: Option<&'static str>,
+	pub previousPageviewLocation: Option<&'static str>,
+	pub previousPageviewMedium: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+pub struct PersonalSectionsInput {
+	/// `RECOMMENDED_SECTION` and/or `SIMILAR_SECTION`
+	pub sectionInputs: Vec<&'static str>,
+	// It's optional for RECOMMENDED_SECTION, but required for SIMILAR_SECTION
+	pub recommendationContext: RecommendationContext,
+	/// Needed for `SIMILAR_SECTION`
+	pub contextChannelName: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PersonalSectionsVariables {
+	pub input: PersonalSectionsInput,
+	// No clue what this is
+	pub creatorAnniversariesExperimentEnabled: bool,
+}
+
+#[derive(Serialize, Default)]
+pub struct ShelvesContext {
+	pub clientApp: Option<&'static str>,
+	pub location: Option<&'static str>,
+	pub referrerDomain: Option<&'static str>,
+	pub viewportHeight: Option<u16>,
+	pub viewportWidth: Option<u16>,
+}
+
+/// The webapp's home page, everything can be left as the default
+#[derive(Serialize, Default)]
+pub struct ShelvesVariables {
+	// `u64` just in case
+	pub imageWidth: Option<u64>,
+	pub itemsPerRow: u16,
+	pub langWeightedCCU: Option<bool>,
+	pub platform: &'static str,
+	pub requestID: &'static str,
+	pub context: Option<ShelvesContext>,
+	pub verbose: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct DirectoryPage_GameOptions {
+	/// `RELEVANCE`, `VIEWER_COUNT`, `VIEWER_COUNT_ASC` or `RECENT`
+	pub sort: &'static str,
+	pub recommendationsContext: Option<RecommendationContext>,
+	pub requestID: Option<&'static str>,
+	pub freeformTags: Option<Vec<String>>,
+	/// IDs of the old kind of tags
+	pub tags: Option<Vec<String>>,
+	pub broadcasterLanguages: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct DirectoryPage_GameVariables {
+	/// Needs to be `Some` to get colour
+	pub imageWidth: Option<u64>,
+	/// The category name
+	pub name: String,
+	pub options: DirectoryPage_GameOptions,
+	pub sortTypeIsRecency: bool,
+	pub limit: u32,
+	/// Where to continue from, to get more streams
+	pub cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct Target {
+	/// `CHANNEL`, `CHANNEL_WITH_TAG`, `GAME` or `VOD`
+	pub index: &'static str,
+	/// Where to continue from, to get more results of this type
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SearchResultsPage_SearchResultsOptions {
+	pub targets: Option<Vec<Target>>,
+}
+
+#[derive(Serialize)]
+pub struct SearchResultsVariables {
+	/// The search
+	pub query: String,
+	/// `None` for every kind of result
+	pub options: Option<SearchResultsPage_SearchResultsOptions>,
+	pub requestID: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct FollowedChannelsVariables {
+	/// How many channels to get per request, at most 100
+	pub first: u32,
+	/// Where to continue from, to get more channels
+	pub cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ChannelPageVariables {
+	/// The channel
+	pub login: String,
+	/// How many of each kind of video, or scheduled streams, to get
+	pub first: u32,
+}
+
+#[derive(Serialize)]
+pub struct ChannelVideosVariables {
+	/// The channel
+	pub login: String,
+	pub first: u32,
+	/// `ARCHIVE`, `HIGHLIGHT`, `UPLOAD` or `PAST_PREMIERE`
+	pub broadcastType: &'static str,
+	/// `TIME` or `VIEWS`
+	pub sort: &'static str,
+	pub cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct ClipsPageVariables {
+	/// The channel, if `isChannel`
+	pub login: String,
+	/// The category, if not `isChannel`
+	pub name: String,
+	pub isChannel: bool,
+	pub first: u32,
+	/// `LAST_DAY`, `LAST_WEEK`, `LAST_MONTH` or `ALL_TIME`
+	pub period: &'static str,
+	/// `VIEWS_DESC` or `CREATED_AT_DESC`
+	pub sort: &'static str,
+	pub cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TopCategoriesVariables {
+	pub first: u32,
+	/// Only categories with all of these tags, `None` for all of them
+	pub tags: Option<Vec<String>>,
+	pub cursor: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SearchSuggestionsVariables {
+	/// What's been typed so far
+	pub queryFragment: String,
+}
+
+#[derive(Serialize)]
+pub struct PlaybackAccessTokenVariables {
+	pub isLive: bool,
+	pub isVod: bool,
+	/// The channel, for streams
+	pub login: String,
+	pub playerType: &'static str,
+	/// The video ID, for VODs
+	pub vodID: String,
+}
+
+#[derive(Serialize)]
+pub struct VideoAccessToken_ClipVariables {
+	pub slug: String,
+}
+
+// Response JSON
+
+#[derive(Deserialize)]
+pub struct BroadcastSettings {
+	pub title: String, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct UserRoles {
+	pub isPartner: bool,
+}
+
+#[derive(Deserialize)]
+pub struct Tag {
+	pub localizedName: String, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct FreeformTag {
+	pub name: String, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct Game {
+	pub name: String,
+	pub displayName: Option<String>,
+	pub viewersCount: Option<u32>,
+	pub tags: Option<Vec<Tag>>,
+	pub originalReleaseDate: Option<String>,
+	// Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct User {
+	pub id: String,
+	pub login: String,
+	pub displayName: String,
+	pub primaryColorHex: Option<String>,
+	pub broadcastSettings: Option<BroadcastSettings>,
+	pub roles: Option<UserRoles>,
+}
+
+#[derive(Deserialize)]
+pub struct Stream {
+	pub title: Option<String>,
+	pub viewersCount: u32,
+	pub createdAt: Option<String>,
+	/// Missing when it's obvious who's streaming
+	pub broadcaster: Option<User>,
+	#[serde(default)]
+	pub freeformTags: Vec<FreeformTag>,
+	pub game: Option<Game>,
+	// Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct PageInfo {
+	pub hasNextPage: bool,
+}
+
+// Personal sections
+
+#[derive(Deserialize)]
+pub struct PersonalSectionTitle {
+	pub localizedFallback: String,
+}
+
+#[derive(Deserialize)]
+pub struct PersonalSectionItem {
+	pub user: User,
+	/// Their stream, without the broadcaster
+	pub content: Stream,
+	// Ignore `trackingID`
+}
+
+#[derive(Deserialize)]
+pub struct PersonalSection {
+	pub title: PersonalSectionTitle,
+	pub items: Vec<PersonalSectionItem>,
+	// Ignore `type`
+}
+
+#[derive(Deserialize)]
+pub struct PersonalSectionsData {
+	pub personalSections: Vec<PersonalSection>,
+}
+
+// Shelves
+
+#[derive(Deserialize)]
+pub struct BrowsableCollectionTitle {
+	pub fallbackLocalizedTitle: String,
+}
+
+/// Part of a shelf's title
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum TextToken {
+	BrowsableCollection {
+		collectionName: BrowsableCollectionTitle,
+		// Ignore `id`
+	},
+	Game(Game),
+	TextToken {
+		text: String,
+		hasEmphasis: bool,
+	},
+	/// Anything we don't know how to show
+	None,
+}
+
+#[derive(Deserialize)]
+pub struct TitleTokenEdge {
+	pub node: TextToken,
+}
+
+#[derive(Deserialize)]
+pub struct ShelfTitle {
+	pub fallbackLocalizedTitle: String,
+	pub localizedTitleTokens: Vec<TitleTokenEdge>,
+	// Ignore `key`
+}
+
+/// Something on a shelf, only clips, categories and streams are asked for
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum ShelfContent {
+	Clip(Clip),
+	Game(Game),
+	Stream(Stream),
+	None,
+}
+
+#[derive(Deserialize)]
+pub struct ShelfContentEdge {
+	pub node: ShelfContent,
+}
+
+#[derive(Deserialize)]
+pub struct ShelfContentConnection {
+	pub edges: Vec<ShelfContentEdge>,
+}
+
+#[derive(Deserialize)]
+pub struct Shelf {
+	pub title: ShelfTitle,
+	pub content: ShelfContentConnection,
+	// Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct ShelfEdge {
+	pub node: Shelf,
+}
+
+#[derive(Deserialize)]
+pub struct ShelfConnection {
+	pub edges: Vec<ShelfEdge>,
+}
+
+#[derive(Deserialize)]
+pub struct ShelvesData {
+	pub shelves: ShelfConnection,
+}
+
+// Categories
+
+#[derive(Deserialize)]
+pub struct StreamEdge {
+	pub node: Stream,
+	pub cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct StreamConnection {
+	pub edges: Vec<StreamEdge>,
+	pub pageInfo: PageInfo,
+}
+
+#[derive(Deserialize)]
+pub struct Category {
+	pub streams: StreamConnection,
+	// Ignore `id`, `name` and `displayName`
+}
+
+#[derive(Deserialize)]
+pub struct DirectoryPage_GameData {
+	/// `null` if there's no category with that name
+	pub game: Option<Category>,
+}
+
+// Search
+
+#[derive(Deserialize)]
+pub struct FollowerConnection {
+	pub totalCount: u32,
+}
+
+#[derive(Deserialize)]
+pub struct Broadcast {
+	pub startedAt: Option<String>, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct ScheduleCategory {
+	pub name: String, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct ScheduleSegment {
+	pub startAt: Option<String>,
+	pub endAt: Option<String>,
+	pub title: String,
+	pub categories: Vec<ScheduleCategory>,
+	// Ignore `id`
+}
+
+/// Search results only ask for the next segment, channel pages for the first few
+#[derive(Deserialize)]
+pub struct Schedule {
+	#[serde(default)]
+	pub nextSegment: Option<ScheduleSegment>,
+	/// `null` if they've never set a schedule
+	#[serde(default)]
+	pub segments: Option<Vec<ScheduleSegment>>,
+	// Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct UserChannel {
+	pub schedule: Option<Schedule>, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct Video {
+	pub id: String,
+	pub title: Option<String>,
+	// Up to 48 hours
+	pub lengthSeconds: u32,
+	pub viewCount: Option<u32>,
+	pub createdAt: Option<String>,
+	pub owner: Option<User>,
+	pub game: Option<Game>,
+}
+
+#[derive(Deserialize)]
+pub struct VideoEdge {
+	pub node: Video,
+	/// `null` on channel pages, which don't load more
+	pub cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct VideoConnection {
+	pub edges: Vec<VideoEdge>,
+	/// `null` on channel pages, which don't load more
+	pub pageInfo: Option<PageInfo>,
+}
+
+/// A channel in search results
+#[derive(Deserialize)]
+pub struct SearchUser {
+	pub id: String,
+	pub login: String,
+	pub displayName: String,
+	pub description: Option<String>,
+	pub broadcastSettings: BroadcastSettings,
+	pub followers: FollowerConnection,
+	pub lastBroadcast: Broadcast,
+	pub channel: UserChannel,
+	/// Their latest past broadcast, if they have one
+	pub latestVideo: VideoConnection,
+	/// Their most viewed clip, if they have one
+	pub topClip: ClipConnection,
+	pub roles: UserRoles,
+	pub stream: Option<Stream>,
+}
+
+/// A related live channel in search results
+#[derive(Deserialize)]
+pub struct LiveUser {
+	pub stream: Option<Stream>, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct SearchEdge<T> {
+	pub item: T, // Ignore `trackingID`
+}
+
+#[derive(Deserialize)]
+pub struct SearchResult<T> {
+	pub edges: Vec<SearchEdge<T>>,
+	/// Where to show this kind of result, from 1 to 5
+	pub score: usize,
+	/// Missing for related live channels
+	pub totalMatches: Option<u32>,
+	/// Set if there are more results
+	pub cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SearchFor {
+	pub channels: SearchResult<SearchUser>,
+	pub channelsWithTag: SearchResult<SearchUser>,
+	pub games: SearchResult<Game>,
+	pub videos: SearchResult<Video>,
+	pub relatedLiveChannels: SearchResult<LiveUser>,
+}
+
+#[derive(Deserialize)]
+pub struct SearchResultsData {
+	pub searchFor: SearchFor,
+}
+
+// Followed channels
+
+/// A channel the logged in user follows
+#[derive(Deserialize)]
+pub struct FollowedUser {
+	#[serde(flatten)]
+	pub user: User,
+	pub lastBroadcast: Broadcast,
+	pub stream: Option<Stream>,
+}
+
+#[derive(Deserialize)]
+pub struct FollowEdge {
+	pub node: FollowedUser,
+	pub cursor: Option<String>, // Ignore `followedAt`
+}
+
+#[derive(Deserialize)]
+pub struct FollowConnection {
+	pub edges: Vec<FollowEdge>,
+	pub pageInfo: PageInfo,
+}
+
+#[derive(Deserialize)]
+pub struct CurrentUser {
+	pub follows: FollowConnection, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct FollowedChannelsData {
+	/// `null` if we aren't logged in
+	pub currentUser: Option<CurrentUser>,
+}
+
+// Channel pages
+
+/// A channel with everything shown on their page
+#[derive(Deserialize)]
+pub struct ChannelUser {
+	/// The same as followed channels have
+	#[serde(flatten)]
+	pub user: FollowedUser,
+	pub description: Option<String>,
+	pub followers: FollowerConnection,
+	pub archives: VideoConnection,
+	pub highlights: VideoConnection,
+	pub uploads: VideoConnection,
+}
+
+#[derive(Deserialize)]
+pub struct ChannelPageData {
+	/// `null` if there's no channel with that login
+	pub user: Option<ChannelUser>,
+}
+
+#[derive(Deserialize)]
+pub struct ScheduleUser {
+	pub channel: UserChannel, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct ChannelScheduleData {
+	/// `null` if there's no channel with that login
+	pub user: Option<ScheduleUser>,
+}
+
+#[derive(Deserialize)]
+pub struct VideosUser {
+	pub videos: VideoConnection, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct ChannelVideosData {
+	/// `null` if there's no channel with that login
+	pub user: Option<VideosUser>,
+}
+
+// Clips
+
+/// Fields are renamed in queries that could mix it up with other things, search results don't
+#[derive(Deserialize)]
+pub struct Clip {
+	pub slug: String,
+	#[serde(alias = "title")]
+	pub clipTitle: Option<String>,
+	#[serde(alias = "viewCount")]
+	pub clipViewCount: Option<u32>,
+	#[serde(alias = "createdAt")]
+	pub clipCreatedAt: Option<String>,
+	// Clips are 60 seconds max
+	pub durationSeconds: u32,
+	pub language: Option<String>,
+	/// `null` if their account is gone
+	pub curator: Option<User>,
+	pub broadcaster: Option<User>,
+	/// `null` if the stream didn't have one
+	pub game: Option<Game>,
+	// Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct ClipEdge {
+	pub node: Clip,
+	/// `null` on channel pages, which don't load more
+	pub cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct ClipConnection {
+	pub edges: Vec<ClipEdge>,
+	/// `null` on channel pages, which don't load more
+	pub pageInfo: Option<PageInfo>,
+}
+
+#[derive(Deserialize)]
+pub struct ClipsOwner {
+	pub clips: ClipConnection, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct ClipsPageData {
+	/// Set for a channel's clips, `null` if there's no channel with that login
+	pub user: Option<ClipsOwner>,
+	/// Set for a category's clips, `null` if there's no category with that name
+	pub game: Option<ClipsOwner>,
+}
+
+// Top categories
+
+#[derive(Deserialize)]
+pub struct GameEdge {
+	pub node: Game,
+	pub cursor: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct GameConnection {
+	pub edges: Vec<GameEdge>,
+	pub pageInfo: PageInfo,
+}
+
+#[derive(Deserialize)]
+pub struct TopCategoriesData {
+	pub games: GameConnection,
+}
+
+// Search suggestions
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum SuggestionContent {
+	Channel {
+		login: String,
+		isLive: bool, // Ignore `id`
+	},
+	Category {
+		game: Game, // Ignore `id`
+	},
+}
+
+#[derive(Deserialize)]
+pub struct SearchSuggestion {
+	pub text: String,
+	/// `null` for suggestions that are just text
+	pub content: Option<SuggestionContent>, // Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct SearchSuggestionEdge {
+	pub node: SearchSuggestion,
+}
+
+#[derive(Deserialize)]
+pub struct SearchSuggestionConnection {
+	pub edges: Vec<SearchSuggestionEdge>,
+}
+
+#[derive(Deserialize)]
+pub struct SearchSuggestionsData {
+	/// `null` if nothing's been typed
+	pub searchSuggestions: Option<SearchSuggestionConnection>,
+}
+
+// Playback
+
+#[derive(Deserialize)]
+pub struct AccessToken {
+	pub signature: String,
+	pub value: String,
+}
+
+#[derive(Deserialize)]
+pub struct PlaybackAccessTokenData {
+	pub streamPlaybackAccessToken: Option<AccessToken>,
+	pub videoPlaybackAccessToken: Option<AccessToken>,
+}
+
+#[derive(Deserialize)]
+pub struct ClipVideoQuality {
+	/// Height, i.e. `720`
+	pub quality: String,
+	pub sourceURL: String,
+	// Ignore `frameRate`
+}
+
+#[derive(Deserialize)]
+pub struct ClipAccess {
+	pub playbackAccessToken: AccessToken,
+	/// Best first
+	pub videoQualities: Vec<ClipVideoQuality>,
+	// Ignore `id`
+}
+
+#[derive(Deserialize)]
+pub struct VideoAccessToken_ClipData {
+	/// `null` if there's no clip with that slug
+	pub clip: Option<ClipAccess>,
+}
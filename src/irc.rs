@@ -1,47 +1,79 @@
-//! Support for reading twitch chat via IRC.
+//! Support for reading (and, when logged in, writing) twitch chat via IRC.
 //!
 //! Run `[play_stream]` to start the client, which will display the chat on the
 //! `[ratatui::Terminal]` passed
+//!
+//! Messages we write are queued in [`Outgoing`] and sent when twitch's rate limit and slow mode
+//! allow. Twitch doesn't send them back to us, so they're shown when it accepts them (with a
+//! `USERSTATE`), or shown as failed if it rejects them (with a `NOTICE`), doesn't answer in time or
+//! chat reconnects first.
 
 use std::borrow::Borrow;
 use std::collections::VecDeque;
 use std::process::Stdio;
 
-use crossterm::event::{Event, EventStream, KeyCode};
-use curl::easy::Easy;
+use crossterm::event::{Event, EventStream, KeyCode, KeyEvent};
+use futures::future::OptionFuture;
 use futures::{SinkExt, StreamExt};
 use irc::client::prelude::Config;
-use irc::client::{Client, ClientStream};
+use irc::client::{Client, ClientStream, Sender};
+use irc::proto::message::Tag;
 use irc::proto::{self, Capability};
 use ratatui::backend::Backend;
 use ratatui::layout::Rect;
-use ratatui::style::{Color, Modifier, Style};
+use ratatui::style::Style;
 use ratatui::text::{Span, Spans};
 use ratatui::widgets::{Block, Borders, List, ListItem, Paragraph, Tabs};
 use ratatui::Terminal;
 use serde::Deserialize;
 use simd_json::from_slice;
 use textwrap::wrap;
-use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::io::{AsyncBufReadExt, BufReader, Lines};
 use tokio::process;
-use tokio::time::{interval, Duration};
+use tokio::time::{interval, sleep_until, Duration, Instant};
 use tokio_tungstenite::connect_async;
 use tokio_tungstenite::tungstenite::protocol;
 
+use crate::auth::token;
 use crate::config::*;
+use crate::error::{Error, ErrorKind};
+use crate::keys::{Action, KeyMap};
+use crate::theme::theme;
 use crate::utils::*;
 
-/// Connect to the channel's IRC server and return it's `ClientStream`.
-async fn connect_irc_client(login: &str) -> ClientStream {
+/// Messages a user can send per [`RATE_WINDOW`]
+const RATE_LIMIT: usize = 20;
+/// Messages moderators (and the broadcaster) can send per [`RATE_WINDOW`]
+const MODERATOR_RATE_LIMIT: usize = 100;
+/// How long twitch counts messages towards the rate limit for
+const RATE_WINDOW: Duration = Duration::from_secs(30);
+/// Longest message twitch accepts, in characters
+const MESSAGE_LENGTH: usize = 500;
+/// How long twitch has to accept or reject a message before it's shown as failed
+const PENDING_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connect to the channel's IRC server at `endpoints` and return it's `ClientStream`, and a
+/// `Sender` for writing messages.
+async fn connect_irc_client(
+	login: &str,
+	endpoints: &Endpoints,
+) -> Result<(ClientStream, Sender), Error> {
+	// Both steps fail for the same reasons
+	let irc_error =
+		|e: irc::error::Error| Error::new("Connecting to chat", ErrorKind::Other(e.to_string()));
+
 	let mut client = Client::from_config(Config {
 		channels: vec![["#", login].concat()],
-		// Anonymous
-		nickname: Some("justinfan0".to_owned()),
-		server: Some("irc.chat.twitch.tv".to_owned()),
+		// Anonymous if we aren't logged in
+		nickname: Some(token().map_or("justinfan0".to_owned(), |token| token.login.clone())),
+		password: token().map(|token| ["oauth:", &token.access_token].concat()),
+		server: Some(endpoints.irc_host.clone()),
+		port: Some(endpoints.irc_port),
+		use_tls: Some(endpoints.irc_tls),
 		..Config::default()
 	})
 	.await
-	.expect("Should be able to open IRC connection");
+	.map_err(irc_error)?;
 
 	// We need this so the server sends chat metadata
 	let _ = client.send_cap_req(&[
@@ -51,7 +83,148 @@ async fn connect_irc_client(login: &str) -> ClientStream {
 
 	let _ = client.identify();
 
-	client.stream().expect("Should be able to get IRC stream")
+	Ok((client.stream().map_err(irc_error)?, client.sender()))
+}
+
+/// Messages we've written that haven't been shown in chat yet, and what we need to know to send
+/// them.
+#[derive(Default)]
+struct Outgoing {
+	/// Waiting for the rate limit or slow mode
+	queued: VecDeque<String>,
+	/// Sent, waiting for twitch to accept or reject them, with when they were sent
+	pending: VecDeque<(String, Instant)>,
+	/// When recent messages were sent, oldest first
+	sent: VecDeque<Instant>,
+	/// Our tags from the last `USERSTATE`, for showing our messages with our badges
+	tags: Vec<Tag>,
+	/// Seconds between messages in slow mode, `0` if it's off
+	slow: u64,
+	/// Moderators and the broadcaster get a higher rate limit and ignore slow mode
+	moderator: bool,
+	/// Set by the `USERSTATE` twitch sends when we join. Nothing is sent before it, so every
+	/// `USERSTATE` after it answers one of our messages.
+	joined: bool,
+}
+impl Outgoing {
+	/// When the next queued message can be sent, if there is one.
+	fn ready_at(&self) -> Option<Instant> {
+		if self.queued.is_empty() || !self.joined {
+			return None;
+		}
+
+		let mut ready_at = Instant::now();
+
+		let limit = if self.moderator {
+			MODERATOR_RATE_LIMIT
+		} else {
+			RATE_LIMIT
+		};
+
+		// Wait for the oldest message in the window to leave it
+		if self.sent.len() >= limit {
+			ready_at = ready_at.max(self.sent[self.sent.len() - limit] + RATE_WINDOW);
+		}
+
+		if !self.moderator {
+			if let Some(&last) = self.sent.back() {
+				ready_at = ready_at.max(last + Duration::from_secs(self.slow));
+			}
+		}
+
+		Some(ready_at)
+	}
+
+	/// Sends the next queued message, showing it as failed if it couldn't be.
+	fn send_next(
+		&mut self,
+		sender: &Sender,
+		channel: &str,
+		chat: &mut VecDeque<ListItem>,
+		terminal_rect: Rect,
+	) {
+		let Some(message) = self.queued.pop_front() else {
+			return;
+		};
+
+		// Forget messages that don't count anymore
+		while self
+			.sent
+			.front()
+			.is_some_and(|sent| sent.elapsed() > RATE_WINDOW)
+		{
+			self.sent.pop_front();
+		}
+
+		match sender.send_privmsg(channel, &message) {
+			Ok(()) => {
+				self.sent.push_back(Instant::now());
+				self.pending.push_back((message, Instant::now()));
+			}
+			Err(e) => add_failed(chat, &message, &e.to_string(), terminal_rect),
+		}
+	}
+
+	/// When the oldest pending message times out, if there is one.
+	fn expires_at(&self) -> Option<Instant> {
+		self.pending
+			.front()
+			.map(|(_, sent)| *sent + PENDING_TIMEOUT)
+	}
+
+	/// Shows pending messages that twitch hasn't answered in time as failed.
+	fn expire(&mut self, chat: &mut VecDeque<ListItem>, terminal_rect: Rect) {
+		while self
+			.pending
+			.front()
+			.is_some_and(|(_, sent)| sent.elapsed() >= PENDING_TIMEOUT)
+		{
+			let (message, _) = self
+				.pending
+				.pop_front()
+				.expect("We already know it's there");
+
+			add_failed(chat, &message, "twitch didn't answer", terminal_rect);
+		}
+	}
+
+	/// Forgets the old connection's pending messages, showing them as failed since we can't know
+	/// if twitch got them. Queued messages wait until we've joined again.
+	fn reconnected(&mut self, chat: &mut VecDeque<ListItem>, terminal_rect: Rect) {
+		for (message, _) in self.pending.drain(..) {
+			add_failed(
+				chat,
+				&message,
+				"chat reconnected before twitch answered",
+				terminal_rect,
+			);
+		}
+
+		self.joined = false;
+	}
+
+	/// Items for messages that aren't in chat yet, oldest first
+	fn items(&self) -> Vec<ListItem<'static>> {
+		self.pending
+			.iter()
+			.map(|(message, _)| ["Sending: ", message].concat())
+			.chain(
+				self.queued
+					.iter()
+					.map(|message| ["Queued: ", message].concat()),
+			)
+			.map(|text| ListItem::new(text).style(theme().log))
+			.collect()
+	}
+}
+
+/// Add a message we couldn't send to chat
+fn add_failed(chat: &mut VecDeque<ListItem>, message: &str, reason: &str, terminal_rect: Rect) {
+	add_to_queue(
+		chat,
+		ListItem::new(["✗ ", message, " (", reason, ")"].concat()).style(theme().error),
+		terminal_rect.height - 3,
+	)
 }
 
 /// Add an item to a queue, removing the first item if it's over the limit
@@ -71,13 +244,14 @@ fn handle_irc_command(
 	chat: &mut VecDeque<ListItem>,
 	info: &mut Vec<ListItem>,
 	log: &mut VecDeque<ListItem>,
+	outgoing: &mut Outgoing,
 	terminal_rect: Rect,
 ) {
 	match message.command {
 		// Welcome message
 		proto::Command::Response(_, mut response) => add_to_queue(
 			chat,
-			ListItem::new(response.swap_remove(1)),
+			ListItem::new(response.swap_remove(1)).style(theme().notice),
 			terminal_rect.height - 3,
 		),
 		proto::Command::Raw(command, response) => match &*command {
@@ -102,7 +276,8 @@ fn handle_irc_command(
 							}),
 					]
 					.concat(),
-				),
+				)
+				.style(theme().notice),
 				terminal_rect.height - 3,
 			),
 			// Chat metadata
@@ -150,18 +325,25 @@ fn handle_irc_command(
 							]
 							.concat(),
 						)),
-						"slow" => info.push(ListItem::new(
-							[
-								"Slow chat: ",
-								// "Off" if it's `None` or `0`
-								&tag.1.filter(|x| x != "0").map_or("Off".to_owned(), |x| {
-									format_seconds(
-										x.parse::<i64>().expect("Response numbers should be valid"),
-									)
-								}),
-							]
-							.concat(),
-						)),
+						"slow" => {
+							// So we know how long to wait between messages
+							outgoing.slow =
+								tag.1.as_ref().and_then(|x| x.parse().ok()).unwrap_or(0);
+
+							info.push(ListItem::new(
+								[
+									"Slow chat: ",
+									// "Off" if it's `None` or `0`
+									&tag.1.filter(|x| x != "0").map_or("Off".to_owned(), |x| {
+										format_seconds(
+											x.parse::<i64>()
+												.expect("Response numbers should be valid"),
+										)
+									}),
+								]
+								.concat(),
+							))
+						}
 						"subs-only" => info.push(ListItem::new(
 							[
 								"Subs only: ",
@@ -187,14 +369,14 @@ fn handle_irc_command(
 				add_to_queue(
 					chat,
 					ListItem::new(Span {
-						style: Style {
+						style: theme().notice.patch(Style {
 							fg: tags.find(|x| x.0 == "color").and_then(|x| {
 								x.1.filter(|x| !x.is_empty())
 									.as_ref()
-									.map(|x| parse_colour(&x[1..]))
+									.and_then(|x| parse_colour(&x[1..]).ok())
 							}),
 							..Style::default()
-						},
+						}),
 						content: tags
 							.find(|x| x.0 == "system-msg")
 							.expect("USERNOTICE should have a system-msg tag")
@@ -205,8 +387,66 @@ fn handle_irc_command(
 					terminal_rect.height - 3,
 				)
 			}
+			// Our badges and colour, sent when we join and after each message we send
+			"USERSTATE" => {
+				outgoing.tags = message.tags.expect("USERSTATE should have tags");
+
+				outgoing.moderator = outgoing.tags.iter().any(|tag| match &*tag.0 {
+					"mod" => tag.1.as_deref() == Some("1"),
+					"badges" => tag.1.as_ref().is_some_and(|badges| {
+						badges
+							.split(',')
+							.any(|badge| badge.starts_with("broadcaster/"))
+					}),
+					_ => false,
+				});
+
+				// The first one is for joining, the rest mean twitch accepted our message, so show
+				// it like anyone else's
+				if !outgoing.joined {
+					outgoing.joined = true;
+				} else if let Some((sent, _)) = outgoing.pending.pop_front() {
+					handle_irc_command(
+						proto::Message {
+							tags: Some(outgoing.tags.clone()),
+							prefix: None,
+							command: proto::Command::PRIVMSG(String::new(), sent),
+						},
+						chat,
+						info,
+						log,
+						outgoing,
+						terminal_rect,
+					)
+				}
+			}
 			_ => (),
 		},
+		// Notice for us, i.e. that our message was rejected or the chat mode changed
+		proto::Command::NOTICE(_, notice) => {
+			let rejected = message.tags.is_some_and(|tags| {
+				tags.iter().any(|tag| {
+					// All the reasons a message can be rejected start with this
+					tag.0 == "msg-id" && tag.1.as_ref().is_some_and(|id| id.starts_with("msg_"))
+				})
+			});
+
+			match outgoing.pending.pop_front() {
+				Some((sent, _)) if rejected => add_failed(chat, &sent, &notice, terminal_rect),
+				sent => {
+					// It wasn't about this message
+					if let Some(sent) = sent {
+						outgoing.pending.push_front(sent);
+					}
+
+					add_to_queue(
+						chat,
+						ListItem::new(notice).style(theme().notice),
+						terminal_rect.height - 3,
+					)
+				}
+			}
+		}
 		// New message
 		proto::Command::PRIVMSG(_, msg) => {
 			// The parts of the message
@@ -250,11 +490,7 @@ fn handle_irc_command(
 									// Verified
 									"partner" => vec.push(Span {
 										content: "✓".into(),
-										style: Style {
-											fg: Some(Color::White),
-											bg: Some(Color::Magenta),
-											..Style::default()
-										},
+										style: theme().badges.partner,
 									}),
 
 									"predictions" => {
@@ -272,129 +508,96 @@ fn handle_irc_command(
 											]
 											.concat()
 											.into(),
-											style: Style {
-												fg: match parts.0 {
-													"blue" => Some(Color::Blue),
-													"pink" => Some(Color::Magenta),
-													// Log unknown colour
-													c => {
-														add_to_queue(
-															log,
-															ListItem::new(
-																["Unknown colour: ", c].concat(),
-															),
-															terminal_rect.height - 3,
-														);
-
-														None
-													}
-												},
-												..Style::default()
+											style: match parts.0 {
+												"blue" => theme().badges.predictions_blue,
+												"pink" => theme().badges.predictions_pink,
+												// Log unknown colour
+												c => {
+													add_to_queue(
+														log,
+														ListItem::new(
+															["Unknown colour: ", c].concat(),
+														)
+														.style(theme().log),
+														terminal_rect.height - 3,
+													);
+
+													Style::default()
+												}
 											},
 										});
 									}
 									// This person has twitch premium, diplay a crown
 									"premium" => vec.push(Span {
 										content: "👑".into(),
-										style: Style {
-											fg: Some(Color::White),
-											bg: Some(Color::Blue),
-											..Style::default()
-										},
+										style: theme().badges.premium,
 									}),
 									// This user is a moderator
 									"moderator" => vec.push(Span {
 										// Closest to the actual moderator badge
 										content: "🗡️".into(),
-										style: Style {
-											fg: Some(Color::White),
-											bg: Some(Color::Green),
-											..Style::default()
-										},
+										style: theme().badges.moderator,
 									}),
 									// This person has had n moments on this channel.
 									// Display a camera with appropriate colours.
 									"moments" => vec.push(Span {
 										content: "📷".into(),
-										style: Style {
-											fg: Some(Color::White),
-											bg: Some(
-												match parts
-													.1
-													.parse::<u16>()
-													.expect("Moments number should be valid")
-												{
-													// Bronze (#CD7F32)
-													..20 => Color::Rgb(0xCD, 0x7F, 0x32),
-													// Silver (#C0C0C0)
-													20..60 => Color::Rgb(0xC0, 0xC0, 0xC0),
-													// Gold (#FFD700)
-													60..120 => Color::Rgb(0xFF, 0xD7, 0x00),
-													// Diamond (#B9F2FF)
-													120..200 => Color::Rgb(0xB9, 0xF2, 0xFF),
-													// Purple (#800080)
-													200.. => Color::Rgb(0x80, 0x00, 0x80),
-												},
-											),
-											..Style::default()
-										},
+										style: theme().badges.moments[match parts
+											.1
+											.parse::<u16>()
+											.expect("Moments number should be valid")
+										{
+											// Bronze
+											..20 => 0,
+											// Silver
+											20..60 => 1,
+											// Gold
+											60..120 => 2,
+											// Diamond
+											120..200 => 3,
+											// Purple
+											200.. => 4,
+										}],
 									}),
 									// This user is watching without audio
 									"no_audio" => vec.push(Span {
 										content: "🔇".into(),
-										style: Style {
-											fg: Some(Color::White),
-											bg: Some(Color::Black),
-											..Style::default()
-										},
+										style: theme().badges.no_audio,
 									}),
 									// This user is listening without video
 									// Eye with strikethrough
 									"no_video" => vec.push(Span {
 										content: "👁".into(),
-										style: Style {
-											fg: Some(Color::White),
-											bg: Some(Color::Black),
-											add_modifier: Modifier::CROSSED_OUT,
-											..Style::default()
-										},
+										style: theme().badges.no_video,
 									}),
 									// This person has gifted subs
 									"sub-gifter" => vec.push(Span {
 										content: "🎁".into(),
-										style: Style {
-											// It will always be one of these, so we don't need to
-											// parse the int.
-											// These are meant to be the same as the official
-											// colours
-											fg: Some(match parts.1 {
-												"1" => Color::Magenta,
-												"5" => Color::Cyan,
-												"10" => Color::Blue,
-												"25" => Color::Red,
-												"50" => Color::LightMagenta,
-												"100" => Color::Green,
-												_ => Color::Yellow,
-											}),
-											..Style::default()
-										},
+										// It will always be one of these, so we don't need to parse
+										// the int.
+										style: theme().badges.sub_gifter[match parts.1 {
+											"1" => 0,
+											"5" => 1,
+											"10" => 2,
+											"25" => 3,
+											"50" => 4,
+											"100" => 5,
+											_ => 6,
+										}],
 									}),
-									"subscriber" => vec.push(
-										[
+									"subscriber" => vec.push(Span {
+										content: [
 											"sub/",
 											subscriber.as_ref().map_or(parts.1, Borrow::borrow),
 											" ",
 										]
 										.concat()
 										.into(),
-									),
+										style: theme().badges.subscriber,
+									}),
 									"vip" => vec.push(Span {
 										content: "💎".into(),
-										style: Style {
-											fg: Some(Color::White),
-											bg: Some(Color::LightMagenta),
-											..Style::default()
-										},
+										style: theme().badges.vip,
 									}),
 									// Ignore other badges
 									_ => (),
@@ -412,7 +615,7 @@ fn handle_irc_command(
 								.as_ref()
 								.filter(|x| !x.is_empty())
                                 // Remove the first character of the hex code '#'
-								.map(|x| parse_colour(&x[1..])),
+								.and_then(|x| parse_colour(&x[1..]).ok()),
 							..Style::default()
 						},
 					}),
@@ -571,7 +774,7 @@ fn handle_websocket_message(
 					ListItem::new(Span {
 						content,
 						style: Style {
-							fg: Some(parse_colour(&background_color[1..])),
+							fg: parse_colour(&background_color[1..]).ok(),
 							..Style::default()
 						},
 					}),
@@ -584,10 +787,7 @@ fn handle_websocket_message(
 						content: ["👤", &video_playback_by_id.viewers.to_string()]
 							.concat()
 							.into(),
-						style: Style {
-							fg: Some(Color::Red),
-							..Style::default()
-						},
+						style: theme().viewers,
 					});
 				}
 			}
@@ -598,60 +798,82 @@ fn handle_websocket_message(
 				options
 			})
 			.into_iter()
-			.for_each(|x| add_to_queue(log, ListItem::new([x].concat()), terminal_size.height - 3)),
+			.for_each(|x| {
+				add_to_queue(
+					log,
+					ListItem::new([x].concat()).style(theme().log),
+					terminal_size.height - 3,
+				)
+			}),
 		}
 	}
 }
 
-/// Connect to a stream and display chat
+/// Connect to a stream and display chat. If `chat_only` is set, the player isn't started.
 #[tokio::main]
 pub async fn play_stream<B: Backend>(
 	terminal: &mut Terminal<B>,
-	_easy: &mut Easy,
 	login: &str,
 	id: &String,
 	qualities: &[&str],
-) {
-	let mut child = process::Command::new("streamlink")
-		.args([
-			["-p=", &PLAYER.join(" ")].concat(),
-			["twitch.tv/", login].concat(),
-			qualities.join(","),
-		])
-		.stdout(Stdio::piped())
-		.stderr(Stdio::piped())
-		.spawn()
-		.expect("Should be able to spawn streamlink");
+	chat_only: bool,
+) -> Result<(), Error> {
+	let mut child = (!chat_only)
+		.then(|| {
+			process::Command::new("streamlink")
+				.args([
+					["-p=", &config().player.join(" ")].concat(),
+					["twitch.tv/", login].concat(),
+					qualities.join(","),
+				])
+				.stdout(Stdio::piped())
+				.stderr(Stdio::piped())
+				.spawn()
+				.map_err(|e| Error::new("Starting streamlink", ErrorKind::Io(e)))
+		})
+		.transpose()?;
 
 	// So we can add it to the log
-	let mut stdout_reader = BufReader::new(
-		child
-			.stdout
-			.as_mut()
-			.expect("Should be able to access command stdout"),
-	)
-	.lines();
-	let mut stderr_reader = BufReader::new(
-		child
-			.stderr
-			.as_mut()
-			.expect("Should be able to access command stdout"),
-	)
-	.lines();
+	let mut stdout_reader = child.as_mut().map(|child| {
+		BufReader::new(
+			child
+				.stdout
+				.take()
+				.expect("Should be able to access command stdout"),
+		)
+		.lines()
+	});
+	let mut stderr_reader = child.as_mut().map(|child| {
+		BufReader::new(
+			child
+				.stderr
+				.take()
+				.expect("Should be able to access command stderr"),
+		)
+		.lines()
+	});
 
 	// Connect to IRC
-	let mut client_stream = connect_irc_client(login).await;
+	let (mut client_stream, mut sender) = connect_irc_client(login, &config().endpoints).await?;
+
+	// Where our messages go
+	let channel = ["#", login].concat();
 
 	// Connect to websocket
-	let mut web_socket_stream = connect_async("wss://pubsub-edge.twitch.tv/v1")
+	let mut web_socket_stream = connect_async(config().endpoints.pubsub.as_str())
 		.await
-		.expect("Should be able to connect to twitch websocket")
+		.map_err(|e| Error::new("Connecting to PubSub", ErrorKind::Other(e.to_string())))?
 		.0;
 
 	// Ping every 4 minutes so it doesn't time out
 	// It could be up to 7 minutes, but this is what the webapp does
 	let mut ping_interval = interval(Duration::new(4 * 60, 0));
 
+	// Some topics need to know who we are
+	let auth_token = token().map_or(String::new(), |token| {
+		[",\"auth_token\":\"", &token.access_token, "\""].concat()
+	});
+
 	// Listen to all the events that the web client does, minus "ads"/"ad-property-refresh"
 	// The twitch websocket requires you to send each as an individual packet
 	for topic in [
@@ -694,6 +916,7 @@ pub async fn play_stream<B: Backend>(
                             \"topics\":[\
                                 \"", topic, ".", id, "\"\
                             ]\
+                            ", &auth_token, "\
                         }\
                     }"
                 ].concat().to_owned(),
@@ -704,6 +927,8 @@ pub async fn play_stream<B: Backend>(
 	// Input (but async)
 	let mut event_stream = EventStream::new();
 
+	let mut keys = KeyMap::chat(&config().keys.chat);
+
 	// Tab selected
 	let mut tab = 0usize;
 
@@ -726,31 +951,43 @@ pub async fn play_stream<B: Backend>(
 	// View count
 	let mut viewers = Paragraph::new(Span {
 		content: "👤".into(),
-		style: Style {
-			fg: Some(Color::Red),
-			..Style::default()
-		},
+		style: theme().viewers,
 	});
 
+	// Messages we've written
+	let mut outgoing = Outgoing::default();
+
+	// The message being written, if we're writing one
+	let mut input = None::<String>;
+
 	// Run until streamlink dies
 	//while let Ok(None) = child.try_wait() {
 
 	// Run until the user inputs 'q'
 	loop {
+		// When we can send the next message we've written
+		let ready_at = outgoing.ready_at();
+		// When we give up on twitch answering the oldest one we've sent
+		let expires_at = outgoing.expires_at();
+
 		// Wait for either a new message or keyboard input
 		tokio::select! {
 			// Read output from streamlink - add it to the log
-			Ok(Some(line)) = stdout_reader.next_line() => add_to_queue(
+			Some(Ok(Some(line))) = OptionFuture::from(
+				stdout_reader.as_mut().map(Lines::next_line)
+			), if stdout_reader.is_some() => add_to_queue(
 				&mut log,
-				ListItem::new(line),
+				ListItem::new(line).style(theme().log),
 				terminal
 					.size()
 					.expect("Should be able to get terminal dimensions")
 					.height - 3
 			),
-			Ok(Some(line)) = stderr_reader.next_line() => add_to_queue(
+			Some(Ok(Some(line))) = OptionFuture::from(
+				stderr_reader.as_mut().map(Lines::next_line)
+			), if stderr_reader.is_some() => add_to_queue(
 				&mut log,
-				ListItem::new(line),
+				ListItem::new(line).style(theme().log),
 				terminal
 					.size()
 					.expect("Should be able to get terminal dimensions")
@@ -763,21 +1000,43 @@ pub async fn play_stream<B: Backend>(
 					&mut chat,
 					&mut info,
 					&mut log,
+					&mut outgoing,
 					terminal.size().expect("Should be able to get terminal dimensions")
 				)
 			} else {
 				// The connection failed, let's try again
 				add_to_queue(
 					&mut log,
-					ListItem::new("IRC connection failed, retrying"),
+					ListItem::new("IRC connection failed, retrying").style(theme().log),
 					terminal
 						.size()
 						.expect("Should be able to get terminal dimensions")
 						.height - 3
 				);
 
-				client_stream = connect_irc_client(login).await;
+				outgoing.reconnected(
+					&mut chat,
+					terminal.size().expect("Should be able to get terminal dimensions")
+				);
+
+				(client_stream, sender) = connect_irc_client(login, &config().endpoints).await?;
 			},
+			// Send the next message we've written once twitch will let us
+			_ = sleep_until(ready_at.unwrap_or_else(Instant::now)), if ready_at.is_some() => {
+				outgoing.send_next(
+					&sender,
+					&channel,
+					&mut chat,
+					terminal.size().expect("Should be able to get terminal dimensions")
+				)
+			}
+			// Twitch didn't answer a message we sent
+			_ = sleep_until(expires_at.unwrap_or_else(Instant::now)), if expires_at.is_some() => {
+				outgoing.expire(
+					&mut chat,
+					terminal.size().expect("Should be able to get terminal dimensions")
+				)
+			}
 			// Read from websocket
 			Some(Ok(protocol::Message::Text(text))) = web_socket_stream.next() => {
 				handle_websocket_message(
@@ -801,13 +1060,50 @@ pub async fn play_stream<B: Backend>(
 			// Read keyboard input
 			Some(Ok(event)) = event_stream.next() => {
 				match event {
-					Event::Key(key) => match key.code {
-						// Quit
-						KeyCode::Char('Q' | 'q') => break,
+					// Writing a message, keys are text
+					Event::Key(KeyEvent { code, .. }) if input.is_some() => {
+						let text = input.as_mut().expect("We already know we're writing");
+
+						match code {
+							KeyCode::Char(c) if text.chars().count() < MESSAGE_LENGTH => {
+								text.push(c)
+							}
+							KeyCode::Backspace => {
+								text.pop();
+							}
+							// Keep writing after sending, like the webapp
+							KeyCode::Enter => {
+								if !text.trim().is_empty() {
+									outgoing.queued.push_back(text.trim().to_owned());
+								}
+
+								text.clear();
+							}
+							// Stop writing
+							KeyCode::Esc => input = None,
+							_ => (),
+						}
+					}
+					Event::Key(key) => match keys.feed(key) {
+						Some(Action::Quit) => break,
 						// Select next tab to the left
-						KeyCode::Left => tab = tab.saturating_sub(1),
+						Some(Action::TabLeft) => tab = tab.saturating_sub(1),
 						// Select next tab to the right
-						KeyCode::Right => if tab != 2 { tab += 1 },
+						Some(Action::TabRight) => if tab != 2 { tab += 1 },
+						// Start writing in the chat tab
+						Some(Action::Message) => if token().is_some() {
+							tab = 0;
+							input = Some(String::new());
+						} else {
+							add_to_queue(
+								&mut log,
+								ListItem::new("Log in with `--login` to chat").style(theme().log),
+								terminal
+									.size()
+									.expect("Should be able to get terminal dimensions")
+									.height - 3
+							);
+						},
 						_ => ()
 					},
 					Event::Resize(_, height) => {
@@ -832,13 +1128,11 @@ pub async fn play_stream<B: Backend>(
 					.block(
 						Block::default()
 							.borders(Borders::ALL)
-							.title_alignment(TITLE_ALIGNMENT)
-							.border_type(BORDER_TYPE),
+							.title_alignment(config().title_alignment)
+							.border_type(config().border_type)
+							.border_style(theme().border),
 					)
-					.highlight_style(Style {
-						add_modifier: Modifier::REVERSED,
-						..Style::default()
-					})
+					.highlight_style(theme().highlight)
 					.select(tab),
 				Rect {
 					height: 3,
@@ -857,11 +1151,24 @@ pub async fn play_stream<B: Backend>(
 				},
 			);
 
+			// Our unsent messages and the input line go under chat, if we can write
+			let unsent = outgoing.items();
+			let bottom_height = if tab == 0 && token().is_some() {
+				(unsent.len() as u16 + 1).min((frame.size().height - 3) / 2)
+			} else {
+				0
+			};
+			let list_height = frame.size().height - 3 - bottom_height;
+
 			frame.render_widget(
 				List::new(
 					// Which list should we render
 					match tab {
-						0 => chat.clone().into(),
+						// The newest messages, if some are hidden by what's under it
+						0 => chat
+							.range(chat.len().saturating_sub(list_height as usize)..)
+							.cloned()
+							.collect(),
 						1 => info.clone(),
 						2 => log.clone().into(),
 						// We make sure it doesn't go past the bounds
@@ -872,9 +1179,467 @@ pub async fn play_stream<B: Backend>(
 					x: 1,
 					y: 3,
 					width: frame.size().width - 2,
-					height: frame.size().height - 3,
+					height: list_height,
 				},
 			);
+
+			if bottom_height != 0 {
+				frame.render_widget(
+					List::new(unsent),
+					Rect {
+						x: 1,
+						y: 3 + list_height,
+						width: frame.size().width - 2,
+						height: bottom_height - 1,
+					},
+				);
+
+				let input_rect = Rect {
+					x: 1,
+					y: frame.size().height - 1,
+					width: frame.size().width - 2,
+					height: 1,
+				};
+
+				if let Some(text) = &input {
+					let line = Span::raw(["> ", text].concat());
+
+					// Keep the end of the message, where the cursor is, in view
+					let scroll = (line.width() as u16 + 1).saturating_sub(input_rect.width);
+
+					frame.set_cursor(input_rect.x + line.width() as u16 - scroll, input_rect.y);
+					frame.render_widget(Paragraph::new(line).scroll((0, scroll)), input_rect);
+				} else {
+					frame.render_widget(
+						Paragraph::new(keys.help(Action::Message)).style(theme().help),
+						input_rect,
+					);
+				}
+			}
 		});
 	}
+
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	//! Messages come from [`twitch_curses::mock`], either straight from the functions that write
+	//! them or over a connection to it's servers.
+
+	use ratatui::buffer::Buffer;
+	use ratatui::widgets::Widget;
+	use twitch_curses::mock::{irc_replies, Options, CHAT};
+
+	use super::*;
+
+	/// Big enough that nothing is wrapped or scrolled away
+	const RECT: Rect = Rect {
+		x: 0,
+		y: 0,
+		width: 200,
+		height: 50,
+	};
+
+	/// What the chat UI keeps track of
+	#[derive(Default)]
+	struct Lists {
+		chat: VecDeque<ListItem<'static>>,
+		info: Vec<ListItem<'static>>,
+		log: VecDeque<ListItem<'static>>,
+		outgoing: Outgoing,
+	}
+	impl Lists {
+		/// After the `USERSTATE` for joining
+		fn joined() -> Self {
+			Self {
+				outgoing: Outgoing {
+					joined: true,
+					..Outgoing::default()
+				},
+				..Self::default()
+			}
+		}
+
+		/// Pretends we've just sent `message`
+		fn send(&mut self, message: &str) {
+			self.outgoing
+				.pending
+				.push_back((message.to_owned(), Instant::now()));
+		}
+
+		fn handle(&mut self, message: proto::Message) {
+			handle_irc_command(
+				message,
+				&mut self.chat,
+				&mut self.info,
+				&mut self.log,
+				&mut self.outgoing,
+				RECT,
+			)
+		}
+
+		/// Handles a line from the server
+		fn handle_line(&mut self, line: &str) {
+			self.handle(line.parse().expect("Should be a valid IRC message"))
+		}
+
+		/// Handles the mock server's replies to `line`, sent by `mockviewer` in `#mockstreamer`
+		fn reply_to(&mut self, line: &str) {
+			for reply in irc_replies(
+				line,
+				&mut "mockviewer".to_owned(),
+				&mut Some("mockstreamer".to_owned()),
+			) {
+				self.handle_line(&reply);
+			}
+		}
+
+		fn websocket(&mut self, text: &str, viewers: &mut Paragraph) {
+			handle_websocket_message(
+				text.to_owned(),
+				RECT,
+				&mut self.chat,
+				&mut self.log,
+				viewers,
+			)
+		}
+	}
+
+	/// Draws `widget` and returns each row, without trailing spaces
+	fn draw<W: Widget>(widget: W, height: u16) -> Vec<String> {
+		let area = Rect { height, ..RECT };
+		let mut buffer = Buffer::empty(area);
+
+		widget.render(area, &mut buffer);
+
+		buffer
+			.content
+			.chunks(area.width as usize)
+			.map(|row| {
+				row.iter()
+					.map(|cell| cell.symbol.as_str())
+					.collect::<String>()
+					.trim_end()
+					.to_owned()
+			})
+			.collect()
+	}
+
+	/// Each row of a list as it's drawn
+	fn rows<'a, I: IntoIterator<Item = &'a ListItem<'static>>>(items: I) -> Vec<String> {
+		let items: Vec<_> = items.into_iter().cloned().collect();
+		let height = items.iter().map(ListItem::height).sum::<usize>() as u16;
+
+		draw(List::new(items), height)
+	}
+
+	/// The view count as it's drawn
+	fn viewer_count(viewers: &Paragraph) -> String {
+		draw(viewers.clone(), 1).swap_remove(0)
+	}
+
+	#[test]
+	fn shows_chat() {
+		let mut lists = Lists::default();
+
+		for line in CHAT {
+			lists.handle_line(&line.replace("{channel}", "mockstreamer"));
+		}
+
+		let chat = rows(&lists.chat);
+		for expected in [
+			"Subbed hello chat",
+			"Moderator please be nice",
+			"Gifter gifted a Tier 1 sub to Subbed!",
+			"subbed's message was removed",
+			"predictor banned for 10 minutes",
+		] {
+			assert!(
+				chat.iter().any(|row| row.contains(expected)),
+				"{expected:?} isn't in chat: {chat:#?}"
+			);
+		}
+		assert!(lists.log.is_empty());
+	}
+
+	#[test]
+	fn shows_room_state() {
+		let mut lists = Lists::default();
+
+		lists.reply_to("NICK mockviewer");
+		lists.reply_to("JOIN #mockstreamer");
+
+		assert!(rows(&lists.chat).contains(&"Welcome, GLHF!".to_owned()));
+		assert_eq!(
+			rows(&lists.info),
+			[
+				"Emote only: Off",
+				"Followers only: 10 Minutes",
+				"Unique chat: Off",
+				"Slow chat: 30 Seconds",
+				"Subs only: Off",
+			]
+		);
+		assert_eq!(lists.outgoing.slow, 30);
+		assert!(!lists.outgoing.moderator);
+	}
+
+	#[test]
+	fn shows_accepted_messages_with_our_badges() {
+		let mut lists = Lists::joined();
+		lists.send("hi chat");
+
+		lists.reply_to("PRIVMSG #mockstreamer :hi chat");
+
+		assert!(lists.outgoing.pending.is_empty());
+		let chat = rows(&lists.chat);
+		assert_eq!(chat.len(), 1);
+		assert!(chat[0].starts_with('👑'));
+		assert!(chat[0].ends_with("mockviewer hi chat"));
+	}
+
+	#[test]
+	fn shows_rejected_messages_as_failed() {
+		let mut lists = Lists::joined();
+		lists.send("an error");
+
+		lists.reply_to("PRIVMSG #mockstreamer :an error");
+
+		assert!(lists.outgoing.pending.is_empty());
+		let chat = rows(&lists.chat);
+		assert_eq!(chat.len(), 1);
+		assert!(chat[0].starts_with("✗ an error (Your message wasn't posted"));
+	}
+
+	#[test]
+	fn other_notices_dont_fail_messages() {
+		let mut lists = Lists::joined();
+		lists.send("hello");
+
+		lists.handle_line(
+			"@msg-id=slow_on :tmi.twitch.tv NOTICE #mockstreamer :This room is now in slow mode.",
+		);
+
+		assert_eq!(lists.outgoing.pending.len(), 1);
+		assert_eq!(rows(&lists.chat), ["This room is now in slow mode."]);
+	}
+
+	#[test]
+	fn joining_doesnt_answer_messages() {
+		let mut lists = Lists::default();
+		// Left over from before a reconnect
+		lists.send("hi chat");
+		lists.outgoing.queued.push_back("later".to_owned());
+
+		assert_eq!(lists.outgoing.ready_at(), None);
+
+		lists.reply_to("JOIN #mockstreamer");
+
+		assert!(lists.outgoing.joined);
+		assert_eq!(lists.outgoing.pending.len(), 1);
+		assert!(lists.outgoing.ready_at().is_some());
+		assert!(lists.chat.is_empty());
+	}
+
+	#[test]
+	fn unanswered_messages_fail() {
+		let mut lists = Lists::joined();
+		let now = Instant::now();
+		lists.outgoing.pending.extend([
+			("old".to_owned(), now - PENDING_TIMEOUT),
+			("new".to_owned(), now),
+		]);
+
+		assert_eq!(lists.outgoing.expires_at(), Some(now));
+
+		lists.outgoing.expire(&mut lists.chat, RECT);
+
+		assert_eq!(rows(&lists.chat), ["✗ old (twitch didn't answer)"]);
+		assert_eq!(lists.outgoing.pending.len(), 1);
+		assert_eq!(lists.outgoing.expires_at(), Some(now + PENDING_TIMEOUT));
+	}
+
+	#[test]
+	fn reconnecting_fails_pending_messages() {
+		let mut lists = Lists::joined();
+		lists.send("hi chat");
+		lists.outgoing.queued.push_back("later".to_owned());
+
+		lists.outgoing.reconnected(&mut lists.chat, RECT);
+
+		assert_eq!(
+			rows(&lists.chat),
+			["✗ hi chat (chat reconnected before twitch answered)"]
+		);
+		assert_eq!(lists.outgoing.expires_at(), None);
+		assert_eq!(lists.outgoing.queued, ["later"]);
+		// Not until we've joined again
+		assert_eq!(lists.outgoing.ready_at(), None);
+	}
+
+	/// Is `outgoing` ready to send now?
+	fn ready_now(outgoing: &Outgoing) -> bool {
+		outgoing
+			.ready_at()
+			.is_some_and(|ready_at| ready_at <= Instant::now())
+	}
+
+	#[test]
+	fn ready_at_needs_something_to_send() {
+		let mut outgoing = Outgoing {
+			joined: true,
+			..Outgoing::default()
+		};
+		assert_eq!(outgoing.ready_at(), None);
+
+		outgoing.queued.push_back("hi".to_owned());
+		assert!(ready_now(&outgoing));
+	}
+
+	#[test]
+	fn ready_at_waits_for_the_rate_limit() {
+		let start = Instant::now();
+		let mut outgoing = Outgoing {
+			queued: VecDeque::from(["hi".to_owned()]),
+			sent: VecDeque::from(vec![start; RATE_LIMIT - 1]),
+			joined: true,
+			..Outgoing::default()
+		};
+		assert!(ready_now(&outgoing));
+
+		outgoing.sent.push_back(start);
+		assert_eq!(outgoing.ready_at(), Some(start + RATE_WINDOW));
+
+		// Moderators can send more
+		outgoing.moderator = true;
+		assert!(ready_now(&outgoing));
+
+		outgoing
+			.sent
+			.extend(vec![start; MODERATOR_RATE_LIMIT - RATE_LIMIT]);
+		assert_eq!(outgoing.ready_at(), Some(start + RATE_WINDOW));
+	}
+
+	#[test]
+	fn ready_at_waits_for_slow_mode() {
+		let start = Instant::now();
+		let mut outgoing = Outgoing {
+			queued: VecDeque::from(["hi".to_owned()]),
+			sent: VecDeque::from([start]),
+			slow: 30,
+			joined: true,
+			..Outgoing::default()
+		};
+		assert_eq!(outgoing.ready_at(), Some(start + Duration::from_secs(30)));
+
+		// Moderators aren't slowed down
+		outgoing.moderator = true;
+		assert!(ready_now(&outgoing));
+	}
+
+	#[test]
+	fn logs_unknown_pubsub_topics() {
+		let mut lists = Lists::default();
+		let mut viewers = Paragraph::new("");
+
+		lists.websocket(
+			r#"{"type":"MESSAGE","data":{"topic":"hype-train-events-v1.1001","message":"{}"}}"#,
+			&mut viewers,
+		);
+		// Ignored
+		lists.websocket("not JSON", &mut viewers);
+
+		assert_eq!(rows(&lists.log), ["hype-train-events-v1 {}"]);
+		assert!(lists.chat.is_empty());
+	}
+
+	/// Endpoints for new mock servers that ask clients to reconnect after a couple of seconds
+	fn reconnecting_endpoints() -> Endpoints {
+		mock(Options {
+			reconnect_after: Some(Duration::from_secs(2)),
+			..Options::default()
+		})
+		.endpoints
+	}
+
+	#[tokio::test]
+	async fn follows_pubsub_until_asked_to_reconnect() {
+		let (mut websocket, _) = connect_async(reconnecting_endpoints().pubsub.as_str())
+			.await
+			.expect("Should be able to connect to the mock server");
+
+		for topic in ["community-points-channel-v1", "video-playback-by-id"] {
+			websocket
+				.send(protocol::Message::Text(
+					[
+						r#"{"type":"LISTEN","data":{"topics":[""#,
+						topic,
+						r#".1001"]}}"#,
+					]
+					.concat(),
+				))
+				.await
+				.expect("Should be able to listen");
+		}
+
+		let mut lists = Lists::default();
+		let mut viewers = Paragraph::new("");
+		let mut asked_to_reconnect = false;
+
+		// It hangs up after asking us to reconnect
+		while let Some(Ok(message)) = websocket.next().await {
+			if let protocol::Message::Text(text) = message {
+				asked_to_reconnect |= text.contains("RECONNECT");
+
+				lists.websocket(&text, &mut viewers);
+			}
+		}
+
+		assert!(asked_to_reconnect);
+		assert_eq!(rows(&lists.chat), ["Subbed redeemed Hydrate (500)"]);
+		let viewers = viewer_count(&viewers);
+		assert!(
+			viewers
+				.trim_start_matches('👤')
+				.trim()
+				.parse::<u32>()
+				.is_ok(),
+			"{viewers:?} should be a view count"
+		);
+		// `RESPONSE`s and `RECONNECT` don't go in the log
+		assert!(lists.log.is_empty());
+	}
+
+	#[tokio::test]
+	async fn reconnects_to_chat() {
+		let endpoints = reconnecting_endpoints();
+
+		// The mock server asks us to reconnect and hangs up each time, like twitch does before
+		// restarting a server
+		for _ in 0..2 {
+			let (mut stream, _sender) = connect_irc_client("mockstreamer", &endpoints)
+				.await
+				.expect("Should be able to connect to the mock server");
+
+			let mut lists = Lists::default();
+			let mut asked_to_reconnect = false;
+
+			while let Some(Ok(message)) = stream.next().await {
+				asked_to_reconnect |= matches!(
+					&message.command,
+					proto::Command::Raw(command, _) if command == "RECONNECT"
+				);
+
+				lists.handle(message);
+			}
+
+			assert!(asked_to_reconnect);
+			// We joined the channel
+			assert_eq!(lists.info.len(), 5);
+			assert!(rows(&lists.chat)
+				.iter()
+				.any(|row| row.contains("hello chat")));
+		}
+	}
 }
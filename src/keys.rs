@@ -0,0 +1,623 @@
+//! Configurable keybindings.
+//!
+//! Each view (the browser and chat) has a [`KeyMap`] from key sequences to [`Action`]s. Sequences
+//! are written in the config as strings, see [`KeySequence`] for the format.
+
+use std::collections::HashMap;
+use std::fmt::{self, Display, Formatter};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+/// Something the user can do with a key.
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+	Quit,
+	Up,
+	Down,
+	PageUp,
+	PageDown,
+	/// Move to the first item
+	Top,
+	/// Move to the last item
+	Bottom,
+	/// Enter the selected item
+	Select,
+	Back,
+	/// Go back to the home page
+	Home,
+	/// Show followed channels
+	Following,
+	/// Open the selected item's channel
+	Channel,
+	/// Open the selected item's channel's videos
+	Videos,
+	/// Open the selected category's or channel's clips
+	Clips,
+	/// Show top categories
+	Directory,
+	/// Save or forget the selected item's channel or category
+	Bookmark,
+	/// Change how the page is sorted
+	Sort,
+	/// Change what the page shows, i.e. which type of videos, how old clips are or which tags
+	/// categories have
+	Filter,
+	Search,
+	Refresh,
+	/// Stop loading a page
+	Cancel,
+	/// Show or hide the log
+	Log,
+	QualityUp,
+	QualityDown,
+	/// Chat tabs
+	TabLeft,
+	TabRight,
+	/// Start writing a chat message
+	Message,
+}
+impl Action {
+	/// Name shown in help text
+	pub fn name(self) -> &'static str {
+		match self {
+			Action::Quit => "quit",
+			Action::Up => "up",
+			Action::Down => "down",
+			Action::PageUp => "page up",
+			Action::PageDown => "page down",
+			Action::Top => "top",
+			Action::Bottom => "bottom",
+			Action::Select => "select",
+			Action::Back => "back",
+			Action::Home => "home",
+			Action::Following => "following",
+			Action::Channel => "channel",
+			Action::Videos => "videos",
+			Action::Clips => "clips",
+			Action::Directory => "categories",
+			Action::Bookmark => "bookmark",
+			Action::Sort => "sort",
+			Action::Filter => "filter",
+			Action::Search => "search",
+			Action::Refresh => "refresh",
+			Action::Cancel => "cancel",
+			Action::Log => "log",
+			Action::QualityUp => "quality up",
+			Action::QualityDown => "quality down",
+			Action::TabLeft => "previous tab",
+			Action::TabRight => "next tab",
+			Action::Message => "write message",
+		}
+	}
+}
+
+/// One key press, with modifiers.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct KeyChord {
+	code: KeyCode,
+	modifiers: KeyModifiers,
+}
+impl KeyChord {
+	/// Parses a single chord, i.e. `q`, `PageUp` or `ctrl+d`.
+	fn parse(string: &str) -> Result<Self, String> {
+		// `+` on it's own is a key, not a separator
+		let (modifiers_string, key) = match string.rsplit_once('+') {
+			Some((modifiers, "")) if modifiers.ends_with('+') => {
+				(&modifiers[..modifiers.len() - 1], "+")
+			}
+			Some((modifiers, key)) if !modifiers.is_empty() && !key.is_empty() => (modifiers, key),
+			_ => ("", string),
+		};
+
+		let mut modifiers = KeyModifiers::NONE;
+		for modifier in modifiers_string.split('+').filter(|x| !x.is_empty()) {
+			modifiers |= match &*modifier.to_lowercase() {
+				"ctrl" | "control" => KeyModifiers::CONTROL,
+				"alt" => KeyModifiers::ALT,
+				"shift" => KeyModifiers::SHIFT,
+				_ => return Err(["Unknown modifier `", modifier, "` in `", string, "`"].concat()),
+			}
+		}
+
+		let code = match key {
+			"Up" => KeyCode::Up,
+			"Down" => KeyCode::Down,
+			"Left" => KeyCode::Left,
+			"Right" => KeyCode::Right,
+			"PageUp" => KeyCode::PageUp,
+			"PageDown" => KeyCode::PageDown,
+			"Home" => KeyCode::Home,
+			"End" => KeyCode::End,
+			"Enter" => KeyCode::Enter,
+			"Esc" => KeyCode::Esc,
+			"Backspace" => KeyCode::Backspace,
+			"Delete" => KeyCode::Delete,
+			"Tab" => KeyCode::Tab,
+			"Space" => KeyCode::Char(' '),
+			_ if key.chars().count() == 1 => {
+				KeyCode::Char(key.chars().next().expect("We already know there's a char"))
+			}
+			_ if key.starts_with('F') => KeyCode::F(
+				key[1..]
+					.parse()
+					.map_err(|_| ["Unknown key `", key, "`"].concat())?,
+			),
+			_ => return Err(["Unknown key `", key, "`"].concat()),
+		};
+
+		Ok(Self::new(code, modifiers))
+	}
+
+	/// Creates a chord, normalising it so it compares equal to the same key from the terminal.
+	fn new(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+		// The case of the character already tells us if shift was pressed
+		if let KeyCode::Char(_) = code {
+			modifiers.remove(KeyModifiers::SHIFT);
+		}
+
+		Self { code, modifiers }
+	}
+}
+impl From<KeyEvent> for KeyChord {
+	fn from(event: KeyEvent) -> Self {
+		Self::new(event.code, event.modifiers)
+	}
+}
+impl Display for KeyChord {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		for (modifier, name) in [
+			(KeyModifiers::CONTROL, "ctrl+"),
+			(KeyModifiers::ALT, "alt+"),
+			(KeyModifiers::SHIFT, "shift+"),
+		] {
+			if self.modifiers.contains(modifier) {
+				f.write_str(name)?;
+			}
+		}
+
+		match self.code {
+			KeyCode::Char(' ') => f.write_str("Space"),
+			KeyCode::Char(c) => write!(f, "{c}"),
+			KeyCode::F(n) => write!(f, "F{n}"),
+			code => write!(f, "{code:?}"),
+		}
+	}
+}
+
+/// A sequence of key presses bound to an action.
+///
+/// Chords are separated by spaces, i.e. `ctrl+w j`. A word that isn't a key name or a chord with
+/// modifiers is split into it's characters, so `gg` is `g` then `g`.
+#[derive(Deserialize, Clone, PartialEq, Debug)]
+#[serde(try_from = "String")]
+pub struct KeySequence(Vec<KeyChord>);
+impl TryFrom<String> for KeySequence {
+	type Error = String;
+
+	fn try_from(string: String) -> Result<Self, Self::Error> {
+		let mut chords = Vec::new();
+
+		for word in string.split_whitespace() {
+			match KeyChord::parse(word) {
+				Ok(chord) => chords.push(chord),
+				// Not a named key, so it's a few characters. Words that look like a mistyped key
+				// name (i.e. `Pageup`) are still errors.
+				Err(_) if !word.contains('+') && !looks_like_name(word) => chords.extend(
+					word.chars()
+						.map(|c| KeyChord::new(KeyCode::Char(c), KeyModifiers::NONE)),
+				),
+				Err(e) => return Err(e),
+			}
+		}
+
+		if chords.is_empty() {
+			Err("Key bindings can't be empty".to_owned())
+		} else {
+			Ok(Self(chords))
+		}
+	}
+}
+impl Display for KeySequence {
+	fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+		// Single characters are written together, like they're typed
+		let mut previous_was_char = false;
+
+		for (i, chord) in self.0.iter().enumerate() {
+			let is_char =
+				matches!(chord.code, KeyCode::Char(c) if c != ' ') && chord.modifiers.is_empty();
+
+			if i != 0 && !(is_char && previous_was_char) {
+				f.write_str(" ")?;
+			}
+
+			write!(f, "{chord}")?;
+
+			previous_was_char = is_char;
+		}
+
+		Ok(())
+	}
+}
+
+/// Is this word capitalised, like a key name?
+fn looks_like_name(word: &str) -> bool {
+	word.starts_with(|c: char| c.is_ascii_uppercase())
+		&& word.contains(|c: char| c.is_ascii_lowercase())
+}
+
+/// Keybinding overrides from the config. Any action in here replaces all of it's default bindings.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyConfig {
+	/// Bindings for browsing pages
+	pub browser: HashMap<Action, Vec<KeySequence>>,
+	/// Bindings for the chat view
+	pub chat: HashMap<Action, Vec<KeySequence>>,
+}
+impl KeyConfig {
+	/// Checks that no binding stops another from being typed, i.e. binding `g` to something would
+	/// mean the default `gg` for `Top` never gets past the first `g`.
+	pub fn validate(&self) -> Result<(), String> {
+		check_conflicts(&bindings(BROWSER_DEFAULTS, &self.browser), "keys.browser")?;
+
+		#[cfg(feature = "chat")]
+		check_conflicts(&bindings(CHAT_DEFAULTS, &self.chat), "keys.chat")?;
+
+		Ok(())
+	}
+}
+
+/// The defaults for every action without an override, and the overrides.
+fn bindings(
+	defaults: &[(Action, &[&str])],
+	overrides: &HashMap<Action, Vec<KeySequence>>,
+) -> Vec<(KeySequence, Action)> {
+	let mut bindings = Vec::new();
+
+	for &(action, keys) in defaults {
+		if !overrides.contains_key(&action) {
+			bindings.extend(keys.iter().map(|key| {
+				(
+					KeySequence::try_from(key.to_string())
+						.expect("Default bindings should be valid"),
+					action,
+				)
+			}));
+		}
+	}
+
+	for (&action, sequences) in overrides {
+		bindings.extend(sequences.iter().map(|sequence| (sequence.clone(), action)));
+	}
+
+	bindings
+}
+
+/// Finds a sequence that's the same as, or the start of, one for a different action. `view` is
+/// where they're set in the config, for the error.
+fn check_conflicts(bindings: &[(KeySequence, Action)], view: &str) -> Result<(), String> {
+	for (short, short_action) in bindings {
+		for (long, long_action) in bindings {
+			if short_action != long_action && long.0.starts_with(&short.0) {
+				return Err([
+					"`",
+					&short.to_string(),
+					"` (",
+					short_action.name(),
+					") and `",
+					&long.to_string(),
+					"` (",
+					long_action.name(),
+					") conflict in `",
+					view,
+					"`, ",
+					if short.0.len() == long.0.len() {
+						"they're the same keys"
+					} else {
+						"the second can never be typed"
+					},
+				]
+				.concat());
+			}
+		}
+	}
+
+	Ok(())
+}
+
+/// Default bindings for browsing pages, the first binding for each action is shown in help text.
+const BROWSER_DEFAULTS: &[(Action, &[&str])] = &[
+	(Action::Quit, &["q", "Q"]),
+	(Action::Down, &["j", "J", "Down"]),
+	(Action::Up, &["k", "K", "Up"]),
+	(Action::PageDown, &["PageDown"]),
+	(Action::PageUp, &["PageUp"]),
+	(Action::Top, &["gg"]),
+	(Action::Bottom, &["G"]),
+	(Action::Select, &["l", "L", "Right"]),
+	(Action::Back, &["b", "B", "Left"]),
+	(Action::Home, &["h", "H"]),
+	(Action::Following, &["f", "F"]),
+	(Action::Channel, &["c", "C"]),
+	(Action::Videos, &["v", "V"]),
+	(Action::Clips, &["p", "P"]),
+	(Action::Directory, &["d", "D"]),
+	(Action::Bookmark, &["m", "M"]),
+	(Action::Sort, &["o", "O"]),
+	(Action::Filter, &["t", "T"]),
+	(Action::QualityUp, &["+"]),
+	(Action::QualityDown, &["-"]),
+	(Action::Search, &["s", "S", "/"]),
+	(Action::Refresh, &["r", "R"]),
+	(Action::Cancel, &["Esc"]),
+	(Action::Log, &["`"]),
+];
+
+/// Default bindings for the chat view
+#[cfg(feature = "chat")]
+const CHAT_DEFAULTS: &[(Action, &[&str])] = &[
+	(Action::Quit, &["q", "Q"]),
+	(Action::TabLeft, &["Left"]),
+	(Action::TabRight, &["Right"]),
+	(Action::Message, &["i", "Enter"]),
+];
+
+/// Maps key sequences to actions, keeping track of partially typed sequences.
+pub struct KeyMap {
+	bindings: Vec<(KeySequence, Action)>,
+	/// Keys typed so far that are the start of a longer sequence
+	pending: Vec<KeyChord>,
+}
+impl KeyMap {
+	/// The browser key map, with overrides from the config
+	pub fn browser(overrides: &HashMap<Action, Vec<KeySequence>>) -> Self {
+		Self::new(BROWSER_DEFAULTS, overrides)
+	}
+
+	/// The chat key map, with overrides from the config
+	#[cfg(feature = "chat")]
+	pub fn chat(overrides: &HashMap<Action, Vec<KeySequence>>) -> Self {
+		Self::new(CHAT_DEFAULTS, overrides)
+	}
+
+	/// Conflicting bindings should have been rejected by [`KeyConfig::validate`].
+	fn new(defaults: &[(Action, &[&str])], overrides: &HashMap<Action, Vec<KeySequence>>) -> Self {
+		Self {
+			bindings: bindings(defaults, overrides),
+			pending: Vec::new(),
+		}
+	}
+
+	/// Handles a key press, returning the action if it completed a sequence.
+	pub fn feed(&mut self, event: KeyEvent) -> Option<Action> {
+		self.pending.push(event.into());
+
+		loop {
+			if let Some((_, action)) = self
+				.bindings
+				.iter()
+				.find(|(sequence, _)| sequence.0 == self.pending)
+			{
+				self.pending.clear();
+				return Some(*action);
+			}
+
+			// Wait for the rest of the sequence
+			if self
+				.bindings
+				.iter()
+				.any(|(sequence, _)| sequence.0.starts_with(&self.pending))
+			{
+				return None;
+			}
+
+			// This doesn't go anywhere, see if the last key means something on it's own
+			if self.pending.len() > 1 {
+				self.pending.drain(..self.pending.len() - 1);
+			} else {
+				self.pending.clear();
+				return None;
+			}
+		}
+	}
+
+	/// The first key bound to an action, for help text
+	pub fn key_name(&self, action: Action) -> String {
+		self.bindings
+			.iter()
+			.find(|(_, a)| *a == action)
+			.map_or("unbound".to_owned(), |(sequence, _)| sequence.to_string())
+	}
+
+	/// Help text line for an action, i.e. `back: b`
+	pub fn help(&self, action: Action) -> String {
+		[action.name(), ": ", &self.key_name(action)].concat()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn chord(code: KeyCode, modifiers: KeyModifiers) -> KeyChord {
+		KeyChord { code, modifiers }
+	}
+
+	fn sequence(string: &str) -> Result<KeySequence, String> {
+		KeySequence::try_from(string.to_owned())
+	}
+
+	fn overrides(bindings: &[(Action, &str)]) -> HashMap<Action, Vec<KeySequence>> {
+		let mut overrides = HashMap::new();
+		for &(action, key) in bindings {
+			overrides
+				.entry(action)
+				.or_insert_with(Vec::new)
+				.push(sequence(key).expect("Test bindings should be valid"));
+		}
+		overrides
+	}
+
+	#[test]
+	fn parses_chords() {
+		assert_eq!(
+			KeyChord::parse("q"),
+			Ok(chord(KeyCode::Char('q'), KeyModifiers::NONE))
+		);
+		assert_eq!(
+			KeyChord::parse("PageUp"),
+			Ok(chord(KeyCode::PageUp, KeyModifiers::NONE))
+		);
+		assert_eq!(
+			KeyChord::parse("ctrl+d"),
+			Ok(chord(KeyCode::Char('d'), KeyModifiers::CONTROL))
+		);
+		assert_eq!(
+			KeyChord::parse("Control+alt+Left"),
+			Ok(chord(
+				KeyCode::Left,
+				KeyModifiers::CONTROL | KeyModifiers::ALT
+			))
+		);
+		assert_eq!(
+			KeyChord::parse("F5"),
+			Ok(chord(KeyCode::F(5), KeyModifiers::NONE))
+		);
+		assert_eq!(
+			KeyChord::parse("Space"),
+			Ok(chord(KeyCode::Char(' '), KeyModifiers::NONE))
+		);
+	}
+
+	#[test]
+	fn parses_plus_as_a_key() {
+		assert_eq!(
+			KeyChord::parse("+"),
+			Ok(chord(KeyCode::Char('+'), KeyModifiers::NONE))
+		);
+		assert_eq!(
+			KeyChord::parse("ctrl++"),
+			Ok(chord(KeyCode::Char('+'), KeyModifiers::CONTROL))
+		);
+	}
+
+	#[test]
+	fn shift_is_part_of_the_character() {
+		// The terminal sends `G` with shift, which should match a binding of `G`
+		assert_eq!(KeyChord::parse("shift+G"), KeyChord::parse("G"));
+		assert_eq!(
+			KeyChord::from(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+			chord(KeyCode::Char('G'), KeyModifiers::NONE)
+		);
+		// But not for other keys
+		assert_eq!(
+			KeyChord::parse("shift+Tab"),
+			Ok(chord(KeyCode::Tab, KeyModifiers::SHIFT))
+		);
+	}
+
+	#[test]
+	fn rejects_bad_chords() {
+		assert_eq!(
+			KeyChord::parse("hyper+a"),
+			Err("Unknown modifier `hyper` in `hyper+a`".to_owned())
+		);
+		assert_eq!(
+			KeyChord::parse("ctrl+Pageup"),
+			Err("Unknown key `Pageup`".to_owned())
+		);
+		assert_eq!(KeyChord::parse("Fx"), Err("Unknown key `Fx`".to_owned()));
+	}
+
+	#[test]
+	fn splits_words_into_characters() {
+		assert_eq!(
+			sequence("gg"),
+			Ok(KeySequence(vec![
+				chord(KeyCode::Char('g'), KeyModifiers::NONE),
+				chord(KeyCode::Char('g'), KeyModifiers::NONE),
+			]))
+		);
+		assert_eq!(
+			sequence("ctrl+w j"),
+			Ok(KeySequence(vec![
+				chord(KeyCode::Char('w'), KeyModifiers::CONTROL),
+				chord(KeyCode::Char('j'), KeyModifiers::NONE),
+			]))
+		);
+	}
+
+	#[test]
+	fn rejects_bad_sequences() {
+		assert_eq!(sequence(""), Err("Key bindings can't be empty".to_owned()));
+		assert_eq!(sequence(" "), Err("Key bindings can't be empty".to_owned()));
+		// Looks like a key name, not characters
+		assert_eq!(sequence("Pageup"), Err("Unknown key `Pageup`".to_owned()));
+		assert!(sequence("ctrl+w super+j").is_err());
+	}
+
+	#[test]
+	fn sequences_display_like_the_config() {
+		for string in ["gg", "ctrl+w j", "PageDown", "Space", "g Esc", "ctrl++"] {
+			assert_eq!(
+				sequence(string).map(|sequence| sequence.to_string()),
+				Ok(string.to_owned())
+			);
+		}
+	}
+
+	#[test]
+	fn defaults_dont_conflict() {
+		assert_eq!(KeyConfig::default().validate(), Ok(()));
+	}
+
+	#[test]
+	fn rejects_prefix_conflicts() {
+		let config = KeyConfig {
+			browser: overrides(&[(Action::Refresh, "g")]),
+			..KeyConfig::default()
+		};
+		assert_eq!(
+			config.validate(),
+			Err(
+				"`g` (refresh) and `gg` (top) conflict in `keys.browser`, the second can never be \
+				 typed"
+					.to_owned()
+			)
+		);
+
+		let config = KeyConfig {
+			browser: overrides(&[(Action::Refresh, "j")]),
+			..KeyConfig::default()
+		};
+		assert!(config.validate().is_err());
+	}
+
+	#[test]
+	fn allows_replacing_a_default() {
+		// `j` isn't bound to `Down` anymore, so it's free
+		let config = KeyConfig {
+			browser: overrides(&[(Action::Down, "Down"), (Action::Refresh, "j")]),
+			..KeyConfig::default()
+		};
+		assert_eq!(config.validate(), Ok(()));
+	}
+
+	#[test]
+	fn feeds_sequences() {
+		let mut keys = KeyMap::browser(&HashMap::new());
+		let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+		assert_eq!(keys.feed(key('g')), None);
+		assert_eq!(keys.feed(key('g')), Some(Action::Top));
+		// A sequence that goes nowhere still lets it's last key count
+		assert_eq!(keys.feed(key('g')), None);
+		assert_eq!(keys.feed(key('j')), Some(Action::Down));
+		assert_eq!(
+			keys.feed(KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT)),
+			Some(Action::Bottom)
+		);
+		assert_eq!(keys.feed(key('z')), None);
+	}
+}
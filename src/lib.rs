@@ -0,0 +1,33 @@
+//! A client for twitch's API, without the UI. It can browse the home page, searches, categories and
+//! channels, and get playlist URLs for streams, VODs and clips, returning plain types instead of
+//! twitch's JSON.
+//!
+//! ```no_run
+//! use twitch_curses::{Client, ClientOptions};
+//!
+//! let mut client = Client::new(ClientOptions::default())?;
+//!
+//! let results = client.search("speedrun")?;
+//! if let Some(category) = results.categories.items.first() {
+//!     for stream in category.streams(&mut client, None)?.items {
+//!         println!("{}: {}", stream.channel.display_name, stream.title);
+//!     }
+//! }
+//! # Ok::<(), twitch_curses::Error>(())
+//! ```
+
+pub mod client;
+pub mod error;
+pub mod fixtures;
+pub mod gql;
+pub mod log;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod models;
+pub mod options;
+pub mod request;
+
+pub use client::{Client, ClientOptions};
+pub use error::{Error, ErrorKind};
+pub use models::*;
+pub use options::*;
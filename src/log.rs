@@ -0,0 +1,38 @@
+//! Warnings that aren't worth interrupting the user for, shown in the browser's log view.
+//!
+//! Anything can add to the log with [`warn`], including the request worker thread and
+//! [`crate::request`]. Programs using the library can read it with [`lines`].
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use chrono::Local;
+
+/// Most lines kept, older ones are dropped
+const LIMIT: usize = 500;
+
+/// Lines in the log, oldest first
+static LOG: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+/// Adds a warning to the log, with the time. Drops the oldest line if the log is full.
+pub fn warn(message: String) {
+	if let Ok(mut log) = LOG.lock() {
+		if log.len() == LIMIT {
+			log.pop_front();
+		}
+
+		log.push_back([&Local::now().format("%H:%M:%S").to_string(), " ", &message].concat());
+	}
+}
+
+/// Everything in the log, up to the last 500 lines
+pub fn lines() -> Vec<String> {
+	LOG.lock()
+		.map(|log| log.iter().cloned().collect())
+		.unwrap_or_default()
+}
+
+/// Number of lines in the log
+pub fn len() -> usize {
+	LOG.lock().map_or(0, |log| log.len())
+}
@@ -4,32 +4,66 @@
 #![feature(stmt_expr_attributes)]
 
 use std::io::stdout;
+use std::process::exit;
+use std::time::Duration;
 
 use config::*;
-use crossterm::event::{read, Event, KeyCode, KeyEvent};
+use crossterm::event::{poll, read, Event};
 use crossterm::execute;
 use crossterm::terminal::{
 	disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
-use curl::easy::{self, Easy};
+use twitch_curses::{
+	error, fixtures, log, request, toggle, Client, ClipPeriod, ClipSort, ClipSource, SearchIndex,
+	SearchResults, StreamOptions, VideoSort, VideoType,
+};
 
+mod auth;
+mod bookmarks;
+mod cli;
 mod config;
 #[cfg(feature = "chat")]
 mod irc;
+mod keys;
+mod search;
 mod structs;
+mod theme;
 mod utils;
+mod worker;
 use std::panic::{set_hook, take_hook};
 
 use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Alignment, Rect};
-use ratatui::widgets::{Block, Borders, List, ListState, Paragraph};
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::{Block, Borders, Clear, Gauge, ListState, Paragraph, Wrap};
 use ratatui::Terminal;
-use simd_json::from_slice;
 use structs::*;
 
+use crate::bookmarks::{Bookmark, Bookmarks};
+use crate::cli::Args;
+use crate::error::Error;
+use crate::keys::{Action, KeyMap};
+use crate::log::warn;
+use crate::search::{search_box, History, Submitted};
+use crate::theme::theme;
 use crate::utils::*;
+use crate::worker::{Progress, Worker};
+
+/// How close the cursor gets to the end of a section before more of it is loaded
+const LOAD_MORE_DISTANCE: usize = 5;
+
+/// Something the worker has finished loading
+enum Loaded {
+	/// A page, with the item to select
+	Page(Page, usize, Result<Widgets<'static>, Error>),
+	/// More items for the section ending at this index
+	More(usize, More, Result<Widgets<'static>, Error>),
+	/// What the selected item needs to be played or opened
+	Selected(Result<Selected, Error>),
+}
 
 /// Current page + information on previous pages
+#[derive(Clone)]
 enum Page {
 	/// Home page, where the program starts
 	Home {
@@ -39,6 +73,8 @@ enum Page {
 	/// A category
 	Game {
 		name: String,
+		/// Sort and filters, kept so going back restores them
+		options: StreamOptions,
 		selection: usize,
 		/// Previous page, needs to be on heap to avoid recursive type
 		previous: Box<Page>,
@@ -46,86 +82,206 @@ enum Page {
 	/// Search page
 	Search {
 		query: String,
+		/// What to search for, `None` for everything in `search_targets`
+		scope: Option<SearchIndex>,
+		selection: usize,
+		previous: Box<Page>,
+	},
+	/// Channels the logged in user follows
+	Following {
+		selection: usize,
+		previous: Box<Page>,
+	},
+	/// A channel's videos, clips, schedule and about
+	Channel {
+		login: String,
+		selection: usize,
+		previous: Box<Page>,
+	},
+	/// All of a channel's videos of one type
+	Videos {
+		login: String,
+		kind: VideoType,
+		sort: VideoSort,
+		selection: usize,
+		previous: Box<Page>,
+	},
+	/// A channel's or category's clips
+	Clips {
+		source: ClipSource,
+		period: ClipPeriod,
+		sort: ClipSort,
+		selection: usize,
+		previous: Box<Page>,
+	},
+	/// Top categories
+	Directory {
+		/// Only categories with all of these
+		tags: Vec<String>,
 		selection: usize,
 		previous: Box<Page>,
 	},
 }
 impl Page {
 	/// Sends this page's request and returns the ratatui widgets.
-	fn request<'a>(&self, easy: &mut Easy) -> (List<'a>, Vec<(Paragraph<'a>, Node)>) {
-		match self {
-			Page::Home { .. } => match HOME_PAGE {
-				HomePage::PersonalSection => from_slice::<TwitchResponse<PersonalSectionData>>(
-					&mut request(easy, &TwitchRequest::<PersonalSectionsVariables>::default()),
-				)
-				.expect("Response should be valid JSON")
-				.data
-				.into_widgets(),
-				HomePage::Shelves => from_slice::<TwitchResponse<ShelvesData>>(&mut request(
-					easy,
-					&TwitchRequest::<ShelvesVariables>::default(),
-				))
-				.expect("Response should be valid JSON")
-				.data
+	fn request<'a>(&self, client: &mut Client) -> Result<Widgets<'a>, Error> {
+		Ok(match self {
+			Page::Home { .. } => match &config().home_page {
+				HomePage::PersonalSection => client
+					.personal_sections(
+						&config().requests.personal_sections,
+						config().requests.context_channel_name.as_deref(),
+					)?
+					.into_widgets(),
+				HomePage::Shelves => client.shelves()?.into_widgets(),
+				HomePage::Game(name) => client
+					.category_streams(name, &config().requests.stream_options(), None)?
+					.into_widgets(),
+				HomePage::Search(search) => {
+					let (scope, query) = SearchIndex::from_prefix(search);
+
+					Page::search(client, query, scope)?.into_widgets()
+				}
+				HomePage::Following => client.followed_channels()?.into_widgets(),
+				HomePage::Directory => client
+					.top_categories(&self.directory_tags(), None)?
+					.into_widgets(),
+			},
+			Page::Game { name, options, .. } => {
+				client.category_streams(name, options, None)?.into_widgets()
+			}
+			Page::Search { query, scope, .. } => {
+				Page::search(client, query, *scope)?.into_widgets()
+			}
+			Page::Following { .. } => client.followed_channels()?.into_widgets(),
+			Page::Channel { login, .. } => client.channel(login)?.into_widgets(),
+			Page::Videos {
+				login, kind, sort, ..
+			} => client
+				.channel_videos(login, *kind, *sort, None)?
 				.into_widgets(),
-				HomePage::Game(name) => from_slice::<TwitchResponse<GameData>>(&mut request(
-					easy,
-					&TwitchRequest {
-						variables: DirectoryPage_GameVariables {
-							name: name.to_owned(),
-							..TwitchRequest::default().variables
-						},
-						..TwitchRequest::default()
-					},
-				))
-				.expect("Response should be valid JSON")
-				.data
+			Page::Clips {
+				source,
+				period,
+				sort,
+				..
+			} => client.clips(source, *period, *sort, None)?.into_widgets(),
+			Page::Directory { .. } => client
+				.top_categories(&self.directory_tags(), None)?
 				.into_widgets(),
-				HomePage::Search(query) => {
-					from_slice::<TwitchResponse<SearchForData>>(&mut request(
-						easy,
-						&TwitchRequest {
-							variables: SearchResultsVariables {
-								query: query.to_owned(),
-								..TwitchRequest::default().variables
-							},
-							..TwitchRequest::default()
-						},
-					))
-					.expect("Response should be valid JSON")
-					.data
-					.into_widgets()
-				}
+		})
+	}
+
+	/// Searches for `query`, only in `scope` if it's set
+	fn search(
+		client: &mut Client,
+		query: &str,
+		scope: Option<SearchIndex>,
+	) -> Result<SearchResults, Error> {
+		match scope {
+			Some(index) => client.search_in(query, &[index], None),
+			None => client.search_in(query, config().requests.search_indexes(), None),
+		}
+	}
+
+	/// Tags the top categories on this page are filtered by
+	fn directory_tags(&self) -> Vec<String> {
+		match self {
+			Page::Directory { tags, .. } => tags.clone(),
+			_ => config().requests.directory_tags.clone(),
+		}
+	}
+
+	/// Sends the request for more items of one of this page's sections.
+	fn request_more<'a>(&self, more: &More, client: &mut Client) -> Result<Widgets<'a>, Error> {
+		// Top categories are just filtered by tags
+		if let More::Directory(cursor) = more {
+			return Ok(client
+				.top_categories(&self.directory_tags(), Some(cursor))?
+				.into_widgets());
+		}
+
+		// The category, search query or channel this page is for
+		let name: &str = match self {
+			Page::Home { .. } => match &config().home_page {
+				HomePage::Game(name) => name,
+				HomePage::Search(search) => SearchIndex::from_prefix(search).1,
+				// Only categories and searches have more items
+				HomePage::PersonalSection
+				| HomePage::Shelves
+				| HomePage::Following
+				| HomePage::Directory => unreachable!(),
 			},
-			Page::Game { name, .. } => from_slice::<TwitchResponse<GameData>>(&mut request(
-				easy,
-				&TwitchRequest {
-					variables: DirectoryPage_GameVariables {
-						name: name.clone(),
-						..TwitchRequest::default().variables
-					},
-					..TwitchRequest::default()
-				},
-			))
-			.expect("Response should be valid JSON")
-			.data
-			.into_widgets(),
-			Page::Search { query, .. } => {
-				from_slice::<TwitchResponse<SearchForData>>(&mut request(
-					easy,
-					&TwitchRequest {
-						variables: SearchResultsVariables {
-							query: query.clone(),
-							..TwitchRequest::default().variables
-						},
-						..TwitchRequest::default()
-					},
-				))
-				.expect("Response should be valid JSON")
-				.data
+			Page::Game { name, .. } => name,
+			Page::Search { query, .. } => query,
+			Page::Videos { login, .. } => login,
+			Page::Clips { source, .. } => source.name(),
+			Page::Following { .. } | Page::Channel { .. } | Page::Directory { .. } => {
+				unreachable!()
+			}
+		};
+
+		Ok(match more {
+			More::Streams(cursor) => {
+				let options = match self {
+					Page::Game { options, .. } => options.clone(),
+					_ => config().requests.stream_options(),
+				};
+
+				client
+					.category_streams(name, &options, Some(cursor))?
+					.into_widgets()
+			}
+			More::Search(index, cursor) => client
+				.search_in(name, &[*index], Some(cursor))?
 				.into_widgets()
+				// It's already shown
+				.without_header(),
+			More::Videos(cursor) => {
+				let Page::Videos { kind, sort, .. } = self else {
+					unreachable!()
+				};
+
+				client
+					.channel_videos(name, *kind, *sort, Some(cursor))?
+					.into_widgets()
 			}
-		}
+			More::Clips(cursor) => {
+				let Page::Clips {
+					source,
+					period,
+					sort,
+					..
+				} = self
+				else {
+					unreachable!()
+				};
+
+				client
+					.clips(source, *period, *sort, Some(cursor))?
+					.into_widgets()
+			}
+			More::Directory(_) => unreachable!(),
+		})
+	}
+
+	/// Starts loading this page in the background, selecting `selection` once it's loaded.
+	fn load(self, worker: &mut Worker<Loaded>, selection: usize) {
+		worker.start(
+			["Loading ", &self.to_string()].concat(),
+			move |client, _| {
+				let result = self.request(client);
+				Loaded::Page(self, selection, result)
+			},
+		);
+	}
+
+	/// Starts loading more items for the section ending at `end`.
+	fn load_more(self, worker: &mut Worker<Loaded>, end: usize, more: More) {
+		worker.start("Loading more".to_owned(), move |client, _| {
+			let result = self.request_more(&more, client);
+			Loaded::More(end, more, result)
+		});
 	}
 
 	/// Selects the given item and returns `self`
@@ -136,6 +292,21 @@ impl Page {
 		}
 		| Page::Search {
 			ref mut selection, ..
+		}
+		| Page::Following {
+			ref mut selection, ..
+		}
+		| Page::Channel {
+			ref mut selection, ..
+		}
+		| Page::Videos {
+			ref mut selection, ..
+		}
+		| Page::Clips {
+			ref mut selection, ..
+		}
+		| Page::Directory {
+			ref mut selection, ..
 		}) = self;
 		*selection = s;
 
@@ -146,42 +317,149 @@ impl Page {
 	fn get_selection(&self) -> usize {
 		let (Page::Home { selection }
 		| Page::Game { selection, .. }
-		| Page::Search { selection, .. }) = self;
+		| Page::Search { selection, .. }
+		| Page::Following { selection, .. }
+		| Page::Channel { selection, .. }
+		| Page::Videos { selection, .. }
+		| Page::Clips { selection, .. }
+		| Page::Directory { selection, .. }) = self;
 
 		*selection
 	}
 }
+
+/// What the error being shown came from, so we know how to retry it
+enum Failed {
+	/// Loading a page, with the item to select
+	Load(Page, usize),
+	/// Loading more items for the section ending at this index
+	More(usize, More),
+	/// Selecting an item
+	Select,
+}
+
 impl ToString for Page {
 	/// Get this page's title
 	fn to_string(&self) -> String {
 		match self {
 			Page::Home { .. } => "Home".to_owned(),
-			Page::Game { name, .. } => name.clone(),
-			Page::Search { query, .. } => query.clone(),
+			Page::Game { name, options, .. } => [name, ": ", &options.describe()].concat(),
+			Page::Search { query, scope, .. } => {
+				[query, " (", &describe_scope(*scope), ")"].concat()
+			}
+			Page::Following { .. } => "Following".to_owned(),
+			Page::Channel { login, .. } => login.clone(),
+			Page::Videos {
+				login, kind, sort, ..
+			} => [login, ": ", kind.name(), ", ", sort.name()].concat(),
+			Page::Clips {
+				source,
+				period,
+				sort,
+				..
+			} => [
+				source.name(),
+				": Clips from ",
+				period.name(),
+				", ",
+				sort.name(),
+			]
+			.concat(),
+			Page::Directory { tags, .. } if tags.is_empty() => "Top categories".to_owned(),
+			Page::Directory { tags, .. } => ["Top categories: ", &tags.join(", ")].concat(),
 		}
 	}
 }
 
 fn main() {
-	// Default to ["best"]
-	let mut qualities = if QUALITY.is_empty() {
+	let Args {
+		config: config_path,
+		channel,
+		game,
+		search,
+		quality,
+		chat_only,
+		home,
+		fixtures: fixture_mode,
+		login,
+		logout,
+	} = Args::parse();
+
+	if let Some(mode) = fixture_mode {
+		fixtures::set(mode);
+	}
+
+	// Load the config before we touch the terminal, so errors are readable
+	let mut loaded_config = config::load(config_path.as_deref()).unwrap_or_else(|e| {
+		eprintln!("{e}");
+		exit(1)
+	});
+
+	if let Some(home) = home {
+		loaded_config.home_page = home;
+	}
+
+	config::set(loaded_config);
+
+	if login {
+		match auth::login() {
+			Ok(token) => println!("Logged in as {}", token.login),
+			Err(e) => {
+				eprintln!("{e}");
+				exit(1)
+			}
+		}
+
+		return;
+	}
+
+	if logout {
+		if let Err(e) = auth::logout() {
+			eprintln!("{e}");
+			exit(1)
+		}
+
+		println!("Logged out");
+		return;
+	}
+
+	// Anonymous if this fails, the reason is in the log
+	auth::set(auth::load().unwrap_or_else(|e| {
+		warn(["Couldn't load login: ", &e.to_string()].concat());
+		None
+	}));
+
+	// Command line qualities take priority, then default to ["best"]
+	let mut qualities = if !quality.is_empty() {
+		quality.iter().map(String::as_str).collect()
+	} else if config().quality.is_empty() {
 		vec!["best"]
 	} else {
-		let mut vec = Vec::with_capacity(QUALITY.len());
-		QUALITY.clone_into(&mut vec);
-
-		vec
+		config().quality.iter().map(String::as_str).collect()
 	};
 
-	let mut easy = Easy::new();
-	let _ = easy.url("https://gql.twitch.tv/gql");
-	let _ = easy.post(true);
+	// Used for the channel from the command line, everything else is loaded by the worker
+	let mut client = client();
 
-	let mut easy_list = easy::List::new();
-	for header in HEADERS {
-		let _ = easy_list.append(header);
-	}
-	let _ = easy.http_headers(easy_list);
+	// Look the channel up before starting the UI, so we can exit if it doesn't exist
+	let channel_node = channel.map(|login| {
+		client
+			// We only need channels
+			.search_in(&login, &[SearchIndex::Channel], None)
+			.unwrap_or_else(|e| {
+				eprintln!("{e}");
+				exit(1)
+			})
+			.channels
+			.items
+			.iter()
+			.find(|channel| channel.login.eq_ignore_ascii_case(&login))
+			.map(Node::stream)
+			.unwrap_or_else(|| {
+				eprintln!("Couldn't find channel: {login}");
+				exit(1)
+			})
+	});
 
 	let hook = take_hook();
 	// Run cleanup code on panic
@@ -194,26 +472,64 @@ fn main() {
 	let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))
 		.expect("Should be able to initialize terminal");
 
-	if DOWNLOAD_PROGRESS {
-		// Display download progress
-		let _ = easy.progress(true);
-		let _ = easy.progress_function(|_, downloaded, _, _| {
-			// POST requests don't return `Content-length`, so we can't know how long it will be
-			print!("\r{} KiB recieved", downloaded as u32 / 1024);
-
-			true
-		});
-	}
+	let mut page = if let Some(name) = game {
+		Page::Game {
+			name,
+			options: config().requests.stream_options(),
+			selection: 0,
+			previous: Box::new(Page::Home { selection: 0 }),
+		}
+	} else if let Some(search) = search {
+		let (scope, query) = SearchIndex::from_prefix(&search);
 
-	let mut page = Page::Home { selection: 0 };
+		Page::Search {
+			query: query.to_owned(),
+			scope,
+			selection: 0,
+			previous: Box::new(Page::Home { selection: 0 }),
+		}
+	} else {
+		Page::Home { selection: 0 }
+	};
 
 	// Init crossterm
 	let _ = enable_raw_mode();
 
 	let _ = execute!(stdout(), EnterAlternateScreen);
 
-	// Fetch data
-	let (mut list, mut info_vec) = page.request(&mut easy);
+	// Just play the channel and exit
+	if let Some(node) = channel_node {
+		let result = node
+			.resolve(&mut client, &Progress::default(), &qualities)
+			.and_then(|selected| selected.run(&mut terminal, &qualities, chat_only));
+
+		let _ = disable_raw_mode();
+		let _ = execute!(stdout(), LeaveAlternateScreen);
+
+		if let Err(e) = result {
+			eprintln!("{e}");
+			exit(1)
+		}
+
+		return;
+	}
+
+	// Error to show over the page, if something failed
+	let mut error = None;
+
+	// Past searches and bookmarks, for the search box
+	let mut history = History::load();
+	let mut bookmarks = Bookmarks::load();
+
+	// Fetch data in the background, there's nothing to show until it's done
+	let mut worker = Worker::new(client);
+	page.clone().load(&mut worker, 0);
+
+	let mut widgets = Widgets {
+		info: vec![(Paragraph::new(""), Node::None)],
+		..Widgets::default()
+	};
+	let mut list = widgets.list();
 
 	// Clear screen
 	let _ = terminal.clear();
@@ -224,17 +540,116 @@ fn main() {
 	let mut list_state = ListState::default();
 	list_state.select(Some(0));
 
+	let mut keys = KeyMap::browser(&config().keys.browser);
+
+	// Is the log view open?
+	let mut show_log = false;
+
 	loop {
+		// Show a page once it's loaded
+		match worker.poll() {
+			Some(Loaded::Page(next, selection, Ok(loaded))) => {
+				page = next;
+				widgets = loaded;
+
+				// There needs to be something to select
+				if widgets.info.is_empty() {
+					widgets
+						.info
+						.push((Paragraph::new("Nothing here"), Node::None));
+				}
+
+				list = widgets.list();
+
+				// Make sure the cursor isn't past the end of the data
+				list_state.select(Some(selection.min(widgets.info.len() - 1)));
+
+				let _ = terminal.clear();
+				redraw = true;
+			}
+			Some(Loaded::More(end, _, Ok(loaded))) => {
+				let added = loaded.items.len();
+
+				widgets.extend_section(end, loaded);
+				list = widgets.list();
+
+				// Keep the same item selected if it was after the new ones
+				let selected = list_state.selected().expect("Something should be selected");
+				if selected >= end {
+					list_state.select(Some(selected + added));
+				}
+
+				redraw = true;
+			}
+			Some(Loaded::Page(next, selection, Err(e))) => {
+				error = Some((e, Failed::Load(next, selection)));
+				redraw = true;
+			}
+			Some(Loaded::More(end, more, Err(e))) => {
+				error = Some((e, Failed::More(end, more)));
+				redraw = true;
+			}
+			Some(Loaded::Selected(result)) => {
+				match result.and_then(|selected| selected.run(&mut terminal, &qualities, chat_only))
+				{
+					Ok(Some(selected)) => {
+						let previous = Box::new(page.clone().set_selection(
+							list_state.selected().expect("Something should be selected"),
+						));
+
+						// If we selected a category or channel, start at the top of it
+						match selected {
+							Selected::Game(name) => Page::Game {
+								name,
+								options: config().requests.stream_options(),
+								// selection doesn't matter yet
+								selection: 0,
+								previous,
+							},
+							Selected::Channel(login) => Page::Channel {
+								login,
+								selection: 0,
+								previous,
+							},
+							Selected::Videos { login, kind } => Page::Videos {
+								login,
+								kind,
+								sort: config().requests.video_sort,
+								selection: 0,
+								previous,
+							},
+							Selected::Clips(source) => Page::Clips {
+								source,
+								period: config().requests.clip_period,
+								sort: config().requests.clip_sort,
+								selection: 0,
+								previous,
+							},
+							_ => unreachable!("Only pages are moved into"),
+						}
+						.load(&mut worker, 0);
+					}
+					Ok(None) => (),
+					Err(e) => error = Some((e, Failed::Select)),
+				}
+
+				let _ = terminal.clear();
+				redraw = true;
+			}
+			None => (),
+		}
+
 		// If something changed, redraw
 		if redraw {
 			let _ = terminal.draw(|frame| {
 				// Left panel border
 				frame.render_widget(
 					Block::default()
-						.title(page.to_string())
+						.title(Span::styled(page.to_string(), theme().title))
 						.borders(Borders::ALL)
-						.title_alignment(TITLE_ALIGNMENT)
-						.border_type(BORDER_TYPE),
+						.title_alignment(config().title_alignment)
+						.border_type(config().border_type)
+						.border_style(theme().border),
 					Rect {
 						width: frame.size().width / 2,
 						..frame.size()
@@ -256,8 +671,9 @@ fn main() {
 				frame.render_widget(
 					Block::default()
 						.borders(Borders::ALL)
-						.title_alignment(TITLE_ALIGNMENT)
-						.border_type(BORDER_TYPE),
+						.title_alignment(config().title_alignment)
+						.border_type(config().border_type)
+						.border_style(theme().border),
 					Rect {
 						x: frame.size().width / 2,
 						width: (frame.size().width + 1) / 2,
@@ -266,7 +682,7 @@ fn main() {
 				);
 				// Top-right panel text
 				frame.render_widget_reusable(
-					&info_vec[list_state.selected().expect("Something should be selected")].0,
+					&widgets.info[list_state.selected().expect("Something should be selected")].0,
 					Rect {
 						x: frame.size().width / 2 + 2,
 						y: 2,
@@ -275,210 +691,564 @@ fn main() {
 					},
 				);
 
+				// Whether the selected item's channel or category is saved
+				let bookmarked = Bookmark::of(
+					&widgets.info[list_state.selected().expect("Something should be selected")].1,
+				)
+				.is_some_and(|bookmark| bookmarks.contains(&bookmark));
+
 				// Bottom-right panel text
 				frame.render_widget(
 					Paragraph::new(vec![
-						"back: b".into(),
-						"search: s".into(),
-						"refresh: r".into(),
-						"quit: q".into(),
+						keys.help(Action::Back).into(),
+						keys.help(Action::Search).into(),
+						keys.help(Action::Refresh).into(),
+						if bookmarked {
+							[&keys.help(Action::Bookmark), " (saved)"].concat()
+						} else {
+							keys.help(Action::Bookmark)
+						}
+						.into(),
+						// Show if there's anything in it
+						match log::len() {
+							0 => keys.help(Action::Log),
+							len => [&keys.help(Action::Log), " (", &len.to_string(), ")"].concat(),
+						}
+						.into(),
+						keys.help(Action::Quit).into(),
 						"".into(),
-						"quality: +-".into(),
+						[
+							"quality: ",
+							&keys.key_name(Action::QualityUp),
+							&keys.key_name(Action::QualityDown),
+						]
+						.concat()
+						.into(),
 						qualities[0].into(),
 					])
+					.style(theme().help)
 					.alignment(Alignment::Right),
 					Rect {
 						x: frame.size().width / 2 + 2,
-						y: frame.size().height - 9,
+						y: frame.size().height - 11,
 						width: (frame.size().width - 7) / 2,
-						height: 7,
+						height: 9,
 					},
 				);
+
+				// Log view, over the right panel
+				if show_log {
+					let area = Rect {
+						x: frame.size().width / 2,
+						width: (frame.size().width + 1) / 2,
+						..frame.size()
+					};
+
+					let lines = log::lines();
+					// Newest at the bottom, skip what doesn't fit
+					let shown = lines.len().min(area.height.saturating_sub(2) as usize);
+
+					frame.render_widget(Clear, area);
+					frame.render_widget(
+						Paragraph::new(
+							lines[lines.len() - shown..]
+								.iter()
+								.map(|line| Spans::from(line.clone()))
+								.collect::<Vec<_>>(),
+						)
+						.style(theme().log)
+						.block(
+							Block::default()
+								.title(Span::styled("Log", theme().title))
+								.borders(Borders::ALL)
+								.title_alignment(config().title_alignment)
+								.border_type(config().border_type)
+								.border_style(theme().border),
+						),
+						area,
+					);
+				}
+
+				// Status bar over the bottom border, while something loads
+				if let Some(progress) = worker.progress() {
+					let mut label = [
+						worker.description().unwrap_or("Loading"),
+						"... ",
+						&worker.elapsed().as_secs().to_string(),
+						" s",
+					]
+					.concat();
+
+					// Playlists say how long they are, GQL responses don't
+					let fraction = progress.fraction().filter(|_| config().download_progress);
+
+					if config().download_progress {
+						label += &match fraction {
+							Some(fraction) => {
+								["  ", &((fraction * 100.0) as u8).to_string(), "%"].concat()
+							}
+							None => {
+								["  ", &(progress.downloaded() / 1024).to_string(), " KiB"].concat()
+							}
+						};
+					}
+
+					label += &["  ", &keys.help(Action::Cancel)].concat();
+
+					frame.render_widget(
+						Gauge::default()
+							.gauge_style(theme().progress)
+							.ratio(fraction.unwrap_or(0.0))
+							.label(label),
+						Rect {
+							y: frame.size().height - 1,
+							height: 1,
+							..frame.size()
+						},
+					);
+				}
+
+				// Error popup
+				if let Some((error, _)) = &error {
+					let mut lines: Vec<Spans> =
+						error.lines().into_iter().map(Spans::from).collect();
+					lines.extend([
+						"".into(),
+						[
+							"retry: ",
+							&keys.key_name(Action::Refresh),
+							"  ",
+							&keys.help(Action::Back),
+							"  ",
+							&keys.help(Action::Quit),
+						]
+						.concat()
+						.into(),
+					]);
+
+					let width = (frame.size().width * 2 / 3).max(20).min(frame.size().width);
+					// Leave room for the border and some wrapping
+					let height = (lines.len() as u16 + 4).min(frame.size().height);
+
+					let area = Rect {
+						x: (frame.size().width - width) / 2,
+						y: (frame.size().height - height) / 2,
+						width,
+						height,
+					};
+
+					frame.render_widget(Clear, area);
+					frame.render_widget(
+						Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+							Block::default()
+								.title(Span::styled("Error", theme().error))
+								.borders(Borders::ALL)
+								.title_alignment(config().title_alignment)
+								.border_type(config().border_type)
+								.border_style(theme().error),
+						),
+						area,
+					);
+				}
 			});
 		}
 
 		redraw = true;
 
+		// While loading, check back on the worker every so often to update the indicator
+		if worker.is_loading() && !poll(Duration::from_millis(100)).expect("IO error") {
+			continue;
+		}
+
 		// Read input
 		match read().expect("IO error") {
-			Event::Key(KeyEvent { code, .. }) => match code {
-				// Quit
-				KeyCode::Char('Q' | 'q') => break,
-				// Move down
-				KeyCode::Down | KeyCode::Char('J' | 'j') => {
-					list_state.select(list_state.selected().map(|s| info_vec.len().min(s + 2) - 1))
-				}
-				// Move up
-				KeyCode::Up | KeyCode::Char('K' | 'k') => {
-					list_state.select(list_state.selected().map(|s| s.saturating_sub(1)))
-				}
-				KeyCode::PageDown => list_state.select(list_state.selected().map(|s| {
-					info_vec.len().min(
-						s + (terminal
-							.size()
-							.expect("Should be able to get terminal height")
-							.height / 2) as usize,
-					) - 1
-				})),
-				KeyCode::PageUp => list_state.select(list_state.selected().map(|s| {
-					s.saturating_sub(
-						(terminal
-							.size()
-							.expect("Should be able to get terminal height")
-							.height / 2 - 1) as usize,
-					)
-				})),
-				KeyCode::Right | KeyCode::Char('L' | 'l') => {
-					// Enter
-					if let Some(name) = info_vec
-						[list_state.selected().expect("Something should be selected")]
-					.1
-					.select(&mut terminal, &mut easy, &qualities)
-					{
-						// If we selected a category
+			Event::Key(key) => {
+				let action = match (keys.feed(key), error.take()) {
+					(action, None) => action,
+					// While an error is shown, only retrying, going back and quitting work
+					(Some(Action::Refresh), Some((_, Failed::Select))) => Some(Action::Select),
+					(Some(Action::Refresh), Some((_, Failed::Load(next, selection)))) => {
+						next.load(&mut worker, selection);
+						continue;
+					}
+					(Some(Action::Refresh), Some((_, Failed::More(end, more)))) => {
+						page.clone().load_more(&mut worker, end, more);
+						continue;
+					}
+					// The current page is still fine, just hide the error
+					(Some(Action::Back), Some(_)) => continue,
+					(Some(Action::Quit), Some(_)) => break,
+					(_, shown) => {
+						error = shown;
+						redraw = false;
+						continue;
+					}
+				};
 
-						// selection doesn't matter yet
-						page = Page::Game {
-							name,
+				match action {
+					Some(Action::Quit) => break,
+					// Move down
+					Some(Action::Down) => list_state.select(
+						list_state
+							.selected()
+							.map(|s| widgets.info.len().min(s + 2) - 1),
+					),
+					// Move up
+					Some(Action::Up) => {
+						list_state.select(list_state.selected().map(|s| s.saturating_sub(1)))
+					}
+					Some(Action::PageDown) => list_state.select(list_state.selected().map(|s| {
+						widgets.info.len().min(
+							s + (terminal
+								.size()
+								.expect("Should be able to get terminal height")
+								.height / 2) as usize,
+						) - 1
+					})),
+					Some(Action::PageUp) => list_state.select(list_state.selected().map(|s| {
+						s.saturating_sub(
+							(terminal
+								.size()
+								.expect("Should be able to get terminal height")
+								.height / 2 - 1) as usize,
+						)
+					})),
+					Some(Action::Top) => list_state.select(Some(0)),
+					Some(Action::Bottom) => list_state.select(Some(widgets.info.len() - 1)),
+					Some(Action::Select) => {
+						// Enter
+						let node = widgets.info
+							[list_state.selected().expect("Something should be selected")]
+						.1
+						.clone();
+						let qualities: Vec<String> = qualities
+							.iter()
+							.map(|quality| quality.to_string())
+							.collect();
+
+						// Get the URL in the background, it's played once it's loaded
+						worker.start("Opening".to_owned(), move |client, progress| {
+							Loaded::Selected(node.resolve(
+								client,
+								progress,
+								&qualities.iter().map(String::as_str).collect::<Vec<_>>(),
+							))
+						});
+					}
+					// Go back
+					// Close the log first
+					Some(Action::Back) if show_log => show_log = false,
+					Some(Action::Back) => {
+						match &page {
+							// Just move cursor to the top
+							Page::Home { .. } => list_state.select(Some(0)),
+							Page::Game { previous, .. }
+							| Page::Search { previous, .. }
+							| Page::Following { previous, .. }
+							| Page::Channel { previous, .. }
+							| Page::Videos { previous, .. }
+							| Page::Clips { previous, .. }
+							| Page::Directory { previous, .. } => {
+								let selection = previous.get_selection();
+								(**previous).clone().load(&mut worker, selection);
+							}
+						}
+					}
+					// home
+					Some(Action::Home) => {
+						// Move cursor to the top once it's loaded
+						Page::Home { selection: 0 }.load(&mut worker, 0);
+					}
+					// Followed channels
+					Some(Action::Following) => {
+						Page::Following {
 							selection: 0,
-							previous: Box::new(page.set_selection(
+							previous: Box::new(page.clone().set_selection(
 								list_state.selected().expect("Something should be selected"),
 							)),
-						};
-
-						// Move cursor to the top
-						list_state.select(Some(0));
+						}
+						.load(&mut worker, 0);
+					}
+					// The selected item's channel
+					Some(Action::Channel) => {
+						let selected = list_state.selected().expect("Something should be selected");
 
-						(list, info_vec) = page.request(&mut easy);
+						if let Some(login) = widgets.info[selected].1.channel() {
+							Page::Channel {
+								login: login.to_owned(),
+								selection: 0,
+								previous: Box::new(page.clone().set_selection(selected)),
+							}
+							.load(&mut worker, 0);
+						} else {
+							redraw = false
+						}
 					}
+					// The selected item's channel's videos
+					Some(Action::Videos) => {
+						let selected = list_state.selected().expect("Something should be selected");
 
-					let _ = terminal.clear();
-				}
-				// Go back
-				KeyCode::Left | KeyCode::Char('B' | 'b') => {
-					match page {
-						// Just move cursor to the top
-						Page::Home { .. } => list_state.select(Some(0)),
-						Page::Game { previous, .. } | Page::Search { previous, .. } => {
-							page = *previous;
-							(list, info_vec) = page.request(&mut easy);
-
-							let _ = terminal.clear();
-
-							list_state.select(Some(page.get_selection().min(info_vec.len() - 1)));
+						if let Some(login) = widgets.info[selected].1.channel() {
+							Page::Videos {
+								login: login.to_owned(),
+								kind: config().requests.video_type,
+								sort: config().requests.video_sort,
+								selection: 0,
+								previous: Box::new(page.clone().set_selection(selected)),
+							}
+							.load(&mut worker, 0);
+						} else {
+							redraw = false
 						}
 					}
-				}
-				// home
-				KeyCode::Char('H' | 'h') => {
-					// Move cursor to the top
-					list_state.select(Some(0));
+					// Clips of the selected (or current) category, or the selected item's channel
+					Some(Action::Clips) => {
+						let selected = list_state.selected().expect("Something should be selected");
+						let node = &widgets.info[selected].1;
 
-					page = Page::Home { selection: 0 };
-					(list, info_vec) = page.request(&mut easy);
+						let source = if let Some(name) = node.game() {
+							Some(ClipSource::Category(name.to_owned()))
+						} else if let Page::Game { name, .. } = &page {
+							Some(ClipSource::Category(name.clone()))
+						} else {
+							node.channel()
+								.map(|login| ClipSource::Channel(login.to_owned()))
+						};
 
-					let _ = terminal.clear();
-				}
-				// Increase quality
-				KeyCode::Char('+') => {
-					qualities[0] = match qualities[0] {
-						"audio_only" => "worst",
-						"worst" => "160p",
-						"160p" => "360p",
-						"360p" => "480p",
-						"480p" => "720p",
-						"720p" => "720p60",
-						"720p60" => "1080p60",
-						"1080p60" => "best",
-						_ => qualities[0],
+						if let Some(source) = source {
+							Page::Clips {
+								source,
+								period: config().requests.clip_period,
+								sort: config().requests.clip_sort,
+								selection: 0,
+								previous: Box::new(page.clone().set_selection(selected)),
+							}
+							.load(&mut worker, 0);
+						} else {
+							redraw = false
+						}
 					}
-				}
-				// Decrease quality
-				KeyCode::Char('-') => {
-					qualities[0] = match qualities[0] {
-						"worst" => "audio_only",
-						"160p" => "worst",
-						"360p" => "160p",
-						"480p" => "360p",
-						"720p" => "480p",
-						"720p60" => "720p",
-						"1080p60" => "720p60",
-						"best" => "1080p60",
-						_ => qualities[0],
+					// Save or forget the selected item's channel or category, for the search box
+					Some(Action::Bookmark) => {
+						let selected = list_state.selected().expect("Something should be selected");
+
+						if let Some(bookmark) = Bookmark::of(&widgets.info[selected].1) {
+							bookmarks.toggle(bookmark);
+						} else {
+							redraw = false
+						}
 					}
-				}
-				// Search
-				KeyCode::Char('S' | 's' | '/') => {
-					// Show cursor
-					let _ = terminal.show_cursor();
-
-					let mut query = String::new();
-
-					loop {
-						let _ = terminal.draw(|frame| {
-							// Width of the input box
-							let width = (query.len() as u16 + 3).clamp(20, frame.size().width);
-
-							frame.render_widget(
-								Paragraph::new(query.clone()).block(
-									Block::default()
-										.borders(Borders::ALL)
-										.title("Search for streams")
-										.title_alignment(TITLE_ALIGNMENT)
-										.border_type(BORDER_TYPE),
-								),
-								Rect {
-									x: (frame.size().width - width) / 2,
-									y: frame.size().height / 2 - 1,
-									width,
-									height: 3,
-								},
-							)
-						});
+					// Change how the page is sorted, starting at the top again
+					Some(Action::Sort) => {
+						// The current page stays until the new one loads
+						let mut next = page.clone();
 
-						if let Event::Key(KeyEvent { code, .. }) =
-							read().expect("Should be able to read input")
-						{
-							match code {
-								KeyCode::Char(c) => query.push(c),
-								KeyCode::Backspace => {
-									query.pop();
-								}
-								KeyCode::Enter => break,
-								_ => (),
+						let changed = match &mut next {
+							Page::Game { options, .. } => {
+								options.sort = options.sort.next();
+								true
+							}
+							Page::Videos { sort, .. } => {
+								*sort = sort.next();
+								true
 							}
+							Page::Clips { sort, .. } => {
+								*sort = sort.next();
+								true
+							}
+							_ => false,
+						};
+
+						if changed {
+							next.load(&mut worker, 0);
+						} else {
+							redraw = false
+						}
+					}
+					// Top categories
+					Some(Action::Directory) => {
+						Page::Directory {
+							tags: config().requests.directory_tags.clone(),
+							selection: 0,
+							previous: Box::new(page.clone().set_selection(
+								list_state.selected().expect("Something should be selected"),
+							)),
 						}
+						.load(&mut worker, 0);
 					}
+					// Add or remove a tag from top categories
+					Some(Action::Filter)
+						if matches!(page, Page::Directory { .. })
+							|| matches!(
+								(&page, &config().home_page),
+								(Page::Home { .. }, HomePage::Directory)
+							) =>
+					{
+						// Cancelled, stay where we are
+						let Some(tag) = prompt(&mut terminal, "Tag to add or remove") else {
+							continue;
+						};
 
-					list_state.select(Some(0));
+						let mut tags = page.directory_tags();
+						toggle(&mut tags, tag);
 
-					page = Page::Search {
-						query,
-						selection: 0,
-						previous: Box::new(page.set_selection(
-							list_state.selected().expect("Something should be selected"),
-						)),
-					};
+						// Stay on the same level, going back goes to the same page as before
+						let previous = if let Page::Directory { previous, .. } = &page {
+							previous.clone()
+						} else {
+							Box::new(page.clone().set_selection(
+								list_state.selected().expect("Something should be selected"),
+							))
+						};
+
+						Page::Directory {
+							tags,
+							selection: 0,
+							previous,
+						}
+						.load(&mut worker, 0);
+					}
+					// Add or remove a tag or language from a category's streams
+					Some(Action::Filter) if matches!(page, Page::Game { .. }) => {
+						// Cancelled, stay where we are
+						let Some(filter) = prompt(
+							&mut terminal,
+							"Tag or language (i.e. `lang:en`) to add or remove",
+						) else {
+							continue;
+						};
 
-					(list, info_vec) = page.request(&mut easy);
+						let mut next = page.clone();
+						if let Page::Game { options, .. } = &mut next {
+							options.toggle(&filter);
+						}
+						next.load(&mut worker, 0);
+					}
+					// Change what the page shows
+					Some(Action::Filter) => {
+						let mut next = page.clone();
+
+						let changed = match &mut next {
+							// Keeps the query
+							Page::Search { scope, .. } => {
+								*scope = SearchIndex::next(*scope);
+								true
+							}
+							Page::Videos { kind, .. } => {
+								*kind = kind.next();
+								true
+							}
+							Page::Clips { period, .. } => {
+								*period = period.next();
+								true
+							}
+							_ => false,
+						};
+
+						if changed {
+							next.load(&mut worker, 0);
+						} else {
+							redraw = false
+						}
+					}
+					// Increase quality
+					Some(Action::QualityUp) => {
+						qualities[0] = match qualities[0] {
+							"audio_only" => "worst",
+							"worst" => "160p",
+							"160p" => "360p",
+							"360p" => "480p",
+							"480p" => "720p",
+							"720p" => "720p60",
+							"720p60" => "1080p60",
+							"1080p60" => "best",
+							_ => qualities[0],
+						}
+					}
+					// Decrease quality
+					Some(Action::QualityDown) => {
+						qualities[0] = match qualities[0] {
+							"worst" => "audio_only",
+							"160p" => "worst",
+							"360p" => "160p",
+							"480p" => "360p",
+							"720p" => "480p",
+							"720p60" => "720p",
+							"1080p60" => "720p60",
+							"best" => "1080p60",
+							_ => qualities[0],
+						}
+					}
+					// Search
+					Some(Action::Search) => {
+						let previous = Box::new(page.clone().set_selection(
+							list_state.selected().expect("Something should be selected"),
+						));
 
-					let _ = terminal.clear();
+						match search_box(&mut terminal, &history, &bookmarks) {
+							Some(Submitted::Search(search)) => {
+								history.add(&search);
+								let (scope, query) = SearchIndex::from_prefix(&search);
 
-					// Hide the cursor again
-					let _ = terminal.hide_cursor();
+								Page::Search {
+									query: query.to_owned(),
+									scope,
+									selection: 0,
+									previous,
+								}
+								.load(&mut worker, 0);
+							}
+							// Suggestions for channels and categories open them straight away
+							Some(Submitted::Channel(login)) => Page::Channel {
+								login,
+								selection: 0,
+								previous,
+							}
+							.load(&mut worker, 0),
+							Some(Submitted::Game(name)) => Page::Game {
+								name,
+								options: config().requests.stream_options(),
+								selection: 0,
+								previous,
+							}
+							.load(&mut worker, 0),
+							None => (),
+						}
+					}
+					// Refresh
+					Some(Action::Refresh) => {
+						// Just send this page's request again, keeping the cursor where it is
+						page.clone().load(
+							&mut worker,
+							list_state.selected().expect("Something should be selected"),
+						);
+					}
+					// Stop loading, the current page stays
+					Some(Action::Cancel) => worker.cancel(),
+					Some(Action::Log) => show_log = !show_log,
+					// Chat only, or not a full key sequence yet
+					Some(Action::TabLeft | Action::TabRight | Action::Message) | None => {
+						redraw = false
+					}
 				}
-				// Refresh
-				KeyCode::Char('R' | 'r') => {
-					// Just send this page's request again and parse it
-					(list, info_vec) = page.request(&mut easy);
 
-					// Make sure the cursor isn't past the end of the data
-					list_state.select(list_state.selected().map(|s| s.min(info_vec.len() - 1)));
+				// Load more items when the cursor gets near the end of a section
+				if matches!(
+					action,
+					Some(Action::Down | Action::PageDown | Action::Bottom)
+				) && !worker.is_loading()
+				{
+					let selected = list_state.selected().expect("Something should be selected");
 
-					let _ = terminal.clear();
+					if let Some((end, more)) = widgets
+						.more
+						.iter()
+						.find(|(end, _)| selected < *end && selected + LOAD_MORE_DISTANCE >= *end)
+					{
+						page.clone().load_more(&mut worker, *end, more.clone());
+					}
 				}
-				_ => redraw = false,
-			},
+			}
 			// We want to redraw
 			Event::Resize(..) => (),
 			_ => redraw = false,
@@ -488,3 +1258,171 @@ fn main() {
 	let _ = disable_raw_mode();
 	let _ = execute!(stdout(), LeaveAlternateScreen);
 }
+
+#[cfg(test)]
+mod tests {
+	//! Pages are requested from [`twitch_curses::mock`]'s servers, started by each test.
+
+	use twitch_curses::mock::Options;
+
+	use super::*;
+	use crate::config::mock;
+	use crate::error::ErrorKind;
+	use crate::utils::client_for;
+
+	/// A client for new mock servers that behave normally
+	fn mock_client() -> Client {
+		client_for(&mock(Options::default()))
+	}
+
+	/// A page to go back to
+	fn home() -> Box<Page> {
+		Box::new(Page::Home { selection: 0 })
+	}
+
+	fn game(name: &str) -> Page {
+		Page::Game {
+			name: name.to_owned(),
+			options: config().requests.stream_options(),
+			selection: 0,
+			previous: home(),
+		}
+	}
+
+	#[test]
+	fn requests_pages() {
+		let pages = [
+			("home", Page::Home { selection: 0 }),
+			("category", game("Just Chatting")),
+			(
+				"search",
+				Page::Search {
+					query: "mock".to_owned(),
+					scope: None,
+					selection: 0,
+					previous: home(),
+				},
+			),
+			(
+				"channel",
+				Page::Channel {
+					login: "mockstreamer".to_owned(),
+					selection: 0,
+					previous: home(),
+				},
+			),
+			(
+				"videos",
+				Page::Videos {
+					login: "mockstreamer".to_owned(),
+					kind: VideoType::Archive,
+					sort: VideoSort::Time,
+					selection: 0,
+					previous: home(),
+				},
+			),
+			(
+				"clips",
+				Page::Clips {
+					source: ClipSource::Category("Just Chatting".to_owned()),
+					period: ClipPeriod::Week,
+					sort: ClipSort::Views,
+					selection: 0,
+					previous: home(),
+				},
+			),
+			(
+				"directory",
+				Page::Directory {
+					tags: Vec::new(),
+					selection: 0,
+					previous: home(),
+				},
+			),
+		];
+
+		let mut client = mock_client();
+
+		for (name, page) in pages {
+			let widgets = page
+				.request(&mut client)
+				.unwrap_or_else(|e| panic!("Should be able to load the {name} page: {e}"));
+
+			assert!(!widgets.items.is_empty(), "The {name} page is empty");
+			assert_eq!(widgets.items.len(), widgets.info.len());
+		}
+	}
+
+	#[test]
+	fn requests_more_until_the_end() {
+		let page = game("Just Chatting");
+		let mut client = mock_client();
+
+		let widgets = page
+			.request(&mut client)
+			.expect("Should be able to load the category");
+		let [(_, more)] = &widgets.more[..] else {
+			panic!("The category should have more streams");
+		};
+
+		let next = page
+			.request_more(more, &mut client)
+			.expect("Should be able to load more streams");
+
+		assert!(!next.items.is_empty());
+		// The mock only has two pages
+		assert!(next.more.is_empty());
+	}
+
+	#[test]
+	fn sends_whole_queries_for_stale_hashes() {
+		let mut client = client_for(&mock(Options {
+			stale_hashes: true,
+			..Options::default()
+		}));
+
+		let widgets = game("Just Chatting")
+			.request(&mut client)
+			.expect("Should fall back to sending the whole query");
+
+		assert!(!widgets.items.is_empty());
+	}
+
+	#[test]
+	fn reports_gql_errors() {
+		let error = game("error")
+			.request(&mut mock_client())
+			.err()
+			.expect("The mock should fail this request");
+
+		assert_eq!(error.operation, "DirectoryPage_Game");
+		assert!(matches!(error.kind, ErrorKind::Gql(_)));
+	}
+
+	#[test]
+	fn reports_http_errors() {
+		let error = game("http-error")
+			.request(&mut mock_client())
+			.err()
+			.expect("The mock should fail this request");
+
+		assert_eq!(error.status, Some(500));
+		assert!(matches!(error.kind, ErrorKind::Status(_)));
+	}
+
+	#[test]
+	fn reports_which_query_in_a_batch_failed() {
+		let error = Page::Channel {
+			login: "error".to_owned(),
+			selection: 0,
+			previous: home(),
+		}
+		.request(&mut mock_client())
+		.err()
+		.expect("The mock should fail this request");
+
+		// Every query in the batch has the login, so the first one fails
+		assert_eq!(error.operation, "ChannelPage");
+		assert!(matches!(error.kind, ErrorKind::Gql(_)));
+	}
+}
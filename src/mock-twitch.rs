@@ -0,0 +1,89 @@
+//! Runs [`twitch_curses::mock`]'s servers until it's killed, for trying the client without the
+//! network. Point the client at them with the environment variables it prints on startup.
+
+use std::env;
+use std::process::exit;
+use std::time::Duration;
+
+use twitch_curses::mock::{Options, Server};
+
+/// Shown for `--help` and after argument errors
+const USAGE: &str = "\
+Usage: mock-twitch [OPTIONS]
+
+Options:
+      --http <ADDR>              Address for GQL and playlists [default: 127.0.0.1:8080]
+      --irc <ADDR>               Address for IRC [default: 127.0.0.1:6667]
+      --pubsub <ADDR>            Address for PubSub [default: 127.0.0.1:8081]
+      --data <DIR>               Serve GQL responses from `<DIR>/<operation>.json` where they exist
+      --stale-hashes             Reject every persisted query hash, so whole queries are sent
+      --reconnect-after <SECS>   Ask IRC and PubSub clients to reconnect after this long
+  -h, --help                     Print this message";
+
+/// Parses the program's arguments, exiting with a usage message if they're invalid.
+fn parse() -> Options {
+	let mut options = Options {
+		http: "127.0.0.1:8080".to_owned(),
+		irc: "127.0.0.1:6667".to_owned(),
+		pubsub: "127.0.0.1:8081".to_owned(),
+		..Options::default()
+	};
+
+	let mut args = env::args().skip(1);
+
+	while let Some(arg) = args.next() {
+		let mut value = || {
+			args.next().unwrap_or_else(|| {
+				eprintln!("{arg} needs a value\n\n{USAGE}");
+				exit(2)
+			})
+		};
+
+		match &*arg {
+			"--http" => options.http = value(),
+			"--irc" => options.irc = value(),
+			"--pubsub" => options.pubsub = value(),
+			"--data" => options.data = Some(value().into()),
+			"--stale-hashes" => options.stale_hashes = true,
+			"--reconnect-after" => {
+				let secs = value();
+
+				options.reconnect_after =
+					Some(Duration::from_secs(secs.parse().unwrap_or_else(|_| {
+						eprintln!("--reconnect-after should be a number of seconds, not {secs}");
+						exit(2)
+					})));
+			}
+			"-h" | "--help" => {
+				println!("{USAGE}");
+				exit(0)
+			}
+			_ => {
+				eprintln!("Unknown argument: {arg}\n\n{USAGE}");
+				exit(2)
+			}
+		}
+	}
+
+	options
+}
+
+#[tokio::main]
+async fn main() {
+	let server = Server::bind(parse()).await.unwrap_or_else(|e| {
+		eprintln!("{e}");
+		exit(1)
+	});
+
+	println!(
+		"Listening, point the client here with:\n{}",
+		server
+			.env()
+			.iter()
+			.map(|(name, value)| [name, "=", value].concat())
+			.collect::<Vec<_>>()
+			.join(" ")
+	);
+
+	server.serve().await;
+}
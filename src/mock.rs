@@ -0,0 +1,1020 @@
+//! A stand-in for twitch's servers, for testing the client without the network.
+//!
+//! Serves canned GQL responses and VOD playlists over plain HTTP, an IRC server that speaks
+//! twitch's tags and capabilities, and a PubSub websocket. Tests [`start`] one with the
+//! [`Options`] they need and point the client at it with the environment variables it returns,
+//! the `mock-twitch` program runs one in the foreground.
+//!
+//! GQL responses are the files in `src/mock`, named after the operation. [`Options::data`] serves
+//! files from another directory instead where they exist. If a request has a `cursor` variable,
+//! `<operation>.more.json` is used instead if there is one, so pagination ends.
+//!
+//! Some variable values (i.e. a search or category name) trigger errors:
+//! - `error`: the response has GQL errors and no data
+//! - `http-error`: the server responds with status 500
+//!
+//! Chat messages containing `error` are rejected with a `NOTICE`, like twitch does for banned
+//! phrases.
+//!
+//! Logging in works too, the device code is "entered" the second time the client checks.
+
+#![allow(non_snake_case)]
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use simd_json::{from_slice, to_string, OwnedValue};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::{interval, interval_at, sleep, Instant};
+use tokio_tungstenite::tungstenite::Message;
+
+/// Hashes the client sends, and their operations. Queries that aren't persisted are found by name
+const OPERATIONS: &[(&str, &str)] = &[
+	(
+		"f8cc9b91bb629f2d09dd8299d9f07c4daefe019236a19fc12fa2b14eb95c359e",
+		"PersonalSections",
+	),
+	(
+		"41858598cc637cf9e6153818f5a4d274a08e8743e4a85903cdfe39c464152404",
+		"Shelves",
+	),
+	(
+		"36b89d2507fce29e5ca551df756d27c1cfe079e2609642b4390aa4c35796eb11",
+		"VideoAccessToken_Clip",
+	),
+	(
+		"df4bb6cc45055237bfaf3ead608bbafb79815c7100b6ee126719fac3762ddf8b",
+		"DirectoryPage_Game",
+	),
+	(
+		"6ea6e6f66006485e41dbe3ebd69d5674c5b22896ce7b595d7fce6411a3790138",
+		"SearchResultsPage_SearchResults",
+	),
+	(
+		"0828119ded1c13477966434e15800ff57ddacf13ba1911c129dc2200705b0712",
+		"PlaybackAccessToken",
+	),
+];
+
+/// Built in responses, `{base}` is replaced with the HTTP server's URL
+const RESPONSES: &[(&str, &str)] = &[
+	(
+		"PersonalSections",
+		include_str!("mock/PersonalSections.json"),
+	),
+	("Shelves", include_str!("mock/Shelves.json")),
+	(
+		"VideoAccessToken_Clip",
+		include_str!("mock/VideoAccessToken_Clip.json"),
+	),
+	(
+		"DirectoryPage_Game",
+		include_str!("mock/DirectoryPage_Game.json"),
+	),
+	(
+		"DirectoryPage_Game.more",
+		include_str!("mock/DirectoryPage_Game.more.json"),
+	),
+	(
+		"SearchResultsPage_SearchResults",
+		include_str!("mock/SearchResultsPage_SearchResults.json"),
+	),
+	(
+		"PlaybackAccessToken",
+		include_str!("mock/PlaybackAccessToken.json"),
+	),
+	(
+		"FollowedChannels",
+		include_str!("mock/FollowedChannels.json"),
+	),
+	("ChannelPage", include_str!("mock/ChannelPage.json")),
+	("ChannelSchedule", include_str!("mock/ChannelSchedule.json")),
+	("ChannelVideos", include_str!("mock/ChannelVideos.json")),
+	(
+		"ChannelVideos.more",
+		include_str!("mock/ChannelVideos.more.json"),
+	),
+	("ClipsPage", include_str!("mock/ClipsPage.json")),
+	("ClipsPage.more", include_str!("mock/ClipsPage.more.json")),
+	("TopCategories", include_str!("mock/TopCategories.json")),
+	(
+		"TopCategories.more",
+		include_str!("mock/TopCategories.more.json"),
+	),
+	(
+		"SearchSuggestions",
+		include_str!("mock/SearchSuggestions.json"),
+	),
+];
+
+/// VOD master playlist, `{base}` is replaced with the HTTP server's URL
+const VOD_PLAYLIST: &str = include_str!("mock/vod.m3u8");
+
+/// Served for anything the playlists and clips point to
+const MEDIA_PLAYLIST: &str = "#EXTM3U\n#EXT-X-ENDLIST";
+
+/// Messages sent to IRC clients once they join, in a loop. `{channel}` is replaced with the
+/// channel.
+pub const CHAT: &[&str] = &[
+	"@badge-info=subscriber/14;badges=subscriber/12,premium/1;color=#FF4500;display-name=Subbed;\
+	 room-id=1001;user-id=2 :subbed!subbed@subbed.tmi.twitch.tv PRIVMSG #{channel} :hello chat",
+	"@badge-info=;badges=moderator/1,partner/1;color=#00FF7F;display-name=Moderator;room-id=1001;\
+	 user-id=3 :moderator!moderator@moderator.tmi.twitch.tv PRIVMSG #{channel} :please be nice",
+	"@badge-info=predictions/Yes;badges=predictions/blue-1,moments/25,vip/1;color=;\
+	 display-name=Predictor;room-id=1001;user-id=4 :predictor!predictor@predictor.tmi.twitch.tv \
+	 PRIVMSG #{channel} :this message is long enough that it should have to be wrapped onto more \
+	 than one line in most terminals, unless yours is very wide",
+	"@badge-info=;badges=sub-gifter/5,no_audio/1,no_video/1;color=#1E90FF;display-name=Gifter;\
+	 room-id=1001;user-id=5 :gifter!gifter@gifter.tmi.twitch.tv PRIVMSG #{channel} :gg",
+	"@badge-info=;badges=;color=#1E90FF;display-name=Gifter;login=gifter;msg-id=subgift;\
+	 room-id=1001;system-msg=Gifter\\sgifted\\sa\\sTier\\s1\\ssub\\sto\\sSubbed!;user-id=5 \
+	 :tmi.twitch.tv USERNOTICE #{channel}",
+	"@room-id=1001;target-msg-id=1 :tmi.twitch.tv CLEARCHAT #{channel} :subbed",
+	"@ban-duration=10;room-id=1001;target-user-id=4 :tmi.twitch.tv CLEARCHAT #{channel} :predictor",
+];
+
+/// How the servers behave
+pub struct Options {
+	/// Address for GQL, playlists and logging in
+	pub http: String,
+	/// Address for IRC
+	pub irc: String,
+	/// Address for PubSub
+	pub pubsub: String,
+	/// Serve GQL responses from `<data>/<operation>.json` where they exist
+	pub data: Option<PathBuf>,
+	/// Reject every persisted query hash, so whole queries are sent
+	pub stale_hashes: bool,
+	/// Ask IRC and PubSub clients to reconnect after this long, then hang up
+	pub reconnect_after: Option<Duration>,
+}
+impl Default for Options {
+	/// Local ports picked by the OS, with nothing going wrong
+	fn default() -> Self {
+		Self {
+			http: "127.0.0.1:0".to_owned(),
+			irc: "127.0.0.1:0".to_owned(),
+			pubsub: "127.0.0.1:0".to_owned(),
+			data: None,
+			stale_hashes: false,
+			reconnect_after: None,
+		}
+	}
+}
+
+/// The servers, listening but not handling connections until [`Server::serve`] is called
+pub struct Server {
+	/// With the addresses actually used
+	options: Options,
+	http: TcpListener,
+	irc: TcpListener,
+	pubsub: TcpListener,
+}
+impl Server {
+	/// Listens on the addresses in `options`. The OS picks the port for any that are 0.
+	pub async fn bind(mut options: Options) -> Result<Self, String> {
+		Ok(Self {
+			http: bind(&mut options.http).await?,
+			irc: bind(&mut options.irc).await?,
+			pubsub: bind(&mut options.pubsub).await?,
+			options,
+		})
+	}
+
+	/// The environment variables that point the client at these servers
+	pub fn env(&self) -> Vec<(&'static str, String)> {
+		let options = &self.options;
+		let (irc_host, irc_port) = options
+			.irc
+			.rsplit_once(':')
+			.expect("Should be listening on a port");
+
+		vec![
+			(
+				"TWITCH_TUI_GQL",
+				["http://", &options.http, "/gql"].concat(),
+			),
+			("TWITCH_TUI_USHER", ["http://", &options.http].concat()),
+			("TWITCH_TUI_IRC_HOST", irc_host.to_owned()),
+			("TWITCH_TUI_IRC_PORT", irc_port.to_owned()),
+			("TWITCH_TUI_IRC_TLS", "false".to_owned()),
+			("TWITCH_TUI_PUBSUB", ["ws://", &options.pubsub].concat()),
+			(
+				"TWITCH_TUI_AUTH",
+				["http://", &options.http, "/oauth2"].concat(),
+			),
+		]
+	}
+
+	/// Handles connections until the program exits.
+	pub async fn serve(self) {
+		let state = Arc::new(State {
+			options: self.options,
+			code_entered: AtomicBool::new(false),
+		});
+
+		tokio::spawn(accept(self.http, state.clone(), handle_http));
+		tokio::spawn(accept(self.irc, state.clone(), handle_irc));
+		accept(self.pubsub, state, handle_pubsub).await;
+	}
+}
+
+/// Starts the servers on their own thread, so tests can use them without a runtime, and returns
+/// the environment variables that point the client at them. Each call starts new servers, so
+/// tests with different `options` don't affect each other.
+pub fn start(options: Options) -> Vec<(&'static str, String)> {
+	let (sender, receiver) = std::sync::mpsc::channel();
+
+	std::thread::spawn(move || {
+		tokio::runtime::Runtime::new()
+			.expect("Should be able to start a runtime")
+			.block_on(async move {
+				let server = Server::bind(options)
+					.await
+					.expect("Should be able to listen on the given addresses");
+
+				let _ = sender.send(server.env());
+
+				server.serve().await
+			})
+	});
+
+	receiver.recv().expect("The servers should start")
+}
+
+/// What the handlers share
+struct State {
+	options: Options,
+	/// Set once the client has checked for the device code being entered
+	code_entered: AtomicBool,
+}
+
+/// Listens on `address`, replacing it with the address actually used.
+async fn bind(address: &mut String) -> Result<TcpListener, String> {
+	let listener = TcpListener::bind(&**address)
+		.await
+		.map_err(|e| ["Couldn't listen on ", address, ": ", &e.to_string()].concat())?;
+
+	if let Ok(local) = listener.local_addr() {
+		*address = local.to_string();
+	}
+
+	Ok(listener)
+}
+
+/// Handles each connection to `listener` on it's own task.
+async fn accept<F, R>(listener: TcpListener, state: Arc<State>, handle: F)
+where
+	F: Fn(TcpStream, Arc<State>) -> R,
+	R: std::future::Future<Output = ()> + Send + 'static,
+{
+	while let Ok((stream, _)) = listener.accept().await {
+		tokio::spawn(handle(stream, state.clone()));
+	}
+}
+
+// HTTP
+
+/// `persistedQuery` in a request's `extensions`
+#[derive(Deserialize)]
+struct PersistedQuery {
+	// The webapp uses `sha256Hash`
+	#[serde(alias = "sha256Hash")]
+	sha256hash: String,
+}
+
+#[derive(Deserialize)]
+struct Extensions {
+	persistedQuery: PersistedQuery,
+}
+
+/// A GQL request, with either a persisted query hash or the whole query
+#[derive(Deserialize)]
+struct GqlRequest {
+	operationName: Option<String>,
+	query: Option<String>,
+	#[serde(default)]
+	variables: HashMap<String, OwnedValue>,
+	extensions: Option<Extensions>,
+}
+
+/// The body of a POST to `/gql`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GqlBody {
+	Batch(Vec<GqlRequest>),
+	Single(GqlRequest),
+}
+
+#[derive(Serialize)]
+struct GqlError {
+	message: &'static str,
+}
+
+/// A response with only errors
+#[derive(Serialize)]
+struct ErrorResponse {
+	errors: Vec<GqlError>,
+	data: Option<()>,
+}
+
+/// Status, content type and body of a response
+type Response = (u16, &'static str, String);
+
+/// Reads one request and responds to it, then closes the connection.
+async fn handle_http(stream: TcpStream, state: Arc<State>) {
+	let mut reader = BufReader::new(stream);
+
+	// i.e. `POST /gql HTTP/1.1`
+	let mut request_line = String::new();
+	if reader.read_line(&mut request_line).await.is_err() {
+		return;
+	}
+
+	let mut content_length = 0;
+	let mut authorization = None;
+	let mut expect_continue = false;
+
+	// Headers, until an empty line
+	loop {
+		let mut line = String::new();
+
+		match reader.read_line(&mut line).await {
+			Ok(0) | Err(_) => return,
+			Ok(_) if line.trim_end().is_empty() => break,
+			Ok(_) => {
+				if let Some((name, value)) = line.split_once(':') {
+					let value = value.trim();
+
+					if name.eq_ignore_ascii_case("content-length") {
+						content_length = value.parse().unwrap_or(0);
+					} else if name.eq_ignore_ascii_case("authorization") {
+						authorization = Some(value.to_owned());
+					} else if name.eq_ignore_ascii_case("expect") {
+						// curl waits for this before sending big bodies
+						expect_continue = value.eq_ignore_ascii_case("100-continue");
+					}
+				}
+			}
+		}
+	}
+
+	if expect_continue
+		&& reader
+			.get_mut()
+			.write_all(b"HTTP/1.1 100 Continue\r\n\r\n")
+			.await
+			.is_err()
+	{
+		return;
+	}
+
+	let mut body = vec![0; content_length];
+	if reader.read_exact(&mut body).await.is_err() {
+		return;
+	}
+
+	let mut parts = request_line.split(' ');
+
+	let (status, content_type, body) = match (parts.next(), parts.next()) {
+		(Some("POST"), Some("/gql")) => gql(&state, &mut body),
+		(Some("GET"), Some(path)) if path.starts_with("/vod/") => (
+			200,
+			"application/vnd.apple.mpegurl",
+			with_base(&state, VOD_PLAYLIST),
+		),
+		(Some("GET"), Some(path)) if path.starts_with("/media/") => (
+			200,
+			"application/vnd.apple.mpegurl",
+			MEDIA_PLAYLIST.to_owned(),
+		),
+		(Some(_), Some(path)) if path.starts_with("/oauth2/") => oauth(
+			&state,
+			&path["/oauth2/".len()..],
+			&body,
+			authorization.as_deref(),
+		),
+		(Some("GET"), Some("/activate")) => (
+			200,
+			"text/plain",
+			"This is where you'd enter the code, the mock server doesn't need it".to_owned(),
+		),
+		_ => (404, "text/plain", "Not found".to_owned()),
+	};
+
+	let _ = reader
+		.into_inner()
+		.write_all(
+			[
+				"HTTP/1.1 ",
+				&status.to_string(),
+				// No reason phrase, but it still needs the space
+				" \r\nContent-Type: ",
+				content_type,
+				"\r\nContent-Length: ",
+				&body.len().to_string(),
+				"\r\nConnection: close\r\n\r\n",
+				&body,
+			]
+			.concat()
+			.as_bytes(),
+		)
+		.await;
+}
+
+/// Responds to a GQL request, or a batch of them.
+fn gql(state: &State, body: &mut [u8]) -> Response {
+	let responses = match from_slice::<GqlBody>(body) {
+		Ok(GqlBody::Single(request)) => gql_response(state, &request),
+		Ok(GqlBody::Batch(requests)) => requests
+			.iter()
+			.map(|request| gql_response(state, request))
+			.collect::<Result<Vec<_>, _>>()
+			.map(|responses| ["[", &responses.join(","), "]"].concat()),
+		Err(e) => {
+			return (
+				400,
+				"text/plain",
+				["Invalid request: ", &e.to_string()].concat(),
+			)
+		}
+	};
+
+	match responses {
+		Ok(body) => (200, "application/json", body),
+		Err(response) => response,
+	}
+}
+
+/// The response to one GQL request, or the whole HTTP response if it should fail.
+fn gql_response(state: &State, request: &GqlRequest) -> Result<String, Response> {
+	let errors = |message| {
+		Ok(to_string(&ErrorResponse {
+			errors: vec![GqlError { message }],
+			data: None,
+		})
+		.expect("Errors should serialize"))
+	};
+
+	// Work out which operation this is
+	let operation = if request.query.is_some() {
+		request.operationName.as_deref()
+	} else if state.options.stale_hashes {
+		None
+	} else {
+		request
+			.extensions
+			.as_ref()
+			.and_then(|extensions| {
+				OPERATIONS
+					.iter()
+					.find(|(hash, _)| *hash == extensions.persistedQuery.sha256hash)
+			})
+			.map(|(_, operation)| *operation)
+	};
+
+	let Some(operation) = operation else {
+		// What twitch says when it doesn't know a hash
+		return errors("PersistedQueryNotFound");
+	};
+
+	for value in request.variables.values() {
+		match value {
+			OwnedValue::String(string) if string == "error" => return errors("Mock error"),
+			OwnedValue::String(string) if string == "http-error" => {
+				return Err((500, "text/plain", "Mock server error".to_owned()))
+			}
+			_ => (),
+		}
+	}
+
+	// Use the next page if it's asking for one
+	let more = [operation, ".more"].concat();
+	let names = if matches!(request.variables.get("cursor"), Some(OwnedValue::String(_))) {
+		vec![&*more, operation]
+	} else {
+		vec![operation]
+	};
+
+	names
+		.into_iter()
+		.find_map(|name| response_file(state, name))
+		.map(|response| with_base(state, &response))
+		.map_or_else(
+			|| errors("Mock server has no response for this operation"),
+			Ok,
+		)
+}
+
+/// Reads a response from the data directory, or the built in ones.
+fn response_file(state: &State, name: &str) -> Option<String> {
+	state
+		.options
+		.data
+		.as_ref()
+		.and_then(|dir| fs::read_to_string(dir.join([name, ".json"].concat())).ok())
+		.or_else(|| {
+			RESPONSES
+				.iter()
+				.find(|(operation, _)| *operation == name)
+				.map(|(_, response)| (*response).to_owned())
+		})
+}
+
+/// The only token the mock server accepts
+const ACCESS_TOKEN: &str = "mock-access-token";
+
+/// Responds to the OAuth endpoints: `device`, `token`, `validate` and `revoke`.
+fn oauth(state: &State, endpoint: &str, body: &[u8], authorization: Option<&str>) -> Response {
+	// Form values aren't decoded, the client doesn't send anything that needs it
+	let form = String::from_utf8_lossy(body);
+	let field = |name: &str| {
+		form.split('&')
+			.find_map(|pair| pair.strip_prefix(name)?.strip_prefix('='))
+	};
+
+	let error = |status: u16, message: &str| {
+		(
+			status,
+			"application/json",
+			[
+				r#"{"status":"#,
+				&status.to_string(),
+				r#","message":""#,
+				message,
+				r#""}"#,
+			]
+			.concat(),
+		)
+	};
+
+	let token = || {
+		(
+			200,
+			"application/json",
+			[
+				r#"{"access_token":""#,
+				ACCESS_TOKEN,
+				r#"","refresh_token":"mock-refresh-token","expires_in":14400,"scope":[],"token_type":"bearer"}"#,
+			]
+			.concat(),
+		)
+	};
+
+	match endpoint {
+		"device" => (
+			200,
+			"application/json",
+			with_base(
+				state,
+				r#"{"device_code":"mock-device-code","expires_in":1800,"interval":1,"user_code":"MOCKCODE","verification_uri":"{base}/activate"}"#,
+			),
+		),
+		"token" => match field("grant_type") {
+			Some("urn:ietf:params:oauth:grant-type:device_code") => {
+				// Pretend the user takes a moment to enter the code
+				if state.code_entered.swap(true, Ordering::Relaxed) {
+					token()
+				} else {
+					error(400, "authorization_pending")
+				}
+			}
+			Some("refresh_token") if field("refresh_token") == Some("mock-refresh-token") => {
+				token()
+			}
+			_ => error(400, "Invalid refresh token"),
+		},
+		"validate" if authorization == Some(&*["OAuth ", ACCESS_TOKEN].concat()) => (
+			200,
+			"application/json",
+			r#"{"client_id":"mock","login":"mockviewer","scopes":[],"user_id":"9001","expires_in":14400}"#
+				.to_owned(),
+		),
+		"validate" => error(401, "invalid access token"),
+		"revoke" => (200, "text/plain", String::new()),
+		_ => error(404, "Not found"),
+	}
+}
+
+/// Replaces `{base}` with the HTTP server's URL.
+fn with_base(state: &State, string: &str) -> String {
+	string.replace("{base}", &["http://", &state.options.http].concat())
+}
+
+// IRC
+
+/// Speaks enough IRC for the client: capabilities, registration, joining and pings. Once a channel
+/// is joined, sends [`CHAT`] in a loop.
+async fn handle_irc(stream: TcpStream, state: Arc<State>) {
+	let (reader, mut writer) = stream.into_split();
+	let mut lines = BufReader::new(reader).lines();
+
+	let mut nick = String::from("justinfan0");
+	let mut channel = None::<String>;
+
+	let mut chat = CHAT.iter().cycle();
+	let mut chat_interval = interval(Duration::from_secs(1));
+
+	let reconnect = sleep(state.options.reconnect_after.unwrap_or(Duration::MAX));
+	tokio::pin!(reconnect);
+
+	loop {
+		let replies = tokio::select! {
+			line = lines.next_line() => match line {
+				Ok(Some(line)) => irc_replies(&line, &mut nick, &mut channel),
+				_ => return,
+			},
+			_ = chat_interval.tick(), if channel.is_some() => vec![chat
+				.next()
+				.expect("`CHAT` is cycled")
+				.replace("{channel}", channel.as_deref().unwrap_or_default())],
+			_ = &mut reconnect, if state.options.reconnect_after.is_some() => {
+				let _ = writer.write_all(b":tmi.twitch.tv RECONNECT\r\n").await;
+				return;
+			}
+		};
+
+		for reply in replies {
+			if writer
+				.write_all([&*reply, "\r\n"].concat().as_bytes())
+				.await
+				.is_err()
+			{
+				return;
+			}
+		}
+	}
+}
+
+/// Replies to a line from an IRC client.
+pub fn irc_replies(line: &str, nick: &mut String, channel: &mut Option<String>) -> Vec<String> {
+	let (command, params) = line.split_once(' ').unwrap_or((line, ""));
+
+	match command {
+		"CAP" => match params.split_once(" :") {
+			Some(("REQ", capabilities)) => {
+				vec![[":tmi.twitch.tv CAP * ACK :", capabilities].concat()]
+			}
+			_ => Vec::new(),
+		},
+		"NICK" => {
+			*nick = params.to_owned();
+
+			[
+				("001", "Welcome, GLHF!"),
+				("002", "Your host is tmi.twitch.tv"),
+				("003", "This server is rather new"),
+				("004", "-"),
+				("375", "-"),
+				("372", "You are in a maze of twisty passages, all alike."),
+				("376", ">"),
+			]
+			.into_iter()
+			.map(|(code, message)| [":tmi.twitch.tv ", code, " ", nick, " :", message].concat())
+			.collect()
+		}
+		"JOIN" => {
+			let joined = params.trim_start_matches('#').to_owned();
+
+			let replies = vec![
+				[
+					":",
+					nick,
+					"!",
+					nick,
+					"@",
+					nick,
+					".tmi.twitch.tv JOIN #",
+					&joined,
+				]
+				.concat(),
+				[
+					":",
+					nick,
+					".tmi.twitch.tv 353 ",
+					nick,
+					" = #",
+					&joined,
+					" :",
+					nick,
+				]
+				.concat(),
+				[
+					":",
+					nick,
+					".tmi.twitch.tv 366 ",
+					nick,
+					" #",
+					&joined,
+					" :End of /NAMES list",
+				]
+				.concat(),
+				[
+					"@emote-only=0;followers-only=10;r9k=0;room-id=1001;slow=30;subs-only=0 \
+					 :tmi.twitch.tv ROOMSTATE #",
+					&joined,
+				]
+				.concat(),
+				user_state(nick, &joined),
+			];
+
+			*channel = Some(joined);
+
+			replies
+		}
+		"PING" => vec![[":tmi.twitch.tv PONG tmi.twitch.tv ", params].concat()],
+		// Messages containing `error` are rejected, anything else is accepted
+		"PRIVMSG" => {
+			let (target, message) = params.split_once(" :").unwrap_or((params, ""));
+			let target = target.trim_start_matches('#');
+
+			if message.contains("error") {
+				vec![[
+					"@msg-id=msg_rejected :tmi.twitch.tv NOTICE #",
+					target,
+					" :Your message wasn't posted due to conflicts with the channel's moderation \
+					 settings.",
+				]
+				.concat()]
+			} else {
+				vec![user_state(nick, target)]
+			}
+		}
+		// Ignore `PASS`, `USER` and anything else
+		_ => Vec::new(),
+	}
+}
+
+/// Our badges in a channel, sent after joining it and after each message
+fn user_state(nick: &str, channel: &str) -> String {
+	[
+		"@badge-info=;badges=premium/1;color=#1E90FF;display-name=",
+		nick,
+		";emote-sets=0;mod=0;subscriber=0;user-type= :tmi.twitch.tv USERSTATE #",
+		channel,
+	]
+	.concat()
+}
+
+// PubSub
+
+/// The parts of a client's message that we use
+#[derive(Deserialize)]
+struct PubSubRequest {
+	r#type: String,
+	nonce: Option<String>,
+	data: Option<ListenData>,
+}
+
+#[derive(Deserialize)]
+struct ListenData {
+	topics: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct PubSubResponse<'a> {
+	r#type: &'static str,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	nonce: Option<&'a str>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	error: Option<&'static str>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	data: Option<PubSubMessage>,
+}
+
+#[derive(Serialize)]
+struct PubSubMessage {
+	topic: String,
+	/// JSON in a string
+	message: String,
+}
+
+/// Answers `LISTEN`s and `PING`s, and sends an event for each topic when it's listened to and
+/// every few seconds after.
+async fn handle_pubsub(stream: TcpStream, state: Arc<State>) {
+	let Ok(mut websocket) = tokio_tungstenite::accept_async(stream).await else {
+		return;
+	};
+
+	let mut topics = Vec::new();
+	let mut viewers = 1234;
+
+	// Topics get their first event when they're listened to
+	let event_period = Duration::from_secs(5);
+	let mut event_interval = interval_at(Instant::now() + event_period, event_period);
+
+	let reconnect = sleep(state.options.reconnect_after.unwrap_or(Duration::MAX));
+	tokio::pin!(reconnect);
+
+	loop {
+		let responses = tokio::select! {
+			message = websocket.next() => match message {
+				Some(Ok(Message::Text(mut text))) => {
+					// Parsing changes the buffer
+					match from_slice::<PubSubRequest>(unsafe { text.as_bytes_mut() }) {
+						Ok(request) => match &*request.r#type {
+							"LISTEN" => {
+								let new = request.data.map_or_else(Vec::new, |data| data.topics);
+
+								let response = to_string(&PubSubResponse {
+									r#type: "RESPONSE",
+									nonce: request.nonce.as_deref(),
+									error: Some(""),
+									data: None,
+								})
+								.expect("Responses should serialize");
+
+								// The first events are sent straight away, so clients don't have
+								// to wait for them
+								let events = new
+									.iter()
+									.filter_map(|topic| pubsub_event(topic, viewers))
+									.collect::<Vec<_>>();
+
+								topics.extend(new);
+
+								[vec![response], events].concat()
+							}
+							"PING" => vec![to_string(&PubSubResponse {
+								r#type: "PONG",
+								nonce: None,
+								error: None,
+								data: None,
+							})
+							.expect("Responses should serialize")],
+							_ => Vec::new(),
+						},
+						Err(_) => Vec::new(),
+					}
+				}
+				Some(Ok(_)) => Vec::new(),
+				_ => return,
+			},
+			_ = event_interval.tick() => {
+				viewers += 1;
+
+				topics
+					.iter()
+					.filter_map(|topic| pubsub_event(topic, viewers))
+					.collect()
+			},
+			_ = &mut reconnect, if state.options.reconnect_after.is_some() => {
+				let _ = websocket.send(Message::Text(r#"{"type":"RECONNECT"}"#.to_owned())).await;
+				let _ = websocket.close(None).await;
+				return;
+			}
+		};
+
+		for response in responses {
+			if websocket.send(Message::Text(response)).await.is_err() {
+				return;
+			}
+		}
+	}
+}
+
+/// Sent on `community-points-channel-v1` topics
+const REDEMPTION: &str = r##"{
+	"type": "reward-redeemed",
+	"data": {
+		"timestamp": "2023-05-01T12:00:00Z",
+		"redemption": {
+			"user": { "id": "2", "login": "subbed", "display_name": "Subbed" },
+			"reward": { "title": "Hydrate", "cost": 500, "background_color": "#00C7AC" }
+		}
+	}
+}"##;
+
+/// A `MESSAGE` for the topic, if we have one for it.
+fn pubsub_event(topic: &str, viewers: u32) -> Option<String> {
+	let message = match topic.split_once('.')?.0 {
+		"video-playback-by-id" => [
+			r#"{"type":"viewcount","server_time":0,"viewers":"#,
+			&viewers.to_string(),
+			"}",
+		]
+		.concat(),
+		"community-points-channel-v1" => REDEMPTION.to_owned(),
+		_ => return None,
+	};
+
+	Some(
+		to_string(&PubSubResponse {
+			r#type: "MESSAGE",
+			nonce: None,
+			error: None,
+			data: Some(PubSubMessage {
+				topic: topic.to_owned(),
+				message,
+			}),
+		})
+		.expect("Messages should serialize"),
+	)
+}
+
+#[cfg(test)]
+mod tests {
+	use curl::easy::Easy;
+
+	use super::*;
+
+	/// Sends `body` to the GQL endpoint of servers started with `options`, returning the status
+	/// and response.
+	fn post(options: Options, body: &str) -> (u32, String) {
+		let (_, url) = start(options)
+			.into_iter()
+			.find(|(name, _)| *name == "TWITCH_TUI_GQL")
+			.expect("Should have a GQL endpoint");
+
+		let mut easy = Easy::new();
+		easy.url(&url).expect("Should be a valid URL");
+		easy.post_fields_copy(body.as_bytes())
+			.expect("Should be able to set the body");
+
+		let mut response = Vec::new();
+
+		{
+			let mut transfer = easy.transfer();
+
+			transfer
+				.write_function(|slice| {
+					response.extend_from_slice(slice);
+					Ok(slice.len())
+				})
+				.expect("Should be able to read the response");
+			transfer.perform().expect("Should reach the mock server");
+		}
+
+		(
+			easy.response_code().expect("Should have a status"),
+			String::from_utf8(response).expect("Responses should be UTF-8"),
+		)
+	}
+
+	#[test]
+	fn answers_batches_in_order() {
+		let (status, body) = post(
+			Options::default(),
+			r#"[
+				{"operationName":"ChannelPage","query":"","variables":{"login":"mockstreamer"}},
+				{"operationName":"ClipsPage","query":"","variables":{"login":"mockstreamer"}}
+			]"#,
+		);
+
+		assert_eq!(status, 200);
+
+		let responses =
+			from_slice::<Vec<OwnedValue>>(&mut body.into_bytes()).expect("Should be a JSON array");
+		assert_eq!(responses.len(), 2);
+	}
+
+	#[test]
+	fn fails_for_error_variables() {
+		let (status, body) = post(
+			Options::default(),
+			r#"{"operationName":"DirectoryPage_Game","query":"","variables":{"name":"error"}}"#,
+		);
+		assert_eq!(status, 200);
+		assert!(body.contains("Mock error"));
+
+		let (status, _) = post(
+			Options::default(),
+			r#"{"operationName":"DirectoryPage_Game","query":"","variables":{"name":"http-error"}}"#,
+		);
+		assert_eq!(status, 500);
+	}
+
+	#[test]
+	fn rejects_unknown_hashes() {
+		let (_, body) = post(
+			Options::default(),
+			r#"{"extensions":{"persistedQuery":{"sha256hash":"stale"}},"variables":{}}"#,
+		);
+
+		assert!(body.contains("PersistedQueryNotFound"));
+	}
+
+	#[test]
+	fn stale_hashes_reject_known_ones() {
+		let shelves = r#"{"extensions":{"persistedQuery":{"sha256hash":"41858598cc637cf9e6153818f5a4d274a08e8743e4a85903cdfe39c464152404"}},"variables":{}}"#;
+
+		let (_, body) = post(Options::default(), shelves);
+		assert!(!body.contains("PersistedQueryNotFound"));
+
+		let (_, body) = post(
+			Options {
+				stale_hashes: true,
+				..Options::default()
+			},
+			shelves,
+		);
+		assert!(body.contains("PersistedQueryNotFound"));
+	}
+}
@@ -0,0 +1,602 @@
+//! What [`crate::Client`] returns. These only have what's useful outside of twitch's API, with
+//! dates parsed and nothing nested in `edges` and `node`s.
+
+use chrono::{DateTime, Utc};
+
+use crate::client::Client;
+use crate::error::Error;
+use crate::gql;
+
+/// Parses one of twitch's RFC 3339 dates, if it's there and valid.
+fn parse_date(date: Option<&str>) -> Option<DateTime<Utc>> {
+	date.and_then(|date| date.parse().ok())
+}
+
+/// The cursor for the page after the one ending at `cursor`, if there is one
+fn next_cursor(cursor: Option<&String>, has_next_page: bool) -> Option<String> {
+	cursor.filter(|_| has_next_page).cloned()
+}
+
+/// A channel (or user, as the API calls them)
+#[derive(Debug, Clone, Default)]
+pub struct Channel {
+	pub id: String,
+	/// Name used in URLs and chat, i.e. `xqc`
+	pub login: String,
+	/// Name shown on twitch, i.e. `xQc`
+	pub display_name: String,
+	/// Hex code without the `#`, i.e. `9146FF`
+	pub colour: Option<String>,
+	pub partner: bool,
+	/// Title of their current or last stream
+	pub title: Option<String>,
+	pub description: Option<String>,
+	pub followers: Option<u32>,
+	/// When they last went live, `None` if they never have (or we didn't ask)
+	pub last_live: Option<DateTime<Utc>>,
+	/// Their stream, if they're live and we know about it
+	pub stream: Option<Box<Stream>>,
+	/// Their next scheduled streams, if we asked
+	pub schedule: Vec<ScheduleSegment>,
+	/// Their latest past broadcast, if we asked
+	pub latest_video: Option<Box<Video>>,
+	/// Their most viewed clip, if we asked
+	pub top_clip: Option<Box<Clip>>,
+}
+impl Channel {
+	/// URL of this channel's stream playlist, see [`Stream::playback_url`]. Fails if they aren't
+	/// live.
+	pub fn playback_url(&self, client: &mut Client) -> Result<String, Error> {
+		client.stream_playback_url(&self.login)
+	}
+
+	/// This channel's most viewed clips from the last week, see [`Client::channel_clips`].
+	pub fn clips(&self, client: &mut Client, cursor: Option<&str>) -> Result<Page<Clip>, Error> {
+		client.channel_clips(&self.login, cursor)
+	}
+
+	/// This channel's page, see [`Client::channel`].
+	pub fn overview(&self, client: &mut Client) -> Result<ChannelOverview, Error> {
+		client.channel(&self.login)
+	}
+
+	/// Sets this channel's stream. The stream doesn't say who's streaming or (usually) it's title,
+	/// they're from this channel.
+	fn with_stream(mut self, stream: Option<gql::Stream>) -> Self {
+		self.stream = stream.map(|stream| {
+			let stream = Stream::from(stream);
+
+			Box::new(Stream {
+				title: if stream.title.is_empty() {
+					self.title.clone().unwrap_or_default()
+				} else {
+					stream.title
+				},
+				channel: self.clone(),
+				..stream
+			})
+		});
+
+		self
+	}
+}
+impl From<gql::User> for Channel {
+	fn from(user: gql::User) -> Self {
+		Self {
+			id: user.id,
+			login: user.login,
+			display_name: user.displayName,
+			colour: user.primaryColorHex.filter(|colour| !colour.is_empty()),
+			partner: user.roles.is_some_and(|roles| roles.isPartner),
+			title: user.broadcastSettings.map(|settings| settings.title),
+			..Self::default()
+		}
+	}
+}
+impl From<gql::SearchUser> for Channel {
+	fn from(user: gql::SearchUser) -> Self {
+		Self {
+			id: user.id,
+			login: user.login,
+			display_name: user.displayName,
+			colour: None,
+			partner: user.roles.isPartner,
+			title: Some(user.broadcastSettings.title),
+			description: user
+				.description
+				.filter(|description| !description.is_empty()),
+			followers: Some(user.followers.totalCount),
+			last_live: parse_date(user.lastBroadcast.startedAt.as_deref()),
+			stream: None,
+			// Searches only ask for the next one
+			schedule: user
+				.channel
+				.schedule
+				.and_then(|schedule| schedule.nextSegment)
+				.map(ScheduleSegment::from)
+				.into_iter()
+				.collect(),
+			latest_video: user
+				.latestVideo
+				.edges
+				.into_iter()
+				.next()
+				.map(|edge| Box::new(Video::from(edge.node))),
+			top_clip: user
+				.topClip
+				.edges
+				.into_iter()
+				.next()
+				.map(|edge| Box::new(Clip::from(edge.node))),
+		}
+		.with_stream(user.stream)
+	}
+}
+impl From<gql::FollowedUser> for Channel {
+	fn from(user: gql::FollowedUser) -> Self {
+		Self {
+			last_live: parse_date(user.lastBroadcast.startedAt.as_deref()),
+			..Self::from(user.user)
+		}
+		.with_stream(user.stream)
+	}
+}
+
+/// A live stream
+#[derive(Debug, Clone)]
+pub struct Stream {
+	/// Who's streaming, without [`Channel::stream`]
+	pub channel: Channel,
+	pub title: String,
+	pub viewers: u32,
+	pub started_at: Option<DateTime<Utc>>,
+	pub category: Option<Category>,
+	/// Tags the streamer set, i.e. `English`
+	pub tags: Vec<String>,
+}
+impl Stream {
+	/// URL of this stream's master playlist, with all of it's qualities. It can be given to a
+	/// player, i.e. `mpv`.
+	pub fn playback_url(&self, client: &mut Client) -> Result<String, Error> {
+		client.stream_playback_url(&self.channel.login)
+	}
+}
+impl From<gql::Stream> for Stream {
+	fn from(stream: gql::Stream) -> Self {
+		// Missing when it's obvious who's streaming
+		let channel = stream.broadcaster.map(Channel::from).unwrap_or_default();
+
+		Self {
+			// Some requests only have the title in the channel's broadcast settings
+			title: stream
+				.title
+				.or_else(|| channel.title.clone())
+				.unwrap_or_default(),
+			channel,
+			viewers: stream.viewersCount,
+			started_at: parse_date(stream.createdAt.as_deref()),
+			category: stream.game.map(Category::from),
+			tags: stream
+				.freeformTags
+				.into_iter()
+				.map(|tag| tag.name)
+				.collect(),
+		}
+	}
+}
+impl From<gql::StreamConnection> for Page<Stream> {
+	fn from(streams: gql::StreamConnection) -> Self {
+		Self {
+			cursor: next_cursor(
+				streams.edges.last().and_then(|edge| edge.cursor.as_ref()),
+				streams.pageInfo.hasNextPage,
+			),
+			items: streams
+				.edges
+				.into_iter()
+				.map(|edge| Stream::from(edge.node))
+				.collect(),
+		}
+	}
+}
+
+/// A past broadcast, highlight or upload
+#[derive(Debug, Clone)]
+pub struct Video {
+	pub id: String,
+	pub title: Option<String>,
+	pub length_seconds: u32,
+	pub views: Option<u32>,
+	pub created_at: Option<DateTime<Utc>>,
+	/// Who it's from
+	pub channel: Option<Channel>,
+	pub category: Option<Category>,
+}
+impl Video {
+	/// URL of this video's master playlist, with all of it's qualities.
+	pub fn playback_url(&self, client: &mut Client) -> Result<String, Error> {
+		client.video_playback_url(&self.id)
+	}
+}
+impl From<gql::Video> for Video {
+	fn from(video: gql::Video) -> Self {
+		Self {
+			id: video.id,
+			title: video.title,
+			length_seconds: video.lengthSeconds,
+			views: video.viewCount,
+			created_at: parse_date(video.createdAt.as_deref()),
+			channel: video.owner.map(Channel::from),
+			category: video.game.map(Category::from),
+		}
+	}
+}
+impl From<gql::VideoConnection> for Page<Video> {
+	fn from(videos: gql::VideoConnection) -> Self {
+		Self {
+			cursor: next_cursor(
+				videos.edges.last().and_then(|edge| edge.cursor.as_ref()),
+				videos.pageInfo.is_some_and(|info| info.hasNextPage),
+			),
+			items: videos
+				.edges
+				.into_iter()
+				.map(|edge| Video::from(edge.node))
+				.collect(),
+		}
+	}
+}
+
+/// A clip of a stream
+#[derive(Debug, Clone)]
+pub struct Clip {
+	/// Identifies the clip in URLs, i.e. `clips.twitch.tv/<slug>`
+	pub slug: String,
+	pub title: Option<String>,
+	pub duration_seconds: u32,
+	pub views: Option<u32>,
+	pub created_at: Option<DateTime<Utc>>,
+	/// Who it's from
+	pub channel: Option<Channel>,
+	/// Who clipped it, `None` if their account is gone
+	pub curator: Option<Channel>,
+	pub category: Option<Category>,
+	/// The stream's language, i.e. `EN`
+	pub language: Option<String>,
+}
+impl Clip {
+	/// URL of this clip's video at the best quality. Clips don't have playlists, so this is the
+	/// video itself.
+	pub fn playback_url(&self, client: &mut Client) -> Result<String, Error> {
+		client.clip_playback_url(&self.slug)
+	}
+}
+impl From<gql::Clip> for Clip {
+	fn from(clip: gql::Clip) -> Self {
+		Self {
+			slug: clip.slug,
+			title: clip.clipTitle,
+			duration_seconds: clip.durationSeconds,
+			views: clip.clipViewCount,
+			created_at: parse_date(clip.clipCreatedAt.as_deref()),
+			channel: clip.broadcaster.map(Channel::from),
+			curator: clip.curator.map(Channel::from),
+			category: clip.game.map(Category::from),
+			language: clip.language,
+		}
+	}
+}
+impl From<gql::ClipConnection> for Page<Clip> {
+	fn from(clips: gql::ClipConnection) -> Self {
+		Self {
+			cursor: next_cursor(
+				clips.edges.last().and_then(|edge| edge.cursor.as_ref()),
+				clips.pageInfo.is_some_and(|info| info.hasNextPage),
+			),
+			items: clips
+				.edges
+				.into_iter()
+				.map(|edge| Clip::from(edge.node))
+				.collect(),
+		}
+	}
+}
+
+/// One of a clip's video files
+#[derive(Debug, Clone)]
+pub struct ClipQuality {
+	/// Height, i.e. `720`
+	pub quality: String,
+	/// The video, signed so it can be played
+	pub url: String,
+}
+
+/// A category (or game, as the API calls them)
+#[derive(Debug, Clone)]
+pub struct Category {
+	/// Used to look it up, i.e. with [`Client::category_streams`]
+	pub name: String,
+	/// Shown on twitch, usually the same as `name`
+	pub display_name: String,
+	/// How many people are watching streams in it, if we asked
+	pub viewers: Option<u32>,
+	pub tags: Vec<String>,
+	/// When the game came out, if we asked and it's a game
+	pub released: Option<DateTime<Utc>>,
+}
+impl Category {
+	/// This category's most viewed clips from the last week, see [`Client::category_clips`].
+	pub fn clips(&self, client: &mut Client, cursor: Option<&str>) -> Result<Page<Clip>, Error> {
+		client.category_clips(&self.name, cursor)
+	}
+
+	/// The live streams in this category, most relevant first. See [`Client::category_streams`]
+	/// for other sorts and filters.
+	pub fn streams(
+		&self,
+		client: &mut Client,
+		cursor: Option<&str>,
+	) -> Result<Page<Stream>, Error> {
+		client.category_streams(&self.name, &Default::default(), cursor)
+	}
+}
+impl From<gql::Game> for Category {
+	fn from(game: gql::Game) -> Self {
+		Self {
+			display_name: game.displayName.unwrap_or_else(|| game.name.clone()),
+			name: game.name,
+			viewers: game.viewersCount,
+			tags: game
+				.tags
+				.unwrap_or_default()
+				.into_iter()
+				.map(|tag| tag.localizedName)
+				.collect(),
+			released: parse_date(game.originalReleaseDate.as_deref()),
+		}
+	}
+}
+impl From<gql::GameConnection> for Page<Category> {
+	fn from(games: gql::GameConnection) -> Self {
+		Self {
+			cursor: next_cursor(
+				games.edges.last().and_then(|edge| edge.cursor.as_ref()),
+				games.pageInfo.hasNextPage,
+			),
+			items: games
+				.edges
+				.into_iter()
+				.map(|edge| Category::from(edge.node))
+				.collect(),
+		}
+	}
+}
+
+/// A stream a channel has planned
+#[derive(Debug, Clone)]
+pub struct ScheduleSegment {
+	pub title: String,
+	pub starts_at: Option<DateTime<Utc>>,
+	/// `None` if they haven't said
+	pub ends_at: Option<DateTime<Utc>>,
+	/// Names of the categories they'll stream in
+	pub categories: Vec<String>,
+}
+impl From<gql::ScheduleSegment> for ScheduleSegment {
+	fn from(segment: gql::ScheduleSegment) -> Self {
+		Self {
+			title: segment.title,
+			starts_at: parse_date(segment.startAt.as_deref()),
+			ends_at: parse_date(segment.endAt.as_deref()),
+			categories: segment
+				.categories
+				.into_iter()
+				.map(|category| category.name)
+				.collect(),
+		}
+	}
+}
+
+/// Part of a listing that's too long for one response
+#[derive(Debug, Clone)]
+pub struct Page<T> {
+	pub items: Vec<T>,
+	/// Pass this to the same method to get the next page, `None` if this is the last one
+	pub cursor: Option<String>,
+}
+
+/// A row of the home page, or a personal section
+#[derive(Debug, Clone)]
+pub struct Shelf {
+	/// The title, in parts that can be emphasised
+	pub title: Vec<TitlePart>,
+	pub items: Vec<ShelfItem>,
+}
+impl From<gql::Shelf> for Shelf {
+	fn from(shelf: gql::Shelf) -> Self {
+		let tokens = shelf.title.localizedTitleTokens;
+
+		Self {
+			// Use the fallback title if we don't know how to show any of the parts
+			title: if tokens
+				.iter()
+				.any(|token| matches!(token.node, gql::TextToken::None))
+			{
+				vec![TitlePart::from(shelf.title.fallbackLocalizedTitle)]
+			} else {
+				tokens
+					.into_iter()
+					.filter_map(|token| match token.node {
+						gql::TextToken::BrowsableCollection { collectionName } => {
+							Some(collectionName.fallbackLocalizedTitle.into())
+						}
+						gql::TextToken::Game(game) => {
+							Some(Category::from(game).display_name.into())
+						}
+						gql::TextToken::TextToken { text, hasEmphasis } => Some(TitlePart {
+							text,
+							emphasis: hasEmphasis,
+						}),
+						gql::TextToken::None => None,
+					})
+					.collect()
+			},
+			items: shelf
+				.content
+				.edges
+				.into_iter()
+				.filter_map(|edge| match edge.node {
+					gql::ShelfContent::Clip(clip) => Some(ShelfItem::Clip(Box::new(clip.into()))),
+					gql::ShelfContent::Game(game) => Some(ShelfItem::Category(game.into())),
+					gql::ShelfContent::Stream(stream) => {
+						Some(ShelfItem::Stream(Box::new(stream.into())))
+					}
+					gql::ShelfContent::None => None,
+				})
+				.collect(),
+		}
+	}
+}
+impl From<gql::PersonalSection> for Shelf {
+	fn from(section: gql::PersonalSection) -> Self {
+		Self {
+			title: vec![section.title.localizedFallback.into()],
+			items: section
+				.items
+				.into_iter()
+				// The stream's title and who's streaming are on the user
+				.filter_map(|item| {
+					Channel::from(item.user)
+						.with_stream(Some(item.content))
+						.stream
+						.map(ShelfItem::Stream)
+				})
+				.collect(),
+		}
+	}
+}
+
+/// Part of a [`Shelf`]'s title
+#[derive(Debug, Clone)]
+pub struct TitlePart {
+	pub text: String,
+	/// Should this be shown in bold?
+	pub emphasis: bool,
+}
+impl From<String> for TitlePart {
+	fn from(text: String) -> Self {
+		Self {
+			text,
+			emphasis: false,
+		}
+	}
+}
+
+/// Something on a [`Shelf`]
+#[derive(Debug, Clone)]
+pub enum ShelfItem {
+	Stream(Box<Stream>),
+	Category(Category),
+	Clip(Box<Clip>),
+}
+
+/// Everything on a channel's page
+#[derive(Debug, Clone)]
+pub struct ChannelOverview {
+	/// With their description, followers, stream and schedule
+	pub channel: Channel,
+	/// Their latest past broadcasts
+	pub archives: Vec<Video>,
+	/// Their latest highlights
+	pub highlights: Vec<Video>,
+	/// Their latest uploads
+	pub uploads: Vec<Video>,
+	/// Their most viewed clips of all time
+	pub clips: Vec<Clip>,
+}
+
+/// One kind of result from a search
+#[derive(Debug, Clone)]
+pub struct SearchSection<T> {
+	pub items: Vec<T>,
+	/// Where twitch shows this section, from 1 (first) to 5
+	pub score: usize,
+	/// How many results there are in total, if twitch says
+	pub total: Option<u32>,
+	/// Pass this to [`Client::search_in`] to get more of this section, `None` if there aren't any
+	pub cursor: Option<String>,
+}
+impl<T, U: Into<T>> From<gql::SearchResult<U>> for SearchSection<T> {
+	fn from(result: gql::SearchResult<U>) -> Self {
+		Self {
+			items: result
+				.edges
+				.into_iter()
+				.map(|edge| edge.item.into())
+				.collect(),
+			score: result.score,
+			total: result.totalMatches,
+			cursor: result.cursor,
+		}
+	}
+}
+
+/// Everything a search found
+#[derive(Debug, Clone)]
+pub struct SearchResults {
+	/// Channels matching the search, whether they're live or not
+	pub channels: SearchSection<Channel>,
+	/// Live channels with the search as a tag
+	pub channels_with_tag: SearchSection<Channel>,
+	pub categories: SearchSection<Category>,
+	pub videos: SearchSection<Video>,
+	/// Live streams that twitch thinks are related
+	pub related_streams: SearchSection<Stream>,
+}
+impl From<gql::SearchFor> for SearchResults {
+	fn from(search_for: gql::SearchFor) -> Self {
+		let related = search_for.relatedLiveChannels;
+
+		Self {
+			channels: search_for.channels.into(),
+			channels_with_tag: search_for.channelsWithTag.into(),
+			categories: search_for.games.into(),
+			videos: search_for.videos.into(),
+			related_streams: SearchSection {
+				items: related
+					.edges
+					.into_iter()
+					.filter_map(|edge| edge.item.stream.map(Stream::from))
+					.collect(),
+				score: related.score,
+				total: related.totalMatches,
+				cursor: related.cursor,
+			},
+		}
+	}
+}
+
+/// Something twitch suggests for a partly typed search
+#[derive(Debug, Clone)]
+pub enum SearchSuggestion {
+	/// Just something to search for
+	Text(String),
+	/// A channel, which can be opened directly
+	Channel { login: String, live: bool },
+	/// A category, which can be opened directly
+	Category(Category),
+}
+impl From<gql::SearchSuggestion> for SearchSuggestion {
+	fn from(suggestion: gql::SearchSuggestion) -> Self {
+		match suggestion.content {
+			Some(gql::SuggestionContent::Channel { login, isLive }) => SearchSuggestion::Channel {
+				login,
+				live: isLive,
+			},
+			Some(gql::SuggestionContent::Category { game }) => {
+				SearchSuggestion::Category(game.into())
+			}
+			None => SearchSuggestion::Text(suggestion.text),
+		}
+	}
+}
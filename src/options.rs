@@ -0,0 +1,345 @@
+//! Sorts and filters for [`crate::Client`]'s listings. These can be read from a config file, using
+//! the variant names (i.e. `"ViewerCount"`), and switched between with their `next` methods.
+
+use serde::Deserialize;
+
+/// A section of the personal section (the column on the left of the webapp)
+#[derive(Deserialize, Clone, Copy)]
+pub enum PersonalSectionType {
+	RecommendedSection,
+	/// Needs a channel to be similar to
+	SimilarSection,
+}
+impl From<PersonalSectionType> for &str {
+	fn from(val: PersonalSectionType) -> Self {
+		match val {
+			PersonalSectionType::RecommendedSection => "RECOMMENDED_SECTION",
+			PersonalSectionType::SimilarSection => "SIMILAR_SECTION",
+		}
+	}
+}
+
+/// How a category's streams are sorted
+#[derive(Deserialize, Clone, Copy)]
+pub enum StreamSort {
+	Relevance,
+	// Most viewers first
+	ViewerCount,
+	// Fewest viewers first
+	ViewerCountAsc,
+	// Most recently started first
+	Recent,
+}
+impl StreamSort {
+	/// The next sort, for switching between them
+	pub fn next(self) -> Self {
+		match self {
+			StreamSort::Relevance => StreamSort::ViewerCount,
+			StreamSort::ViewerCount => StreamSort::ViewerCountAsc,
+			StreamSort::ViewerCountAsc => StreamSort::Recent,
+			StreamSort::Recent => StreamSort::Relevance,
+		}
+	}
+
+	/// Name shown in titles
+	pub fn name(self) -> &'static str {
+		match self {
+			StreamSort::Relevance => "most relevant first",
+			StreamSort::ViewerCount => "most viewers first",
+			StreamSort::ViewerCountAsc => "fewest viewers first",
+			StreamSort::Recent => "recently started first",
+		}
+	}
+}
+impl From<StreamSort> for &str {
+	fn from(val: StreamSort) -> Self {
+		match val {
+			StreamSort::Relevance => "RELEVANCE",
+			StreamSort::ViewerCount => "VIEWER_COUNT",
+			StreamSort::ViewerCountAsc => "VIEWER_COUNT_ASC",
+			StreamSort::Recent => "RECENT",
+		}
+	}
+}
+
+/// Sort and filters for a category's streams
+#[derive(Clone)]
+pub struct StreamOptions {
+	pub sort: StreamSort,
+	/// Tags streams need to have, i.e. `English`
+	pub tags: Vec<String>,
+	/// Languages streams need to be in, i.e. `EN`
+	pub languages: Vec<String>,
+	/// IDs of the old kind of tags, which twitch mostly ignores now
+	pub tag_ids: Vec<String>,
+}
+impl Default for StreamOptions {
+	fn default() -> Self {
+		Self {
+			sort: StreamSort::Relevance,
+			tags: Vec::new(),
+			languages: Vec::new(),
+			tag_ids: Vec::new(),
+		}
+	}
+}
+impl StreamOptions {
+	/// Adds a tag, or a language if it starts with `lang:`, or removes it if it's already there.
+	pub fn toggle(&mut self, filter: &str) {
+		if let Some(language) = filter.strip_prefix("lang:") {
+			toggle(&mut self.languages, language.to_uppercase());
+		} else {
+			toggle(&mut self.tags, filter.to_owned());
+		}
+	}
+
+	/// Describes these options for titles, i.e. "most viewers first; tags: English"
+	pub fn describe(&self) -> String {
+		let mut parts = vec![self.sort.name().to_owned()];
+
+		if !self.tags.is_empty() {
+			parts.push(["tags: ", &self.tags.join(", ")].concat());
+		}
+		if !self.languages.is_empty() {
+			parts.push(["languages: ", &self.languages.join(", ")].concat());
+		}
+
+		parts.join("; ")
+	}
+}
+
+/// What kind of results a search looks for
+#[derive(Deserialize, Clone, Copy)]
+pub enum SearchIndex {
+	// Live channels with query as a tag
+	ChannelWithTag,
+	// Live channels that match the search
+	Channel,
+	// Category matching query
+	Game,
+	// Past videos
+	Vod,
+}
+impl SearchIndex {
+	/// Splits a scope off the start of a search, i.e. `game:minecraft` is `Game` and `minecraft`.
+	/// `channel:`, `tag:`, `game:` and `vod:` are scopes.
+	pub fn from_prefix(search: &str) -> (Option<Self>, &str) {
+		match search.split_once(':') {
+			Some(("channel", query)) => (Some(SearchIndex::Channel), query),
+			Some(("tag", query)) => (Some(SearchIndex::ChannelWithTag), query),
+			Some(("game" | "category", query)) => (Some(SearchIndex::Game), query),
+			Some(("vod" | "video", query)) => (Some(SearchIndex::Vod), query),
+			_ => (None, search),
+		}
+	}
+
+	/// The next scope, for switching between them. `None` is every kind of result.
+	pub fn next(scope: Option<Self>) -> Option<Self> {
+		match scope {
+			None => Some(SearchIndex::Channel),
+			Some(SearchIndex::Channel) => Some(SearchIndex::ChannelWithTag),
+			Some(SearchIndex::ChannelWithTag) => Some(SearchIndex::Game),
+			Some(SearchIndex::Game) => Some(SearchIndex::Vod),
+			Some(SearchIndex::Vod) => None,
+		}
+	}
+
+	/// Name shown in titles
+	pub fn name(self) -> &'static str {
+		match self {
+			SearchIndex::ChannelWithTag => "live channels with tag",
+			SearchIndex::Channel => "channels",
+			SearchIndex::Game => "categories",
+			SearchIndex::Vod => "videos",
+		}
+	}
+}
+impl From<SearchIndex> for &str {
+	fn from(val: SearchIndex) -> Self {
+		match val {
+			SearchIndex::ChannelWithTag => "CHANNEL_WITH_TAG",
+			SearchIndex::Channel => "CHANNEL",
+			SearchIndex::Game => "GAME",
+			SearchIndex::Vod => "VOD",
+		}
+	}
+}
+
+/// A kind of video on a channel
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum VideoType {
+	// Past broadcasts
+	Archive,
+	Highlight,
+	Upload,
+	// Uploads that were shown like a stream
+	Premiere,
+}
+impl VideoType {
+	/// The next type, for switching between them
+	pub fn next(self) -> Self {
+		match self {
+			VideoType::Archive => VideoType::Highlight,
+			VideoType::Highlight => VideoType::Upload,
+			VideoType::Upload => VideoType::Premiere,
+			VideoType::Premiere => VideoType::Archive,
+		}
+	}
+
+	/// Name shown in titles
+	pub fn name(self) -> &'static str {
+		match self {
+			VideoType::Archive => "Past broadcasts",
+			VideoType::Highlight => "Highlights",
+			VideoType::Upload => "Uploads",
+			VideoType::Premiere => "Premieres",
+		}
+	}
+}
+impl From<VideoType> for &str {
+	fn from(val: VideoType) -> Self {
+		match val {
+			VideoType::Archive => "ARCHIVE",
+			VideoType::Highlight => "HIGHLIGHT",
+			VideoType::Upload => "UPLOAD",
+			VideoType::Premiere => "PAST_PREMIERE",
+		}
+	}
+}
+
+/// How a channel's videos are sorted
+#[derive(Deserialize, Clone, Copy)]
+pub enum VideoSort {
+	// Newest first
+	Time,
+	// Most viewed first
+	Views,
+}
+impl VideoSort {
+	/// The other sort, for switching between them
+	pub fn next(self) -> Self {
+		match self {
+			VideoSort::Time => VideoSort::Views,
+			VideoSort::Views => VideoSort::Time,
+		}
+	}
+
+	/// Name shown in titles
+	pub fn name(self) -> &'static str {
+		match self {
+			VideoSort::Time => "newest first",
+			VideoSort::Views => "most viewed first",
+		}
+	}
+}
+impl From<VideoSort> for &str {
+	fn from(val: VideoSort) -> Self {
+		match val {
+			VideoSort::Time => "TIME",
+			VideoSort::Views => "VIEWS",
+		}
+	}
+}
+
+/// How far back a clip listing goes
+#[derive(Deserialize, Clone, Copy)]
+pub enum ClipPeriod {
+	// Last 24 hours
+	Day,
+	// Last 7 days
+	Week,
+	// Last 30 days
+	Month,
+	AllTime,
+}
+impl ClipPeriod {
+	/// The next period, for switching between them
+	pub fn next(self) -> Self {
+		match self {
+			ClipPeriod::Day => ClipPeriod::Week,
+			ClipPeriod::Week => ClipPeriod::Month,
+			ClipPeriod::Month => ClipPeriod::AllTime,
+			ClipPeriod::AllTime => ClipPeriod::Day,
+		}
+	}
+
+	/// Name shown in titles
+	pub fn name(self) -> &'static str {
+		match self {
+			ClipPeriod::Day => "last 24 hours",
+			ClipPeriod::Week => "last 7 days",
+			ClipPeriod::Month => "last 30 days",
+			ClipPeriod::AllTime => "all time",
+		}
+	}
+}
+impl From<ClipPeriod> for &str {
+	fn from(val: ClipPeriod) -> Self {
+		match val {
+			ClipPeriod::Day => "LAST_DAY",
+			ClipPeriod::Week => "LAST_WEEK",
+			ClipPeriod::Month => "LAST_MONTH",
+			ClipPeriod::AllTime => "ALL_TIME",
+		}
+	}
+}
+
+/// How a clip listing is sorted
+#[derive(Deserialize, Clone, Copy)]
+pub enum ClipSort {
+	// Most viewed first
+	Views,
+	// Newest first
+	Time,
+}
+impl ClipSort {
+	/// The other sort, for switching between them
+	pub fn next(self) -> Self {
+		match self {
+			ClipSort::Views => ClipSort::Time,
+			ClipSort::Time => ClipSort::Views,
+		}
+	}
+
+	/// Name shown in titles
+	pub fn name(self) -> &'static str {
+		match self {
+			ClipSort::Views => "most viewed first",
+			ClipSort::Time => "newest first",
+		}
+	}
+}
+impl From<ClipSort> for &str {
+	fn from(val: ClipSort) -> Self {
+		match val {
+			ClipSort::Views => "VIEWS_DESC",
+			ClipSort::Time => "CREATED_AT_DESC",
+		}
+	}
+}
+
+/// Whose clips to list
+#[derive(Debug, Clone)]
+pub enum ClipSource {
+	/// Property is the channel's login
+	Channel(String),
+	/// Property is the category's name
+	Category(String),
+}
+impl ClipSource {
+	/// The login or name, used in titles
+	pub fn name(&self) -> &str {
+		let (ClipSource::Channel(name) | ClipSource::Category(name)) = self;
+		name
+	}
+}
+
+/// Adds `item` to `list`, or removes it (ignoring case) if it's already there. Empty items aren't
+/// added.
+pub fn toggle(list: &mut Vec<String>, item: String) {
+	if let Some(i) = list.iter().position(|x| x.eq_ignore_ascii_case(&item)) {
+		list.remove(i);
+	} else if !item.is_empty() {
+		list.push(item);
+	}
+}
@@ -0,0 +1,368 @@
+//! Sending GQL operations to twitch, used by [`crate::Client`] and the browser.
+//!
+//! Each query is a type implementing [`Operation`], sent with [`execute`] (or several at once with
+//! [`execute_batch`]). Persisted queries are sent as their hash like the webapp does, falling back
+//! to the whole query if twitch doesn't recognise it. Every request goes through
+//! [`crate::fixtures`], so they can be recorded and replayed.
+
+// For the JSON stuff:
+#![allow(non_snake_case)]
+
+use std::io::Read;
+
+use curl::easy::Easy;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use simd_json::{from_slice, to_vec};
+
+use crate::error::{Error, ErrorKind, GqlError};
+use crate::fixtures;
+use crate::log::warn;
+
+/// A GQL query, tying it's variables to the data it returns. Each query is a unit struct named
+/// after the operation, sent with [`execute`].
+pub trait Operation {
+	/// Sent as the request's `variables`
+	type Variables: Serialize;
+	/// The response's `data`
+	type Response: DeserializeOwned;
+
+	/// Hash of the webapp's persisted query. `None` for queries that aren't the webapp's, which are
+	/// always sent whole.
+	const SHA256HASH: Option<&'static str>;
+	/// Name of the GQL operation, shown in errors
+	const OPERATION_NAME: &'static str;
+	/// The whole GQL document, sent if twitch doesn't know the hash anymore
+	const QUERY: &'static str;
+}
+
+#[derive(Serialize)]
+struct PersistedQuery {
+	sha256hash: &'static str,
+}
+
+#[derive(Serialize)]
+struct RequestExtensions {
+	persistedQuery: PersistedQuery,
+}
+
+/// POST data with the query's hash
+#[derive(Serialize)]
+struct TwitchRequest<'a, T: Serialize> {
+	variables: &'a T,
+	extensions: RequestExtensions,
+}
+
+/// POST data with the whole query instead of it's hash
+#[derive(Serialize)]
+struct QueryRequest<'a, T: Serialize> {
+	operationName: &'static str,
+	query: &'static str,
+	variables: &'a T,
+}
+
+/// Response from any API call
+#[derive(Deserialize)]
+struct TwitchResponse<D> {
+	/// Missing if the request failed completely
+	data: Option<D>,
+	#[serde(default)]
+	errors: Vec<GqlError>,
+	// Ignore `extensions`
+}
+
+/// Just the errors from a response, for when the data doesn't parse
+#[derive(Deserialize)]
+struct ResponseErrors {
+	// Missing from responses in a batch that succeeded
+	#[serde(default)]
+	errors: Vec<GqlError>,
+}
+
+/// Send a POST request and return the status and body.
+fn send<J: Serialize + ?Sized>(
+	easy: &mut Easy,
+	operation: &str,
+	json: &J,
+) -> Result<(u32, Vec<u8>), Error> {
+	send_bytes(
+		easy,
+		operation,
+		&to_vec(json).map_err(|e| Error::new(operation, ErrorKind::Json(e)))?,
+	)
+}
+
+/// Send a POST request with an already serialized body and return the status and body.
+fn send_bytes(easy: &mut Easy, operation: &str, data: &[u8]) -> Result<(u32, Vec<u8>), Error> {
+	let (status, body) = fixtures::serve(operation, data, || {
+		// Otherwise it's sent chunked, which not every server accepts
+		easy.post_field_size(data.len() as u64)
+			.map_err(|e| Error::new(operation, ErrorKind::Curl(e)))?;
+
+		let mut data = data;
+		let mut vec = Vec::new();
+
+		// Make sure `transfer` is dropped before we use can `vec` again
+		{
+			let mut transfer = easy.transfer();
+
+			let _ = transfer.read_function(|slice| Ok(data.read(slice).unwrap_or(0)));
+			let _ = transfer.write_function(|slice| {
+				// Copy the packet to the buffer
+				vec.extend_from_slice(slice);
+				Ok(slice.len())
+			});
+
+			transfer
+				.perform()
+				.map_err(|e| Error::new(operation, ErrorKind::Curl(e)))?;
+		}
+
+		Ok((response_code(easy, operation)?, vec))
+	})?;
+
+	check_status(operation, status, body)
+}
+
+/// Status of the last transfer on `easy`
+pub fn response_code(easy: &mut Easy, operation: &str) -> Result<u32, Error> {
+	easy.response_code()
+		.map_err(|e| Error::new(operation, ErrorKind::Curl(e)))
+}
+
+/// Makes sure a response was successful, returning it's status and body.
+pub fn check_status(operation: &str, status: u32, body: Vec<u8>) -> Result<(u32, Vec<u8>), Error> {
+	if (200..300).contains(&status) {
+		Ok((status, body))
+	} else {
+		// The start of the body usually says what went wrong
+		Err(Error::new(
+			operation,
+			ErrorKind::Status(String::from_utf8_lossy(&body[..body.len().min(200)]).into_owned()),
+		)
+		.with_status(status))
+	}
+}
+
+/// Send a GQL query and return the response's `data`, i.e.
+/// `execute::<DirectoryPage_Game>(easy, &DirectoryPage_GameVariables { .. })`.
+///
+/// If twitch doesn't recognise the persisted query's hash, it's sent again with the whole query.
+pub fn execute<O: Operation>(
+	easy: &mut Easy,
+	variables: &O::Variables,
+) -> Result<O::Response, Error> {
+	let full_query = QueryRequest {
+		operationName: O::OPERATION_NAME,
+		query: O::QUERY,
+		variables,
+	};
+
+	// Twitch has never had this one's hash
+	let Some(hash) = O::SHA256HASH else {
+		return send_and_parse(easy, O::OPERATION_NAME, &full_query);
+	};
+
+	match send_and_parse(easy, O::OPERATION_NAME, &hash_request(variables, hash)) {
+		Err(e) if is_stale_hash(&e) => {
+			warn(
+				[
+					"Persisted query hash for ",
+					O::OPERATION_NAME,
+					" is stale (",
+					hash,
+					"), sending the whole query",
+				]
+				.concat(),
+			);
+
+			send_and_parse(easy, O::OPERATION_NAME, &full_query)
+		}
+		result => result,
+	}
+}
+
+/// POST data with a persisted query's hash
+fn hash_request<'a, T: Serialize>(variables: &'a T, hash: &'static str) -> TwitchRequest<'a, T> {
+	TwitchRequest {
+		variables,
+		extensions: RequestExtensions {
+			persistedQuery: PersistedQuery { sha256hash: hash },
+		},
+	}
+}
+
+/// Did this request fail because twitch doesn't know the persisted query's hash?
+fn is_stale_hash(error: &Error) -> bool {
+	matches!(&error.kind, ErrorKind::Gql(errors) if errors
+		.iter()
+		.any(|error| error.message == "PersistedQueryNotFound"))
+}
+
+/// Send a GQL request and parse the response.
+fn send_and_parse<J: Serialize + ?Sized, D: DeserializeOwned>(
+	easy: &mut Easy,
+	operation: &str,
+	json: &J,
+) -> Result<D, Error> {
+	let (status, mut body) = send(easy, operation, json)?;
+
+	parse_response(operation, &mut body).map_err(|e| e.with_status(status))
+}
+
+/// Gets the data from a response, or it's errors.
+fn into_data<D>(response: TwitchResponse<D>, operation: &str) -> Result<D, Error> {
+	match response {
+		TwitchResponse {
+			data: Some(data), ..
+		} => Ok(data),
+		TwitchResponse { data: None, errors } => Err(Error::new(
+			operation,
+			if errors.is_empty() {
+				ErrorKind::Other("Response has no data".to_owned())
+			} else {
+				ErrorKind::Gql(errors)
+			},
+		)),
+	}
+}
+
+/// Parses a GQL response, returning it's data or it's errors.
+pub fn parse_response<D: DeserializeOwned>(operation: &str, body: &mut [u8]) -> Result<D, Error> {
+	// Parsing changes the buffer, so keep a copy in case we need to look for errors
+	let mut copy = body.to_vec();
+
+	match from_slice::<TwitchResponse<D>>(body) {
+		Ok(response) => into_data(response, operation),
+		// If the server returned errors, the data probably isn't the right shape
+		Err(e) => Err(Error::new(
+			operation,
+			match from_slice::<ResponseErrors>(&mut copy) {
+				Ok(ResponseErrors { errors }) if !errors.is_empty() => ErrorKind::Gql(errors),
+				_ => ErrorKind::Json(e),
+			},
+		)),
+	}
+}
+
+/// A list of GQL queries sent in one POST, implemented for tuples of up to 4 [`Operation`]s. See
+/// [`execute_batch`].
+pub trait Batch {
+	/// Tuple of the queries' variables
+	type Variables;
+	/// Tuple of the data they return
+	type Response;
+
+	/// Names of the queries' operations, in order
+	fn operation_names() -> Vec<&'static str>;
+
+	/// The JSON array to send, with whole queries instead of hashes if `full_queries` is set
+	fn to_json(
+		variables: &Self::Variables,
+		full_queries: bool,
+	) -> Result<Vec<u8>, simd_json::Error>;
+
+	/// Parses the array of responses. `operations` are the queries' names, for errors.
+	fn parse(body: &mut [u8], operations: &[&'static str]) -> Result<Self::Response, Error>;
+}
+
+/// Serializes one query in a batch.
+fn batch_item<O: Operation>(
+	variables: &O::Variables,
+	full_query: bool,
+) -> Result<Vec<u8>, simd_json::Error> {
+	match O::SHA256HASH {
+		Some(hash) if !full_query => to_vec(&hash_request(variables, hash)),
+		_ => to_vec(&QueryRequest {
+			operationName: O::OPERATION_NAME,
+			query: O::QUERY,
+			variables,
+		}),
+	}
+}
+
+/// Finds the first query in a batch that returned errors, for when the data doesn't parse.
+fn batch_errors(body: &mut [u8], operations: &[&'static str]) -> Option<Error> {
+	from_slice::<Vec<ResponseErrors>>(body)
+		.ok()?
+		.into_iter()
+		.zip(operations)
+		.find(|(response, _)| !response.errors.is_empty())
+		.map(|(response, operation)| Error::new(*operation, ErrorKind::Gql(response.errors)))
+}
+
+/// Implements [`Batch`] for tuples of the given size.
+macro_rules! impl_batch {
+	($($O:ident $i:tt),+) => {
+		impl<$($O: Operation),+> Batch for ($($O,)+) {
+			type Variables = ($($O::Variables,)+);
+			type Response = ($($O::Response,)+);
+
+			fn operation_names() -> Vec<&'static str> {
+				vec![$($O::OPERATION_NAME),+]
+			}
+
+			fn to_json(
+				variables: &Self::Variables,
+				full_queries: bool,
+			) -> Result<Vec<u8>, simd_json::Error> {
+				let items = [$(batch_item::<$O>(&variables.$i, full_queries)?),+];
+
+				Ok([&b"["[..], &items.join(&b","[..]), b"]"].concat())
+			}
+
+			fn parse(body: &mut [u8], operations: &[&'static str]) -> Result<Self::Response, Error> {
+				// Parsing changes the buffer, so keep a copy in case we need to look for errors
+				let mut copy = body.to_vec();
+
+				match from_slice::<($(TwitchResponse<$O::Response>,)+)>(body) {
+					Ok(responses) => Ok(($(into_data(responses.$i, operations[$i])?,)+)),
+					Err(e) => Err(batch_errors(&mut copy, operations).unwrap_or_else(|| {
+						Error::new(operations.join(", "), ErrorKind::Json(e))
+					})),
+				}
+			}
+		}
+	};
+}
+
+impl_batch!(O0 0, O1 1);
+impl_batch!(O0 0, O1 1, O2 2);
+impl_batch!(O0 0, O1 1, O2 2, O3 3);
+
+/// Send several GQL queries in one POST, returning each one's data in order.
+///
+/// `B` is a tuple of the queries and `variables` a tuple of their variables, i.e.
+/// `let (game, search) = execute_batch::<(DirectoryPage_Game,
+/// SearchResultsPage_SearchResults)>(easy, &(game, search))?;`. If any of the hashes are stale, the
+/// whole batch is sent again with full queries.
+pub fn execute_batch<B: Batch>(
+	easy: &mut Easy,
+	variables: &B::Variables,
+) -> Result<B::Response, Error> {
+	let operations = B::operation_names();
+
+	let send_batch = |easy: &mut Easy, full_queries: bool| {
+		let json = B::to_json(variables, full_queries)
+			.map_err(|e| Error::new(operations.join(", "), ErrorKind::Json(e)))?;
+
+		let (status, mut body) = send_bytes(easy, &operations.join(", "), &json)?;
+
+		B::parse(&mut body, &operations).map_err(|e| e.with_status(status))
+	};
+	match send_batch(easy, false) {
+		Err(e) if is_stale_hash(&e) => {
+			warn(
+				[
+					"Persisted query hash for ",
+					&e.operation,
+					" is stale, sending the whole batch's queries",
+				]
+				.concat(),
+			);
+
+			send_batch(easy, true)
+		}
+		result => result,
+	}
+}
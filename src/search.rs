@@ -0,0 +1,338 @@
+//! The search box, with the search history, bookmarks and twitch's suggestions under it.
+//!
+//! Searches are saved to `$XDG_DATA_HOME/twitch-tui/history.json` (or
+//! `~/.local/share/twitch-tui/history.json`), newest last, keeping at most `search_history` of
+//! them. While typing, past searches containing the text are listed first, then
+//! [`Bookmarks`](crate::bookmarks::Bookmarks), then twitch's suggestions, which are requested once
+//! typing pauses. Bookmarks and channel and category suggestions open them straight away instead
+//! of searching.
+
+use std::time::{Duration, Instant};
+
+use crossterm::event::{poll, read, Event, KeyCode, KeyEvent};
+use ratatui::backend::Backend;
+use ratatui::layout::Rect;
+use ratatui::text::{Span, Spans};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Terminal;
+use twitch_curses::SearchSuggestion;
+
+use crate::bookmarks::{Bookmark, Bookmarks};
+use crate::config::config;
+use crate::error::Error;
+use crate::log::warn;
+use crate::theme::theme;
+use crate::utils::{client, load_data, save_data};
+use crate::worker::Worker;
+
+/// Where the history is saved, in [`crate::utils::data_dir`]
+const HISTORY_FILE: &str = "history.json";
+
+/// How long typing has to pause for before suggestions are requested
+const DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// Most items to show under the box
+const MAX_SUGGESTIONS: usize = 10;
+
+/// Something listed under the search box
+#[derive(Debug, Clone, PartialEq)]
+pub enum Suggestion {
+	/// A past search
+	History(String),
+	Bookmark(Bookmark),
+	/// Text twitch thinks is being searched for
+	Text(String),
+	Channel {
+		login: String,
+		live: bool,
+	},
+	Game {
+		name: String,
+		display_name: String,
+	},
+}
+impl Suggestion {
+	/// What to put in the box if this is picked with tab
+	fn text(&self) -> &str {
+		match self {
+			Self::History(text) | Self::Text(text) => text,
+			Self::Bookmark(bookmark) => bookmark.name(),
+			Self::Channel { login, .. } => login,
+			Self::Game { display_name, .. } => display_name,
+		}
+	}
+
+	fn item(&self) -> ListItem<'_> {
+		ListItem::new(match self {
+			Self::History(text) => Spans::from(vec![
+				Span::styled("↺ ", theme().help),
+				Span::raw(text.as_str()),
+			]),
+			Self::Bookmark(bookmark) => Spans::from(vec![
+				Span::styled("★ ", theme().help),
+				Span::raw(bookmark.name()),
+			]),
+			Self::Text(text) => Spans::from(vec![
+				Span::styled("? ", theme().help),
+				Span::raw(text.as_str()),
+			]),
+			Self::Channel { login, live } => Spans::from(vec![
+				Span::styled("@ ", theme().help),
+				Span::raw(login.as_str()),
+				Span::styled(if *live { " (live)" } else { "" }, theme().help),
+			]),
+			Self::Game { display_name, .. } => Spans::from(vec![
+				Span::styled("# ", theme().help),
+				Span::raw(display_name.as_str()),
+			]),
+		})
+	}
+}
+impl From<SearchSuggestion> for Suggestion {
+	fn from(suggestion: SearchSuggestion) -> Self {
+		match suggestion {
+			SearchSuggestion::Text(text) => Self::Text(text),
+			SearchSuggestion::Channel { login, live } => Self::Channel { login, live },
+			SearchSuggestion::Category(category) => Self::Game {
+				name: category.name,
+				display_name: category.display_name,
+			},
+		}
+	}
+}
+
+/// What the search box was closed with
+pub enum Submitted {
+	/// Text to search for, which may start with a prefix like `channel:`
+	Search(String),
+	/// A channel's login
+	Channel(String),
+	/// A category's name
+	Game(String),
+}
+
+/// Past searches, oldest first
+#[derive(Default)]
+pub struct History(Vec<String>);
+impl History {
+	/// Reads the saved history. It's empty if there isn't one, or if it can't be read (the reason
+	/// is in the log).
+	pub fn load() -> Self {
+		Self(load_data(HISTORY_FILE, "search history").unwrap_or_default())
+	}
+
+	/// Adds a search, moving it to the end if it's already there, and saves the history.
+	pub fn add(&mut self, search: &str) {
+		let limit = config().search_history;
+		if limit == 0 || search.is_empty() {
+			return;
+		}
+
+		self.0.retain(|x| x != search);
+		self.0.push(search.to_owned());
+		if self.0.len() > limit {
+			self.0.drain(..self.0.len() - limit);
+		}
+
+		if let Err(e) = save_data(HISTORY_FILE, "search history", &self.0) {
+			warn(["Couldn't save search history: ", &e.to_string()].concat());
+		}
+	}
+
+	/// Past searches containing `text` (ignoring case), newest first
+	fn matching<'a>(&'a self, text: &'a str) -> impl Iterator<Item = &'a String> {
+		let text = text.to_lowercase();
+
+		self.0
+			.iter()
+			.rev()
+			.filter(move |search| search.to_lowercase().contains(&text))
+	}
+}
+
+/// Shows the search box over the page until enter or esc is pressed. Up and down pick one of the
+/// items under it, and tab copies the picked one into the box. `None` if it was cancelled or
+/// nothing was typed.
+pub fn search_box<B: Backend>(
+	terminal: &mut Terminal<B>,
+	history: &History,
+	bookmarks: &Bookmarks,
+) -> Option<Submitted> {
+	const TITLE: &str = "Search (start with channel:, tag:, game: or vod: to narrow it)";
+
+	// Show cursor
+	let _ = terminal.show_cursor();
+
+	let mut worker = Worker::<Result<Vec<Suggestion>, Error>>::new(client());
+	let mut text = String::new();
+	// From twitch, for `requested`
+	let mut suggestions: Vec<Suggestion> = Vec::new();
+	let mut requested = String::new();
+	let mut last_edit: Option<Instant> = None;
+	let mut list_state = ListState::default();
+
+	let submitted = loop {
+		// History first, then bookmarks, then anything new from twitch
+		let mut items: Vec<Suggestion> = history
+			.matching(&text)
+			.map(|search| Suggestion::History(search.clone()))
+			.chain(bookmarks.matching(&text).cloned().map(Suggestion::Bookmark))
+			.collect();
+		for suggestion in &suggestions {
+			if !items.iter().any(|item| item.text() == suggestion.text()) {
+				items.push(suggestion.clone());
+			}
+		}
+		items.truncate(MAX_SUGGESTIONS);
+
+		if list_state.selected().is_some_and(|i| i >= items.len()) {
+			list_state.select(None);
+		}
+
+		// Columns the text takes up, not it's length in bytes
+		let text_width = Span::raw(text.as_str()).width() as u16;
+
+		let _ = terminal.draw(|frame| {
+			// Width of the input box, wide enough for the title
+			let width = (text_width.max(TITLE.len() as u16 + 1) + 3).clamp(20, frame.size().width);
+			let area = Rect {
+				x: (frame.size().width - width) / 2,
+				y: frame.size().height / 2 - 1,
+				width,
+				height: 3,
+			};
+
+			frame.render_widget(Clear, area);
+			frame.render_widget(
+				Paragraph::new(text.clone()).block(
+					Block::default()
+						.borders(Borders::ALL)
+						.title(Span::styled(TITLE, theme().title))
+						.title_alignment(config().title_alignment)
+						.border_type(config().border_type)
+						.border_style(theme().border),
+				),
+				area,
+			);
+
+			if !items.is_empty() {
+				let list_area = Rect {
+					y: area.y + area.height,
+					height: (items.len() as u16 + 1)
+						.min(frame.size().height.saturating_sub(area.y + area.height)),
+					..area
+				};
+
+				frame.render_widget(Clear, list_area);
+				frame.render_stateful_widget(
+					List::new(items.iter().map(Suggestion::item).collect::<Vec<_>>())
+						.block(
+							Block::default()
+								.borders(Borders::LEFT | Borders::RIGHT | Borders::BOTTOM)
+								.border_type(config().border_type)
+								.border_style(theme().border),
+						)
+						.highlight_style(theme().highlight),
+					list_area,
+					&mut list_state,
+				);
+			}
+
+			// Kept in the box if the text is wider than the screen
+			frame.set_cursor(
+				(area.x + 1 + text_width).min(area.x + area.width - 2),
+				area.y + 1,
+			);
+		});
+
+		// Ask twitch once typing pauses
+		if config().search_suggestions
+			&& last_edit.is_some_and(|edit| edit.elapsed() >= DEBOUNCE)
+			&& text != requested
+		{
+			last_edit = None;
+			requested = text.clone();
+
+			if text.is_empty() {
+				suggestions.clear();
+				worker.cancel();
+			} else {
+				let fragment = text.clone();
+				worker.start(["Suggestions for ", &text].concat(), move |client, _| {
+					client
+						.search_suggestions(&fragment)
+						.map(|suggestions| suggestions.into_iter().map(Suggestion::from).collect())
+				});
+			}
+		}
+
+		match worker.poll() {
+			Some(Ok(new)) => suggestions = new,
+			// Suggestions are optional, so this doesn't need a popup
+			Some(Err(e)) => warn(["Couldn't get search suggestions: ", &e.to_string()].concat()),
+			None => (),
+		}
+
+		// Keep checking for suggestions while waiting for keys
+		if !poll(Duration::from_millis(50)).expect("IO error") {
+			continue;
+		}
+
+		if let Event::Key(KeyEvent { code, .. }) = read().expect("Should be able to read input") {
+			match code {
+				KeyCode::Char(c) => {
+					text.push(c);
+					last_edit = Some(Instant::now());
+					list_state.select(None);
+				}
+				KeyCode::Backspace => {
+					text.pop();
+					last_edit = Some(Instant::now());
+					list_state.select(None);
+				}
+				KeyCode::Up => list_state.select(match list_state.selected() {
+					None | Some(0) => items.len().checked_sub(1),
+					Some(i) => Some(i - 1),
+				}),
+				KeyCode::Down => list_state.select(match list_state.selected() {
+					Some(i) if i + 1 < items.len() => Some(i + 1),
+					_ if items.is_empty() => None,
+					_ => Some(0),
+				}),
+				KeyCode::Tab => {
+					if let Some(item) = list_state.selected().map(|i| &items[i]) {
+						text = item.text().to_owned();
+						last_edit = Some(Instant::now());
+						list_state.select(None);
+					}
+				}
+				KeyCode::Enter => {
+					break match list_state.selected().map(|i| items.swap_remove(i)) {
+						Some(
+							Suggestion::Channel { login, .. }
+							| Suggestion::Bookmark(Bookmark::Channel(login)),
+						) => Some(Submitted::Channel(login)),
+						Some(
+							Suggestion::Game { name, .. }
+							| Suggestion::Bookmark(Bookmark::Game(name)),
+						) => Some(Submitted::Game(name)),
+						Some(Suggestion::History(text) | Suggestion::Text(text)) => {
+							Some(Submitted::Search(text))
+						}
+						None if text.is_empty() => None,
+						None => Some(Submitted::Search(text)),
+					};
+				}
+				KeyCode::Esc => break None,
+				_ => (),
+			}
+		}
+	};
+
+	let _ = terminal.clear();
+
+	// Hide the cursor again
+	let _ = terminal.hide_cursor();
+
+	submitted
+}
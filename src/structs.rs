@@ -1,33 +1,28 @@
-//! Structures used for JSON {,de}serialization in twitch HTTPS requests. These also provide some
-//! convenience methods such as [`TwitchResponse::to_widgets`] and [`Node::select`].
-
-// For the JSON stuff:
-#![allow(non_snake_case)]
-#![allow(non_camel_case_types)]
-// Because we don't actually construct most of these structs
-#![allow(dead_code)]
+//! Turns what [`twitch_curses::Client`] returns into ratatui widgets with [`Data::into_widgets`],
+//! and what selecting them does with [`Node::resolve`].
 
 use std::borrow::Cow;
-use std::io::stdout;
+use std::cmp::Reverse;
 use std::process::Command;
 use std::str::from_utf8;
 
 use chrono::{DateTime, Utc};
-use crossterm::execute;
-use crossterm::terminal::{
-	disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
-};
-use curl::easy::Easy;
 use ratatui::backend::Backend;
 use ratatui::style::{Modifier, Style};
 use ratatui::text::{Span, Spans, Text};
 use ratatui::widgets::{List, ListItem, Paragraph, Wrap};
 use ratatui::Terminal;
-use serde::{Deserialize, Serialize};
-use simd_json::from_slice;
+use serde::Deserialize;
+use twitch_curses::{
+	Category, Channel, ChannelOverview, Client, Clip, ClipSource, Page, SearchIndex, SearchResults,
+	SearchSection, Shelf, ShelfItem, Stream, Video, VideoType,
+};
 
 use crate::config::*;
+use crate::error::{Error, ErrorKind};
+use crate::theme::theme;
 use crate::utils::*;
+use crate::worker::Progress;
 
 /// Takes text and makes it take an extra line
 fn spaced<'a, T: Into<Spans<'a>>>(text: T) -> Text<'a> {
@@ -36,484 +31,506 @@ fn spaced<'a, T: Into<Spans<'a>>>(text: T) -> Text<'a> {
 	}
 }
 
-// Takes a string and returns it as a [`Span`] with an underline.
+// Takes a string and returns it as a [`Span`] with the theme's header style (an underline by
+// default).
 fn header<'a, T: Into<Cow<'a, str>>>(content: T) -> Span<'a> {
 	Span {
 		content: content.into(),
-		style: Style {
-			add_modifier: Modifier::UNDERLINED,
-			..Style::default()
-		},
+		style: theme().header,
 	}
 }
 
 /// Formats a date according to config.
-fn format_date(string: &str) -> String {
-	if let Ok(dt) = string.parse::<DateTime<Utc>>() {
-		if let Some(fmt) = DATE_FORMAT {
-			// Use user's format
-			dt.format(fmt).to_string()
+fn format_date(date: &DateTime<Utc>) -> String {
+	if let Some(fmt) = &config().date_format {
+		// Use user's format
+		date.format(fmt).to_string()
+	} else {
+		// Relative time
+		let delta = Utc::now().signed_duration_since(*date).num_seconds();
+
+		if delta < 0 {
+			// It's in the future
+			["In ", &format_seconds(delta.abs())].concat()
 		} else {
-			// Relative time
-			let delta = Utc::now().signed_duration_since(dt).num_seconds();
-
-			if delta < 0 {
-				// It's in the future
-				["In ", &format_seconds(delta.abs())].concat()
-			} else {
-				// It's in the past
-				[&format_seconds(delta.abs()), " ago"].concat()
-			}
+			// It's in the past
+			[&format_seconds(delta.abs()), " ago"].concat()
 		}
-	} else {
-		// If the date was invalid, it was probably the "Music" category (200,000 years ago was
-		// about when music was invented).
-		"200,000 years ago".to_owned()
 	}
 }
 
-/// A title, no additional info
-#[derive(Debug)]
-pub enum Title {
-	/// [`Vec`] of [`TitleTokenEdge`]s,
-	Tokens(Vec<TitleTokenEdge>),
-	/// Just one string
-	Fallback(String),
-}
-
-// Request JSON
-
-// PersonalSection
-
-#[derive(Serialize)]
-pub struct RecommendationContext {
-	pub platform: Option<&'static str>,
-	pub clientApp: Option<&'static str>,
-	pub location: Option<&'static str>,
-	pub referrerDomain: Option<&'static str>,
-	pub viewportHeight: Option<u16>,
-	pub viewportWidth: Option<u16>,
-	pub channelName: Option<&'static str>,
-	pub categoryName: Option<&'static str>,
-	pub lastChannelName: Option<&'static str>,
-	pub lastCategoryName: Option<&'static str>,
-	pub pageviewContent: Option<&'static str>,
-	pub pageviewContentType: Option<&'static str>,
-	pub pageviewLocation: Option<&'static str>,
-	pub pageviewMedium: Option<&'static str>,
-	pub previousPageviewContent: Option<&'static str>,
-	pub previousPageviewContentType: Option<&'static str>,
-	pub previousPageviewLocation: Option<&'static str>,
-	pub previousPageviewMedium: Option<&'static str>,
-}
-
-#[derive(Serialize)]
-pub struct PersonalSectionsInput {
-	pub sectionInputs: Vec<&'static str>,
-	// It's optional for RECOMMENDED_SECTION, but required for SIMILAR_SECTION
-	pub recommendationContext: RecommendationContext,
-	pub contextChannelName: Option<&'static str>,
-}
-
-pub trait Variables {
-	const SHA256HASH: &'static str;
+/// "Yes" or "No"
+fn yes_no(value: bool) -> &'static str {
+	if value {
+		"Yes"
+	} else {
+		"No"
+	}
 }
 
-#[derive(Serialize)]
-pub struct PersonalSectionsVariables {
-	pub input: PersonalSectionsInput,
-	pub creatorAnniversariesExperimentEnabled: bool,
-}
-impl Variables for PersonalSectionsVariables {
-	const SHA256HASH: &'static str =
-		"f8cc9b91bb629f2d09dd8299d9f07c4daefe019236a19fc12fa2b14eb95c359e";
+/// Get a [`ratatui::Style`] with the channel's colour as forground.
+fn channel_style(channel: &Channel) -> Style {
+	Style {
+		fg: channel
+			.colour
+			.as_ref()
+			.and_then(|colour| parse_colour(colour).ok()),
+		..Style::default()
+	}
 }
 
-// Shelves
-
-#[derive(Serialize)]
-pub struct ShelvesContext {
-	pub clientApp: Option<&'static str>,
-	pub location: Option<&'static str>,
-	pub referrerDomain: Option<&'static str>,
-	pub viewportHeight: Option<u16>,
-	pub viewportWidth: Option<u16>,
-}
+/// Info shown for a stream
+fn stream_lines<'a>(stream: &Stream) -> Vec<Spans<'a>> {
+	let mut lines = vec![
+		stream.title.clone().into(),
+		"".into(),
+		stream.channel.display_name.clone().into(),
+		["Viewers: ", &stream.viewers.to_string()].concat().into(),
+	];
 
-#[derive(Serialize)]
-pub struct ShelvesVariables {
-	// `u64` just in case
-	pub imageWidth: Option<u64>,
-	pub itemsPerRow: u16,
-	pub langWeightedCCU: Option<bool>,
-	pub platform: &'static str,
-	pub requestID: &'static str,
-	pub context: Option<ShelvesContext>,
-	pub verbose: Option<bool>,
-}
-impl Variables for ShelvesVariables {
-	const SHA256HASH: &'static str =
-		"41858598cc637cf9e6153818f5a4d274a08e8743e4a85903cdfe39c464152404";
-}
+	if let Some(category) = &stream.category {
+		lines.push(["Game: ", &category.display_name].concat().into());
+	}
 
-#[derive(Serialize, Default)]
-struct VideoAccessToken_ClipVariables {
-	slug: String,
-}
-impl Variables for VideoAccessToken_ClipVariables {
-	const SHA256HASH: &'static str =
-		"36b89d2507fce29e5ca551df756d27c1cfe079e2609642b4390aa4c35796eb11";
-}
+	if let Some(started_at) = &stream.started_at {
+		lines.push(["Started: ", &format_date(started_at)].concat().into());
+	}
 
-#[derive(Serialize)]
-pub struct DirectoryPage_GameOptions {
-	pub sort: &'static str,
-	pub recommendationsContext: Option<RecommendationContext>,
-	pub requestID: Option<&'static str>,
-	pub freeformTags: Option<Vec<&'static str>>,
-	pub tags: Option<Vec<&'static str>>,
-}
+	if !stream.tags.is_empty() {
+		lines.push(["Tags: ", &stream.tags.join(", ")].concat().into());
+	}
 
-#[derive(Serialize)]
-pub struct DirectoryPage_GameVariables {
-	pub imageWidth: Option<u64>,
-	/// The category name
-	pub name: String,
-	pub options: DirectoryPage_GameOptions,
-	pub sortTypeIsRecency: bool,
-	pub limit: u32,
-}
-impl Variables for DirectoryPage_GameVariables {
-	const SHA256HASH: &'static str =
-		"df4bb6cc45055237bfaf3ead608bbafb79815c7100b6ee126719fac3762ddf8b";
+	lines
 }
 
-#[derive(Serialize)]
-pub struct Target {
-	pub index: &'static str,
-}
+/// Info shown for a category: viewers, tags and release date, whichever we have
+fn category_lines<'a>(category: &Category) -> Vec<Spans<'a>> {
+	let mut lines = vec![category.display_name.clone().into(), "".into()];
 
-#[derive(Serialize)]
-pub struct SearchResultsPage_SearchResultsOptions {
-	pub targets: Option<Vec<Target>>,
-}
+	if let Some(viewers) = category.viewers {
+		lines.push(["Viewers: ", &viewers.to_string()].concat().into());
+	}
 
-#[derive(Serialize)]
-pub struct SearchResultsVariables {
-	/// The search
-	pub query: String,
-	pub options: Option<SearchResultsPage_SearchResultsOptions>,
-	pub requestID: Option<String>,
-}
-impl Variables for SearchResultsVariables {
-	const SHA256HASH: &'static str =
-		"6ea6e6f66006485e41dbe3ebd69d5674c5b22896ce7b595d7fce6411a3790138";
-}
+	if !category.tags.is_empty() {
+		lines.push(["Tags: ", &category.tags.join(", ")].concat().into());
+	}
 
-#[derive(Serialize)]
-pub struct PlaybackAccessTokenVariables {
-	/// Should always be `false`
-	pub isLive: bool,
-	/// Must be `true`
-	pub isVod: bool,
-	pub login: &'static str,
-	pub playerType: &'static str,
-	/// Set at runtime
-	pub vodID: String,
-}
-impl Variables for PlaybackAccessTokenVariables {
-	const SHA256HASH: &'static str =
-		"0828119ded1c13477966434e15800ff57ddacf13ba1911c129dc2200705b0712";
-}
+	if let Some(released) = &category.released {
+		lines.push(["Released: ", &format_date(released)].concat().into());
+	}
 
-#[derive(Serialize)]
-pub struct PersistedQuery {
-	pub sha256hash: &'static str,
+	lines
 }
 
-#[derive(Serialize)]
-pub struct RequestExtensions {
-	pub persistedQuery: PersistedQuery,
-}
+/// Info about a clip, shown when it's selected
+fn clip_lines<'a>(clip: &Clip) -> Vec<Spans<'a>> {
+	let mut lines = vec![clip.title.clone().unwrap_or_default().into(), "".into()];
 
-/// POST data for API calls.
-#[derive(Serialize)]
-pub struct TwitchRequest<T: Serialize> {
-	pub variables: T,
-	pub extensions: RequestExtensions,
-}
-impl<T: Serialize + Variables + Default> Default for TwitchRequest<T> {
-	fn default() -> Self {
-		Self {
-			variables: T::default(),
-			extensions: RequestExtensions {
-				persistedQuery: PersistedQuery {
-					sha256hash: T::SHA256HASH,
-				},
-			},
-		}
+	if let Some(views) = clip.views {
+		lines.push(["Views: ", &views.to_string()].concat().into());
 	}
-}
 
-/// Page loaded on start
-pub enum HomePage {
-	/// The bit on the left on the webapp
-	PersonalSection,
-	/// The main home page
-	Shelves,
-	/// A category
-	Game(&'static str),
-	/// A search
-	Search(&'static str),
-}
+	lines.extend([
+		[
+			"Curator: ",
+			clip.curator
+				.as_ref()
+				.map_or("deleted account", |curator| &curator.display_name),
+		]
+		.concat()
+		.into(),
+		[
+			"Game: ",
+			clip.category
+				.as_ref()
+				.map_or("none", |category| &category.display_name),
+		]
+		.concat()
+		.into(),
+	]);
+
+	if let Some(channel) = &clip.channel {
+		lines.push(["Broadcaster: ", &channel.display_name].concat().into());
+	}
 
-// Response JSON
+	if let Some(created_at) = &clip.created_at {
+		lines.push(["Clip created: ", &format_date(created_at)].concat().into());
+	}
 
-// Personalsection
+	lines.push(
+		["Duration: ", &clip.duration_seconds.to_string(), "s"]
+			.concat()
+			.into(),
+	);
 
-#[derive(Deserialize, Debug)]
-struct PersonalSectionTitle {
-	localizedFallback: String,
-	// Ignore `localizedTokens` and `__typename`
-}
+	if let Some(language) = &clip.language {
+		lines.push(["Language: ", language].concat().into());
+	}
 
-#[derive(Deserialize, Debug)]
-struct BroadcastSettings {
-	title: String, // Ignore `id` and `__typename`
+	lines
 }
 
-#[derive(Deserialize, Debug)]
-struct UserRoles {
-	isPartner: bool, // Ignore `__typename`
-}
+/// Info about a past broadcast, highlight or upload
+fn video_lines<'a>(video: &Video) -> Vec<Spans<'a>> {
+	let mut lines = vec![
+		video
+			.title
+			.clone()
+			.unwrap_or_else(|| "Untitled".to_owned())
+			.into(),
+		"".into(),
+	];
 
-#[derive(Deserialize, Debug)]
-pub struct User {
-	id: String,
-	login: String,
-	displayName: String,
-	primaryColorHex: Option<String>,
-	broadcastSettings: Option<BroadcastSettings>,
-	roles: Option<UserRoles>,
-	// Ignore `id`, `profileImageURL`, `largeProfileImageURL` and `__typename`
-}
-impl User {
-	/// Get a [`ratatui::Style`] with the user's colour as forground.
-	fn style(&self) -> Style {
-		Style {
-			fg: self
-				.primaryColorHex
-				.as_ref()
-				.map(|primary_colour_hex| parse_colour(primary_colour_hex)),
-			..Style::default()
-		}
+	if let Some(channel) = &video.channel {
+		lines.push(["Channel: ", &channel.display_name].concat().into());
 	}
 
-	/// Get a `Node::Stream` object from a `User`.
-	fn as_node(&self) -> Node {
-		// Doesn't need most of this information, just `broadcaster.login`
-		Node::Stream {
-			broadcaster: User {
-				id: self.id.clone(),
-				login: self.login.clone(),
-				displayName: String::new(),
-				primaryColorHex: None,
-				broadcastSettings: Some(BroadcastSettings {
-					title: String::new(),
-				}),
-				roles: None,
-			},
-			game: None,
-			freeformTags: Vec::new(),
-			viewersCount: 0,
-			createdAt: None,
-		}
+	if let Some(category) = &video.category {
+		lines.push(["Game: ", &category.display_name].concat().into());
 	}
-}
 
-#[derive(Deserialize, Debug)]
-struct Tag {
-	localizedName: String, // Ignore `id`, `isLanguageTag`, `tagName`, `__typename`
-}
+	lines.push(
+		["Length: ", &format_length(video.length_seconds)]
+			.concat()
+			.into(),
+	);
 
-#[derive(Deserialize, Debug)]
-pub struct Game {
-	viewersCount: Option<u32>,
-	name: String,
-	displayName: Option<String>,
-	#[serde(alias = "tags")]
-	gameTags: Option<Vec<Tag>>,
-	originalReleaseDate: Option<String>, // Ignore `id`, `boxArtURL and `__typename`
-}
+	if let Some(views) = video.views {
+		lines.push(["Views: ", &views.to_string()].concat().into());
+	}
 
-#[derive(Deserialize, Debug)]
-struct PersonalSectionContent {
-	viewersCount: u32,
-	game: Game,
-	// Ignore `id`, `previewImageURL`, `broadcaster`, `type` and `__typename`
-}
+	if let Some(created_at) = &video.created_at {
+		lines.push(["Created: ", &format_date(created_at)].concat().into());
+	}
 
-#[derive(Deserialize, Debug)]
-pub struct PersonalSectionChannel {
-	user: User,
-	content: PersonalSectionContent, /* Ignore `trackingID`, `promotionsCampaignID`, `label` and
-	                                  * `__typename` */
-}
+	lines
+}
+
+/// Adds a stream's item and info to the given `Vec`s
+fn add_stream<'a>(
+	stream: &Stream,
+	titles: &mut Vec<ListItem<'a>>,
+	info: &mut Vec<(Paragraph<'a>, Node)>,
+) {
+	let style = channel_style(&stream.channel);
+
+	titles.push(ListItem::new(spaced(stream.channel.display_name.clone())).style(style));
+	info.push((
+		Paragraph::new(stream_lines(stream))
+			.style(style)
+			.wrap(Wrap { trim: false }),
+		Node::stream(&stream.channel),
+	));
+}
+
+/// Adds a video's item and info to the given `Vec`s
+fn add_video<'a>(
+	video: Video,
+	titles: &mut Vec<ListItem<'a>>,
+	info: &mut Vec<(Paragraph<'a>, Node)>,
+) {
+	titles.push(ListItem::new(spaced(
+		video.title.clone().unwrap_or_else(|| "Untitled".to_owned()),
+	)));
+	info.push((
+		Paragraph::new(video_lines(&video)).wrap(Wrap { trim: false }),
+		Node::Video(video.id),
+	));
+}
+
+/// Adds a clip's item and info to the given `Vec`s
+fn add_clip<'a>(clip: Clip, titles: &mut Vec<ListItem<'a>>, info: &mut Vec<(Paragraph<'a>, Node)>) {
+	titles.push(ListItem::new(spaced(
+		clip.title.clone().unwrap_or_default(),
+	)));
+	info.push((
+		Paragraph::new(clip_lines(&clip)).wrap(Wrap { trim: false }),
+		Node::Clip(Box::new(clip)),
+	));
+}
+
+/// Adds a category's item and info to the given `Vec`s
+fn add_category<'a>(
+	category: Category,
+	titles: &mut Vec<ListItem<'a>>,
+	info: &mut Vec<(Paragraph<'a>, Node)>,
+) {
+	titles.push(ListItem::new(spaced(category.display_name.clone())));
+	info.push((
+		Paragraph::new(category_lines(&category)).wrap(Wrap { trim: false }),
+		Node::Game(category.name),
+	));
+}
+
+/// Adds a channel from a search to the given `Vec`s, with their top clip after it
+fn add_search_channel<'a>(
+	channel: Channel,
+	titles: &mut Vec<ListItem<'a>>,
+	info: &mut Vec<(Paragraph<'a>, Node)>,
+) {
+	let style = channel_style(&channel);
 
-#[derive(Deserialize, Debug)]
-struct PersonalSection {
-	title: PersonalSectionTitle,
-	items: Vec<PersonalSectionChannel>, // Ignore `type` and `__typename`
-}
+	titles.push(ListItem::new(spaced(channel.display_name.clone())).style(style));
 
-// Shelves
+	let mut lines = vec![channel.title.clone().unwrap_or_default().into(), "".into()];
 
-#[derive(Deserialize, Debug)]
-struct BrowsableCollectionTitle {
-	fallbackLocalizedTitle: String, // Ignore `__typename`
-}
+	if let Some(followers) = channel.followers {
+		lines.push(["Followers: ", &followers.to_string()].concat().into());
+	}
 
-#[derive(Deserialize, Debug)]
-#[serde(untagged)]
-enum TextToken {
-	BrowsableCollection {
-		collectionName: BrowsableCollectionTitle,
-		// Ignore `id`, `slug` and `__typename`
-	},
-	Game(Game),
-	TextToken {
-		text: String,
-		hasEmphasis: bool,
-		// Ignore `location` and `__typename`
-	},
-	None,
-}
+	lines.extend([
+		[
+			"Started: ",
+			&channel
+				.last_live
+				.as_ref()
+				.map_or("Never".to_owned(), format_date),
+		]
+		.concat()
+		.into(),
+		["Partner: ", yes_no(channel.partner)].concat().into(),
+	]);
+
+	// Add the appropriate items for their stream or channel
+	let node = if let Some(stream) = &channel.stream {
+		// They're streaming right now
+		if let Some(category) = &stream.category {
+			lines.push(["Game: ", &category.display_name].concat().into());
+		}
 
-#[derive(Deserialize, Debug)]
-pub struct TitleTokenEdge {
-	node: TextToken,
-	// Ignore `__typename`
-}
+		lines.push(["Viewers: ", &stream.viewers.to_string()].concat().into());
 
-#[derive(Deserialize, Debug)]
-struct ShelfTitle {
-	fallbackLocalizedTitle: String,
-	localizedTitleTokens: Vec<TitleTokenEdge>,
-	// Ignore `key`, `context` and `__typename`
-}
+		if !stream.tags.is_empty() {
+			lines.push(["Tags: ", &stream.tags.join(", ")].concat().into());
+		}
 
-#[derive(Deserialize, Debug)]
-pub struct FreeformTag {
-	name: String, // Ignore `id`, `__typename`
-}
+		Node::stream(&channel)
+	} else {
+		if channel.last_live.is_some() {
+			// Their channel has their past broadcasts
+			lines.extend([
+				"".into(),
+				"Not currently streaming, open their channel to see past broadcasts".into(),
+			]);
 
-#[derive(Deserialize, Debug)]
-struct PlaybackAccessToken {
-	signature: String,
-	value: String,
-	// Ignore `__typename`
-}
+			if let Some(video) = &channel.latest_video {
+				lines.push(
+					["Latest VOD length: ", &format_length(video.length_seconds)]
+						.concat()
+						.into(),
+				);
+			}
+		}
 
-#[derive(Deserialize, Debug)]
-struct ClipVideoQuality {
-	quality: String,
-	sourceURL: String,
-	// Ignore `frameRate` and `__typename`
-}
+		// They might have uploads even if they've never streamed
+		Node::Channel(channel.login.clone())
+	};
 
-#[derive(Deserialize, Debug)]
-struct Clip {
-	playbackAccessToken: PlaybackAccessToken,
-	videoQualities: Vec<ClipVideoQuality>, // Ignore `id` and `__typename`
-}
+	if let Some(description) = &channel.description {
+		lines.extend(["".into(), description.clone().into()]);
+	}
 
-#[derive(Deserialize, Debug)]
-struct VideoAccessToken_ClipData {
-	clip: Clip,
-}
+	// Searches only have the next one
+	if let Some(segment) = channel.schedule.first() {
+		lines.extend([
+			"".into(),
+			"Next scheduled stream:".into(),
+			segment.title.clone().into(),
+		]);
+		lines.extend(segment_times(segment));
+	}
 
-#[derive(Deserialize, Debug)]
-struct VideoAccessToken_ClipResponse {
-	data: VideoAccessToken_ClipData,
-	// Ignore `extensions`
+	info.push((
+		Paragraph::new(Text { lines })
+			.style(style)
+			.wrap(Wrap { trim: false }),
+		node,
+	));
+
+	if let Some(clip) = channel.top_clip {
+		titles.push(ListItem::new(spaced("| Top clip")));
+		info.push((
+			Paragraph::new(vec![
+				clip.title.clone().unwrap_or_default().into(),
+				"".into(),
+				["Duration: ", &clip.duration_seconds.to_string(), " s"]
+					.concat()
+					.into(),
+			])
+			.wrap(Wrap { trim: false }),
+			Node::Clip(clip),
+		));
+	}
 }
 
-#[derive(Deserialize, Debug)]
-struct PlaybackAccessTokenData {
-	videoPlaybackAccessToken: PlaybackAccessToken,
+/// When a scheduled stream starts and ends, and what it's in
+fn segment_times<'a>(segment: &twitch_curses::ScheduleSegment) -> Vec<Spans<'a>> {
+	vec![
+		[
+			"Starts: ",
+			&segment
+				.starts_at
+				.as_ref()
+				.map_or("tbd".to_owned(), format_date),
+		]
+		.concat()
+		.into(),
+		[
+			"Ends: ",
+			&segment
+				.ends_at
+				.as_ref()
+				.map_or("tbd".to_owned(), format_date),
+		]
+		.concat()
+		.into(),
+		["Categories: ", &segment.categories.join(", ")]
+			.concat()
+			.into(),
+	]
 }
 
-#[derive(Deserialize, Debug)]
-struct PlaybackAccessTokenResponse {
-	data: PlaybackAccessTokenData, // Ignore `extensions`
+/// Page loaded on start
+#[derive(Deserialize, Clone)]
+pub enum HomePage {
+	/// The bit on the left on the webapp
+	PersonalSection,
+	/// The main home page
+	Shelves,
+	/// A category
+	Game(String),
+	/// A search
+	Search(String),
+	/// Channels the logged in user follows
+	Following,
+	/// Top categories, most viewers first
+	Directory,
 }
 
-#[derive(Deserialize, Debug)]
-#[serde(untagged)]
+/// Something in a list, and what selecting it does
+#[derive(Debug, Clone)]
 pub enum Node {
-	Clip {
-		slug: String,
-		clipTitle: String,
-		clipViewCount: u32,
-		curator: User,
-		game: Game,
-		broadcaster: User,
-		clipCreatedAt: String,
-		// Clips are 60 seconds max
-		durationSeconds: u8,
-		language: String,
-		// Ignore `id`, `url`, `embedURl`, `thumbnailURL`, `champBadge` and `__typename`
-	},
-	Game(Game),
+	Clip(Box<Clip>),
+	/// Property is the category's name
+	Game(String),
+	/// A channel's live stream
 	Stream {
-		broadcaster: User,
-		game: Option<Game>,
-		freeformTags: Vec<FreeformTag>,
-		viewersCount: u32,
-		createdAt: Option<String>,
-		// Ignore `id`, `previewImageUrl`, `type` and `__typename`
+		login: String,
+		id: String,
 	},
 	/// Property is the VOD ID
 	Video(String),
+	/// Property is the channel's login
+	Channel(String),
+	/// A channel's videos of this type
+	Videos {
+		login: String,
+		kind: VideoType,
+	},
+	/// A channel's or category's clips
+	Clips(ClipSource),
 	None,
 }
-impl Node {
-	/// Select this node. Returns the game name if it needs to be moved into.
-	pub fn select<B: Backend>(
-		&self,
+
+/// What selecting a [`Node`] does, once anything it needs has been downloaded
+pub enum Selected {
+	/// Open this category
+	Game(String),
+	/// Watch this channel
+	Stream { login: String, id: String },
+	/// Open this channel's page
+	Channel(String),
+	/// Open this channel's videos of this type
+	Videos { login: String, kind: VideoType },
+	/// Open these clips
+	Clips(ClipSource),
+	/// Play this URL with the player
+	Play(String),
+	/// Nothing to do
+	None,
+}
+impl Selected {
+	/// Does what this selection needs the terminal for. Returns itself if it's a page to move
+	/// into instead. If `chat_only` is set, streams only show chat without starting the player.
+	pub fn run<B: Backend>(
+		self,
 		terminal: &mut Terminal<B>,
-		easy: &mut Easy,
 		qualities: &[&str],
-	) -> Option<String> {
+		chat_only: bool,
+	) -> Result<Option<Selected>, Error> {
 		match self {
-			Node::Clip { slug, .. } => {
-				let _ = disable_raw_mode();
+			Selected::Game(_)
+			| Selected::Channel(_)
+			| Selected::Videos { .. }
+			| Selected::Clips(_) => Ok(Some(self)),
+			Selected::Stream { login, id } => {
+				// Load chat UI if enabled
+				#[cfg(feature = "chat")]
+				crate::irc::play_stream(terminal, &login, &id, qualities, chat_only)?;
 
-				// We want to be in a normal terminal
-				let _ = execute!(stdout(), LeaveAlternateScreen);
+				// Otherwise, just run the stream
+				#[cfg(not(feature = "chat"))]
+				run_outside_ui(Command::new("streamlink").args([
+					["-p=", &config().player.join(" ")].concat(),
+					["twitch.tv/", &login].concat(),
+					qualities.join(","),
+				]))?;
 
-				let response = from_slice::<VideoAccessToken_ClipResponse>(&mut request(
-					easy,
-					&TwitchRequest {
-						variables: VideoAccessToken_ClipVariables { slug: slug.clone() },
-						..TwitchRequest::default()
-					},
-				))
-				.expect("Response should be valid JSON");
+				Ok(None)
+			}
+			Selected::Play(url) => {
+				run_outside_ui(
+					Command::new(&config().player[0])
+						.args(&config().player[1..])
+						.arg(url),
+				)?;
+
+				Ok(None)
+			}
+			Selected::None => Ok(None),
+		}
+	}
+}
+
+impl Node {
+	/// Watching `channel`'s stream
+	pub fn stream(channel: &Channel) -> Self {
+		Node::Stream {
+			login: channel.login.clone(),
+			id: channel.id.clone(),
+		}
+	}
+
+	/// Gets what's needed to select this node, i.e. the URL of a clip or VOD. This doesn't touch
+	/// the terminal, so it can run on the worker while the UI shows it's `progress`.
+	pub fn resolve(
+		&self,
+		client: &mut Client,
+		progress: &Progress,
+		qualities: &[&str],
+	) -> Result<Selected, Error> {
+		match self {
+			Node::Clip(clip) => {
+				let clip_qualities = client.clip_qualities(&clip.slug)?;
 
 				// Default to best quality
-				let mut source_url = &response.data.clip.videoQualities[0].sourceURL;
+				let mut url = &clip_qualities
+					.first()
+					.ok_or_else(|| {
+						Error::new(
+							"VideoAccessToken_Clip",
+							ErrorKind::Other("Clip has no qualities".to_owned()),
+						)
+					})?
+					.url;
 				for quality in qualities {
 					match *quality {
 						"audio_only" | "worst" => {
 							// Get last quality
-							source_url = &response
-								.data
-								.clip
-								.videoQualities
+							url = &clip_qualities
 								.last()
-								.expect("Server should give at least one quality")
-								.sourceURL;
+								.expect("We already know there's at least one quality")
+								.url;
 							break;
 						}
 						"best" => {
@@ -524,14 +541,11 @@ impl Node {
 							// If quality ends in a p
 							if matches!(quality.as_bytes()[quality.len() - 1], b'P' | b'p') {
 								// See if the requested quality is available
-								if let Some(clip_video_quality) =
-									response.data.clip.videoQualities.iter().find(
-										|clip_video_quality| {
-											clip_video_quality.quality
-												== quality[..quality.len() - 1]
-										},
-									) {
-									source_url = &clip_video_quality.sourceURL;
+								if let Some(clip_quality) =
+									clip_qualities.iter().find(|clip_quality| {
+										clip_quality.quality == quality[..quality.len() - 1]
+									}) {
+									url = &clip_quality.url;
 									break;
 								}
 							}
@@ -540,120 +554,31 @@ impl Node {
 					}
 				}
 
-				let _ = Command::new(PLAYER[0])
-					.args(&PLAYER[1..])
-					.arg(
-						[
-							source_url,
-							"?sig=",
-							&response.data.clip.playbackAccessToken.signature,
-							"&token=",
-							// token needs to be urlencoded again, luckily we just need to replace
-							// `%`s
-							&response
-								.data
-								.clip
-								.playbackAccessToken
-								.value
-								.replace('%', "%25"),
-						]
-						.concat(),
-					)
-					.spawn()
-					.unwrap_or_else(|_| {
-						panic!("Should be able to spawn PLAYER ({})", &PLAYER.join(" "))
-					})
-					.wait();
-
-				let _ = enable_raw_mode();
-				let _ = execute!(stdout(), EnterAlternateScreen);
-
-				None
+				Ok(Selected::Play(url.clone()))
 			}
-			Node::Game(Game { name, .. }) => Some(name.clone()),
-			Node::Stream {
-				broadcaster: User { id, login, .. },
-				..
-			} => {
-				// Load chat UI if enabled
-				#[cfg(feature = "chat")]
-				crate::irc::play_stream(terminal, easy, login, id, qualities);
-
-				// Otherwise, just run the stream
-				#[cfg(not(feature = "chat"))]
-				{
-					let _ = disable_raw_mode();
-					// We want to be in a normal terminal
-					let _ = execute!(stdout(), LeaveAlternateScreen);
-
-					let _ = Command::new("streamlink")
-						.args([
-							["-p=", &PLAYER.join(" ")].concat(),
-							["twitch.tv/", login].concat(),
-							qualities.join(","),
-						])
-						.spawn()
-						.expect("Should be able to spawn streamlink")
-						.wait();
-
-					let _ = enable_raw_mode();
-					let _ = execute!(stdout(), EnterAlternateScreen);
-				}
-
-				None
-			}
-			Node::Video(vodID) => {
-				let _ = disable_raw_mode();
-
-				// We want to be in a normal terminal
-				let _ = execute!(stdout(), LeaveAlternateScreen);
-
-				let response = from_slice::<PlaybackAccessTokenResponse>(&mut request(
-					easy,
-					&TwitchRequest {
-						variables: PlaybackAccessTokenVariables {
-							vodID: vodID.to_owned(),
-							..TwitchRequest::default().variables
-						},
-						..TwitchRequest::default()
-					},
-				))
-				.expect("Response should be valid JSON");
-
-				let mut new_easy = Easy::new();
-
-				let _ = new_easy.url(
-					&[
-						"https://usher.ttvnw.net/vod/",
-						vodID,
-						".m3u8?sig=",
-						&response.data.videoPlaybackAccessToken.signature,
-						"&token=",
-						&response.data.videoPlaybackAccessToken.value,
-					]
-					.concat(),
-				);
-
-				// Make sure `vec` lives longer than `transfer`
-				let mut vec = Vec::new();
-
-				{
-					let mut transfer = new_easy.transfer();
-
-					let _ = transfer.write_function(|slice| {
-						vec.extend_from_slice(slice);
-						Ok(slice.len())
-					});
-
-					let _ = transfer.perform();
-				}
+			Node::Game(name) => Ok(Selected::Game(name.clone())),
+			Node::Stream { login, id } => Ok(Selected::Stream {
+				login: login.clone(),
+				id: id.clone(),
+			}),
+			Node::Video(id) => {
+				let playlist = get(
+					&client.video_playback_url(id)?,
+					"Getting the VOD playlist",
+					progress,
+				)?;
 
 				// Set to `Some` when the appropriate URL is found
 				let mut url = None;
 
 				// Split response into lines
-				let mut split = from_utf8(&vec)
-					.expect("Response should be valid utf8")
+				let mut split = from_utf8(&playlist)
+					.map_err(|_| {
+						Error::new(
+							"Getting the VOD playlist",
+							ErrorKind::Other("Playlist isn't valid UTF-8".to_owned()),
+						)
+					})?
 					.split('\n');
 				for quality in qualities {
 					match *quality {
@@ -681,1085 +606,774 @@ impl Node {
 					};
 				}
 
-				let _ = Command::new(PLAYER[0])
-					.args(&PLAYER[1..])
-					.arg(
-						// Default to best quality
-						url.unwrap_or(split.nth(4))
-							.expect("Should be able to get a VOD URL"),
+				// Default to best quality
+				let url = url.unwrap_or_else(|| split.nth(4)).ok_or_else(|| {
+					Error::new(
+						"Getting the VOD playlist",
+						ErrorKind::Other("Playlist has no streams".to_owned()),
 					)
-					.spawn()
-					.unwrap_or_else(|_| {
-						panic!(
-							"{}",
-							["Should be able to spawn PLAYER (", &PLAYER.join(" "), ")"].concat()
-						)
-					})
-					.wait();
-
-				let _ = enable_raw_mode();
+				})?;
 
-				let _ = execute!(stdout(), EnterAlternateScreen);
+				Ok(Selected::Play(url.to_owned()))
+			}
+			Node::Channel(login) => Ok(Selected::Channel(login.clone())),
+			Node::Videos { login, kind } => Ok(Selected::Videos {
+				login: login.clone(),
+				kind: *kind,
+			}),
+			Node::Clips(source) => Ok(Selected::Clips(source.clone())),
+			Node::None => Ok(Selected::None),
+		}
+	}
 
+	/// Login of the channel this node is from, if it's from one. For clips it's the curator.
+	pub fn channel(&self) -> Option<&str> {
+		match self {
+			Node::Clip(clip) => clip.curator.as_ref().map(|curator| curator.login.as_str()),
+			Node::Stream { login, .. }
+			| Node::Channel(login)
+			| Node::Videos { login, .. }
+			| Node::Clips(ClipSource::Channel(login)) => Some(login),
+			Node::Game(_) | Node::Video(_) | Node::Clips(ClipSource::Category(_)) | Node::None => {
 				None
 			}
-			Node::None => None,
 		}
 	}
-}
 
-#[derive(Deserialize, Debug)]
-pub struct ShelfContentEdge {
-	node: Node, /* Ignore `cursor`, `trackingID`, `promotionsCampaignID`, `sourceType`
-	             * and `__typename` */
+	/// Name of the category this node is, if it's one.
+	pub fn game(&self) -> Option<&str> {
+		match self {
+			Node::Game(name) | Node::Clips(ClipSource::Category(name)) => Some(name),
+			_ => None,
+		}
+	}
 }
 
-#[derive(Deserialize, Debug)]
-struct ShelfContentConnection {
-	edges: Vec<ShelfContentEdge>,
-	// Ignore `__typename`
-}
+/// How to continue a listing that has more items
+#[derive(Clone)]
+pub enum More {
+	/// Streams in a category, after this cursor
+	Streams(String),
+	/// Search results of this type, after this cursor
+	Search(SearchIndex, String),
+	/// A channel's videos, after this cursor
+	Videos(String),
+	/// A channel's or category's clips, after this cursor
+	Clips(String),
+	/// Top categories, after this cursor
+	Directory(String),
+}
+
+/// Everything shown for a page
+#[derive(Default)]
+pub struct Widgets<'a> {
+	/// Items in the main list
+	pub items: Vec<ListItem<'a>>,
+	/// Info and node for each item
+	pub info: Vec<(Paragraph<'a>, Node)>,
+	/// Sections that can load more items, with where they end in `items`
+	pub more: Vec<(usize, More)>,
+}
+impl<'a> Widgets<'a> {
+	/// The main [`List`] widget
+	pub fn list(&self) -> List<'a> {
+		List::new(self.items.clone()).highlight_style(theme().highlight)
+	}
 
-#[derive(Deserialize, Debug)]
-struct Shelf {
-	title: ShelfTitle,
-	content: ShelfContentConnection,
-	// Ignore `id`, `trackingInfo` and `__typename`
-}
+	/// Adds the next page of the section ending at `end`.
+	pub fn extend_section(&mut self, end: usize, other: Widgets<'a>) {
+		let added = other.items.len();
 
-#[derive(Deserialize, Debug)]
-struct ShelfEdge {
-	node: Shelf,
-	// Ignore `__typename`
-}
+		// The section's old continuation is replaced by the new one
+		self.more.retain(|(section_end, _)| *section_end != end);
+		for (section_end, _) in &mut self.more {
+			if *section_end > end {
+				*section_end += added;
+			}
+		}
+		self.more.extend(
+			other
+				.more
+				.into_iter()
+				.map(|(section_end, more)| (end + section_end, more)),
+		);
+
+		self.items.splice(end..end, other.items);
+		self.info.splice(end..end, other.info);
+	}
 
-#[derive(Deserialize, Debug)]
-struct ShelfConnection {
-	edges: Vec<ShelfEdge>,
-	// Ignore `verboseResults` and `__typename`
-}
+	/// Removes the section header at the start, for when these are more items for a section
+	/// that's already shown.
+	pub fn without_header(mut self) -> Self {
+		if !self.items.is_empty() {
+			self.items.remove(0);
+			self.info.remove(0);
 
-#[derive(Deserialize, Debug)]
-struct Stream {
-	title: String,
-	viewersCount: u32,
-	createdAt: String,
-	broadcaster: User,
-	freeformTags: Vec<FreeformTag>,
-	game: Game, // Ignore `id`, `previewImageUrl`, `type` and `__typename`
-}
+			for (section_end, _) in &mut self.more {
+				*section_end -= 1;
+			}
+		}
 
-#[derive(Deserialize, Debug)]
-struct StreamEdge {
-	node: Stream, // Ignore `cursor`, `trackingID` and `__typename`
-}
+		self
+	}
 
-#[derive(Deserialize, Debug)]
-struct StreamConnection {
-	edges: Vec<StreamEdge>,
-	// Ignore `pageInfo` and `__typename`
+	/// A listing's widgets, with `more` continuing it after the last item if there's a `cursor`
+	fn listing(
+		items: Vec<ListItem<'a>>,
+		info: Vec<(Paragraph<'a>, Node)>,
+		cursor: Option<String>,
+		more: impl FnOnce(String) -> More,
+	) -> Self {
+		Self {
+			more: cursor
+				.map(|cursor| vec![(items.len(), more(cursor))])
+				.unwrap_or_default(),
+			items,
+			info,
+		}
+	}
 }
 
-#[derive(Deserialize, Debug)]
-struct Category {
-	streams: StreamConnection,
-	// Ignore `id`, `name`, `displayName` and `__typename`
+pub trait Data {
+	/// Converts the data to [`Widgets`].
+	fn into_widgets<'a>(self) -> Widgets<'a>;
 }
 
-#[derive(Deserialize, Debug)]
-struct FollowerConnection {
-	totalCount: u32,
-	// Ignore `__typename`
-}
+/// The home page or personal sections
+impl Data for Vec<Shelf> {
+	fn into_widgets<'a>(self) -> Widgets<'a> {
+		let mut titles = Vec::new();
+		let mut info = Vec::new();
 
-#[derive(Deserialize, Debug)]
-struct Broadcast {
-	startedAt: Option<String>,
-	// Ignore `id` and `__typename`
-}
+		for shelf in self {
+			titles.push(ListItem::new(spaced(Spans(
+				shelf
+					.title
+					.into_iter()
+					.map(|part| Span {
+						content: part.text.into(),
+						style: theme().header.add_modifier(if part.emphasis {
+							Modifier::BOLD
+						} else {
+							Modifier::empty()
+						}),
+					})
+					.collect(),
+			))));
 
-#[derive(Deserialize, Debug)]
-struct ScheduleSegmentGame {
-	name: String, // Ignore `id` and `__typename`
-}
+			// No info for title
+			info.push((Paragraph::new(Text { lines: Vec::new() }), Node::None));
 
-#[derive(Deserialize, Debug)]
-struct ScheduleSegment {
-	startAt: String,
-	endAt: Option<String>,
-	title: String,
-	categories: Vec<ScheduleSegmentGame>, // Ignore `id`, `hasReminder` and `__typename`
-}
+			for item in shelf.items {
+				match item {
+					ShelfItem::Stream(stream) => add_stream(&stream, &mut titles, &mut info),
+					ShelfItem::Category(category) => add_category(category, &mut titles, &mut info),
+					ShelfItem::Clip(clip) => add_clip(*clip, &mut titles, &mut info),
+				}
+			}
+		}
 
-#[derive(Deserialize, Debug)]
-struct Schedule {
-	nextSegment: Option<ScheduleSegment>, // Ignore `id` and `__typename`
+		Widgets {
+			items: titles,
+			info,
+			more: Vec::new(),
+		}
+	}
 }
 
-#[derive(Deserialize, Debug)]
-struct Channel {
-	schedule: Option<Schedule>, // Ignore `id` and `__typename`
-}
+/// A category's streams
+impl Data for Page<Stream> {
+	fn into_widgets<'a>(self) -> Widgets<'a> {
+		let mut titles = Vec::new();
+		let mut info = Vec::new();
 
-#[derive(Deserialize, Debug)]
-struct Video {
-	id: String,
-	// Up to 48 hours
-	lengthSeconds: u32,
-	// Ignore `title`, `previewThumbnailURL` and `__typename`
-}
+		for stream in &self.items {
+			add_stream(stream, &mut titles, &mut info);
+		}
 
-#[derive(Deserialize, Debug)]
-struct VideoEdge {
-	node: Video, // Ignore `__typename`
+		Widgets::listing(titles, info, self.cursor, More::Streams)
+	}
 }
 
-#[derive(Deserialize, Debug)]
-struct VideoConnection {
-	edges: Vec<VideoEdge>,
-	// Ignore `__typrname`
-}
+/// Adds an item and it's info to the given `Vec`s, like [`add_stream`]
+type AddItem<'a, T> = fn(T, &mut Vec<ListItem<'a>>, &mut Vec<(Paragraph<'a>, Node)>);
+
+/// A search section's widgets, with a header and how to get more of them if `index` is set
+fn search_section<'a, T>(
+	title: &'static str,
+	section: SearchSection<T>,
+	index: Option<SearchIndex>,
+	add: AddItem<'a, T>,
+) -> Widgets<'a> {
+	let mut titles = vec![ListItem::new(spaced(header(title)))];
+	let mut info = vec![(
+		Paragraph::new(section.total.map_or_else(String::new, |total| {
+			["Total matches: ", &total.to_string()].concat()
+		})),
+		Node::None,
+	)];
+
+	for item in section.items {
+		add(item, &mut titles, &mut info);
+	}
 
-#[derive(Deserialize, Debug)]
-struct SearchForEdgeClip {
-	title: String,
-	// Max 60 seconds, fits in u8
-	durationSeconds: u8,
-	slug: String, // Ignore `id`, `thumbnailURL` and `__typename`
+	match index {
+		Some(index) => Widgets::listing(titles, info, section.cursor, |cursor| {
+			More::Search(index, cursor)
+		}),
+		None => Widgets {
+			items: titles,
+			info,
+			more: Vec::new(),
+		},
+	}
 }
 
-#[derive(Deserialize, Debug)]
-struct ClipEdge {
-	node: SearchForEdgeClip, // Ignore `__typename`
-}
+impl Data for SearchResults {
+	fn into_widgets<'a>(self) -> Widgets<'a> {
+		let mut sections = Vec::new();
 
-#[derive(Deserialize, Debug)]
-struct ClipConnection {
-	edges: Vec<ClipEdge>, // Ignore `__typename`
-}
+		if !self.channels.items.is_empty() {
+			sections.push((
+				self.channels.score,
+				search_section(
+					"Channels",
+					self.channels,
+					Some(SearchIndex::Channel),
+					add_search_channel,
+				),
+			));
+		}
+		if !self.channels_with_tag.items.is_empty() {
+			sections.push((
+				self.channels_with_tag.score,
+				search_section(
+					"Live channels with tag",
+					self.channels_with_tag,
+					Some(SearchIndex::ChannelWithTag),
+					add_search_channel,
+				),
+			));
+		}
+		if !self.categories.items.is_empty() {
+			sections.push((
+				self.categories.score,
+				search_section(
+					"Categories",
+					self.categories,
+					Some(SearchIndex::Game),
+					add_category,
+				),
+			));
+		}
+		if !self.videos.items.is_empty() {
+			sections.push((
+				self.videos.score,
+				search_section(
+					"Past videos",
+					self.videos,
+					Some(SearchIndex::Vod),
+					add_video,
+				),
+			));
+		}
+		if !self.related_streams.items.is_empty() {
+			sections.push((
+				self.related_streams.score,
+				search_section(
+					"People searching also watch:",
+					self.related_streams,
+					None,
+					|stream, titles, info| add_stream(&stream, titles, info),
+				),
+			));
+		}
 
-#[derive(Deserialize, Debug)]
-struct SearchForEdgeStream {
-	game: Game,
-	freeformTags: Vec<FreeformTag>,
-	viewersCount: u32, // Ignore `id`, `previewImageURL`, `type` and `__typename`
-}
+		// Add the sections in score order
+		sections.sort_by_key(|(score, _)| *score);
+
+		let mut widgets = Widgets::default();
+		for (_, section) in sections {
+			// Section ends are relative to the start of the section
+			let start = widgets.items.len();
+			widgets.more.extend(
+				section
+					.more
+					.into_iter()
+					.map(|(end, more)| (start + end, more)),
+			);
 
-/// [`User`] returned by a search
-#[derive(Deserialize, Debug)]
-struct SearchForEdgeUser {
-	id: String,
-	broadcastSettings: BroadcastSettings,
-	displayName: String,
-	followers: FollowerConnection,
-	lastBroadcast: Broadcast,
-	login: String,
-	description: Option<String>,
-	channel: Channel,
-	latestVideo: VideoConnection,
-	topClip: ClipConnection,
-	roles: UserRoles,
-	stream: Option<SearchForEdgeStream>,
-	// Ignore `id`, `profileImageURL`, `self`, `watchParty` and `__typename`
+			widgets.items.extend(section.items);
+			widgets.info.extend(section.info);
+		}
+
+		widgets
+	}
 }
 
-impl SearchForEdgeUser {
-	/// Adds this item's info to the given `Vec`
-	fn add_items_to(self, items_list: &mut (Vec<Span>, Vec<(Paragraph, Node)>)) {
-		items_list.0.push(self.displayName.into());
+/// Followed channels, live ones first
+impl Data for Vec<Channel> {
+	fn into_widgets<'a>(self) -> Widgets<'a> {
+		let mut titles = Vec::new();
+		let mut info = Vec::new();
 
-		let mut lines = vec![
-			self.broadcastSettings.title.into(),
-			"".into(),
-			["Followers: ", &self.followers.totalCount.to_string()]
-				.concat()
-				.into(),
-			[
-				"Started: ",
-				&self
-					.lastBroadcast
-					.startedAt
-					.as_ref()
-					.map_or("Never".to_owned(), |x| format_date(x)),
-			]
-			.concat()
-			.into(),
-			["Partner: ", if self.roles.isPartner { "Yes" } else { "No" }]
-				.concat()
-				.into(),
-		];
-
-		// Add the appropriate items for stream/VOD/nothing
-		let node = if self.lastBroadcast.startedAt.is_some() {
-			if let Some(stream) = self.stream {
-				//They're streaming right now
-				lines.extend([
-					[
-						"Game: ",
-						&stream.game.displayName.unwrap_or(stream.game.name),
-					]
-					.concat()
-					.into(),
-					["Viewers: ", &stream.viewersCount.to_string()]
-						.concat()
-						.into(),
-					[
-						"Tags: ",
-						&stream
-							.freeformTags
-							.iter()
-							.map(|tag| tag.name.clone())
-							.collect::<Vec<String>>()
-							.join(", "),
-					]
-					.concat()
-					.into(),
-				]);
-
-				// Their current stream
-				Node::Stream {
-					broadcaster: User {
-						id: self.id,
-						login: self.login,
-						displayName: String::new(),
-						primaryColorHex: None,
-						broadcastSettings: Some(BroadcastSettings {
-							title: String::new(),
-						}),
-						roles: None,
-					},
-					game: None,
-					freeformTags: Vec::new(),
-					viewersCount: 0,
-					createdAt: None,
-				}
-			} else if self.latestVideo.edges.is_empty() {
-				// They have streamed before, but we didn't get a VOD
-				Node::None
-			} else {
-				// They're not currently streaming, show most recent VOD
-				lines.extend([
-					"".into(),
-					"Not currently streaming, you can watch their latest VOD".into(),
-					[
-						"Length: ",
-						&self.latestVideo.edges[0].node.lengthSeconds.to_string(),
-						" s",
-					]
-					.concat()
-					.into(),
-				]);
+		let (mut live, mut offline): (Vec<_>, Vec<_>) = self
+			.into_iter()
+			.partition(|channel| channel.stream.is_some());
 
-				// Their last stream
-				Node::Video(self.latestVideo.edges[0].node.id.clone())
-			}
-		} else {
-			// They've never streamed
-			Node::None
-		};
+		// Most viewers first
+		live.sort_by_key(|channel| {
+			Reverse(channel.stream.as_ref().map_or(0, |stream| stream.viewers))
+		});
+		// Most recently live first, channels that have never streamed last
+		offline.sort_by_key(|channel| Reverse(channel.last_live));
 
-		if let Some(description) = self.description {
-			lines.extend(["".into(), description.into()]);
+		if !live.is_empty() {
+			titles.push(ListItem::new(spaced(header("Live"))));
+			info.push((Paragraph::new(Text { lines: Vec::new() }), Node::None));
 		}
 
-		if let Some(Schedule {
-			nextSegment: Some(next_segment),
-		}) = self.channel.schedule
-		{
-			lines.extend([
-				"".into(),
-				"Next scheduled stream:".into(),
-				next_segment.title.into(),
-				["Starts: ", &format_date(&next_segment.startAt)]
-					.concat()
-					.into(),
-				[
-					"Ends: ",
-					&if let Some(end_at) = &next_segment.endAt {
-						format_date(end_at)
-					} else {
-						"tbd".to_owned()
-					},
-				]
-				.concat()
-				.into(),
-				[
-					"Categories: ",
-					&next_segment
-						.categories
-						.into_iter()
-						.map(|game| game.name)
-						.collect::<Vec<String>>()
-						.join(", "),
-				]
-				.concat()
-				.into(),
-			]);
+		for channel in live {
+			let stream = channel.stream.expect("Live channels should have a stream");
+			add_stream(&stream, &mut titles, &mut info);
 		}
 
-		items_list
-			.1
-			.push((Paragraph::new(lines).wrap(Wrap { trim: false }), node));
+		if !offline.is_empty() {
+			titles.push(ListItem::new(spaced(header("Offline"))));
+			info.push((Paragraph::new(Text { lines: Vec::new() }), Node::None));
+		}
 
-		// If there is a top clip
-		if self.topClip.edges.len() == 1 {
-			items_list.0.push("| Top clip".into());
+		for channel in offline {
+			let style = channel_style(&channel);
 
-			items_list.1.push((
+			titles.push(ListItem::new(spaced(channel.display_name.clone())).style(style));
+			info.push((
 				Paragraph::new(vec![
-					self.topClip.edges[0].node.title.clone().into(),
+					channel.title.unwrap_or_default().into(),
 					"".into(),
+					channel.display_name.into(),
 					[
-						"Duration: ",
-						&self.topClip.edges[0].node.durationSeconds.to_string(),
-						" s",
+						"Last live: ",
+						&channel
+							.last_live
+							.as_ref()
+							.map_or("Never".to_owned(), format_date),
 					]
 					.concat()
 					.into(),
 				])
+				.style(style)
 				.wrap(Wrap { trim: false }),
-				// We just need the slug
-				Node::Clip {
-					slug: self.topClip.edges[0].node.slug.clone(),
-					clipTitle: String::new(),
-					clipViewCount: 0,
-					curator: User {
-						id: String::new(),
-						login: String::new(),
-						displayName: String::new(),
-						primaryColorHex: None,
-						broadcastSettings: None,
-						roles: None,
-					},
-					game: Game {
-						viewersCount: None,
-						name: String::new(),
-						displayName: None,
-						gameTags: None,
-						originalReleaseDate: None,
-					},
-					broadcaster: User {
-						id: String::new(),
-						login: String::new(),
-						displayName: String::new(),
-						primaryColorHex: None,
-						broadcastSettings: None,
-						roles: None,
-					},
-					clipCreatedAt: String::new(),
-					durationSeconds: 0,
-					language: String::new(),
-				},
+				Node::Channel(channel.login),
 			));
 		}
-	}
-}
-
-#[derive(Deserialize, Debug)]
-struct SearchForEdge {
-	item: SearchForEdgeUser, // Ignore `trackingID` and `__typename`
-}
-
-#[derive(Deserialize, Debug)]
-struct SearchForResultUsers {
-	edges: Vec<SearchForEdge>,
-	// It's from 1 to 5, so would fit in a u8
-	// However, we want to use it later for indexing
-	score: usize,
-	// Max 10,000, so fits in u16
-	totalMatches: u16,
-	// Ignore `cursor` and `__typename`
-}
-
-#[derive(Deserialize, Debug)]
-struct SearchForEdgeGame {
-	item: Game, // Ignore `trackingID` and `__typename`
-}
 
-// Same as `SearchForResultUsers`
-#[derive(Deserialize, Debug)]
-struct SearchForResultGames {
-	edges: Vec<SearchForEdgeGame>,
-	// It's from 1 to 5, so would fit in a u8
-	// However, we want to use it later for indexing
-	score: usize,
-	// Max 10,000, so fits in u16
-	totalMatches: u16,
-	// Ignore `cursor` and `__typename`
+		Widgets {
+			items: titles,
+			info,
+			more: Vec::new(),
+		}
+	}
 }
 
-#[derive(Deserialize, Debug)]
-struct SearchForEdgeVideoVideo {
-	createdAt: String,
-	owner: User,
-	id: String,
-	game: Game,
-	// Up to 2 days
-	lengthSeconds: u32,
-	title: String,
-	viewCount: u32,
-	// Ignore `id`, `previewThumbnailURL` and `__typename`
-}
+/// A channel's videos of one type
+impl Data for Page<Video> {
+	fn into_widgets<'a>(self) -> Widgets<'a> {
+		let mut titles = Vec::new();
+		let mut info = Vec::new();
 
-#[derive(Deserialize, Debug)]
-struct SearchForEdgeVideo {
-	item: SearchForEdgeVideoVideo, // Ignore `trackingID` and `__typename`
-}
+		for video in self.items {
+			add_video(video, &mut titles, &mut info);
+		}
 
-#[derive(Deserialize, Debug)]
-struct SearchForResultVideos {
-	edges: Vec<SearchForEdgeVideo>,
-	// It's from 1 to 5, so would fit in a u8
-	// However, we want to use it later for indexing
-	score: usize,
-	// Max 10,000, so fits in u16
-	totalMatches: u16,
-	// Ignore `cursor` and `__typename`
+		Widgets::listing(titles, info, self.cursor, More::Videos)
+	}
 }
 
-#[derive(Deserialize, Debug)]
-struct SearchForEdgeRelatedLiveChannelsStream {
-	viewersCount: u32,
-	game: Game,
-	broadcaster: User, // Ignore `id`, `previewImageURL` and `__typename`
-}
+/// Top categories
+impl Data for Page<Category> {
+	fn into_widgets<'a>(self) -> Widgets<'a> {
+		let mut titles = Vec::new();
+		let mut info = Vec::new();
 
-#[derive(Deserialize, Debug)]
-struct SearchForEdgeRelatedLiveChannelsUser {
-	stream: SearchForEdgeRelatedLiveChannelsStream, // Ignore `id`, `watchParty` and `__typename`
-}
+		for category in self.items {
+			add_category(category, &mut titles, &mut info);
+		}
 
-#[derive(Deserialize, Debug)]
-struct SearchForEdgeRelatedLiveChannels {
-	item: SearchForEdgeRelatedLiveChannelsUser, // Ignore `trackingId` and `__typename`
+		Widgets::listing(titles, info, self.cursor, More::Directory)
+	}
 }
 
-#[derive(Deserialize, Debug)]
-struct SearchForResultRelatedLiveChannels {
-	edges: Vec<SearchForEdgeRelatedLiveChannels>,
-	// It's from 1 to 5, so would fit in a u8
-	// However, we want to use it later for indexing
-	score: usize,
-	// Ignore `__typename`
-}
+/// A channel's or category's clips
+impl Data for Page<Clip> {
+	fn into_widgets<'a>(self) -> Widgets<'a> {
+		let mut titles = Vec::new();
+		let mut info = Vec::new();
 
-#[derive(Deserialize, Debug)]
-struct SearchFor {
-	channels: SearchForResultUsers,
-	channelsWithTag: SearchForResultUsers,
-	games: SearchForResultGames,
-	videos: SearchForResultVideos,
-	relatedLiveChannels: SearchForResultRelatedLiveChannels,
-	// Ignore `__typename`
-}
+		for clip in self.items {
+			add_clip(clip, &mut titles, &mut info);
+		}
 
-pub trait Data {
-	/// Converts the data to a main [`List`] widget and a [`Vec`] of data widgets.
-	fn into_widgets<'a>(self) -> (List<'a>, Vec<(Paragraph<'a>, Node)>);
+		Widgets::listing(titles, info, self.cursor, More::Clips)
+	}
 }
 
-#[derive(Deserialize)]
-pub struct PersonalSectionData {
-	personalSections: Vec<PersonalSection>,
-}
-impl Data for PersonalSectionData {
-	fn into_widgets<'a>(self) -> (List<'a>, Vec<(Paragraph<'a>, Node)>) {
+/// A channel's page
+impl Data for ChannelOverview {
+	fn into_widgets<'a>(self) -> Widgets<'a> {
 		let mut titles = Vec::new();
 		let mut info = Vec::new();
 
-		for personal_section in self.personalSections {
-			// Title
-			titles.push(ListItem::new(spaced(header(
-				personal_section.title.localizedFallback,
-			))));
+		let channel = self.channel;
+		let style = channel_style(&channel);
+		let title = channel.title.clone().unwrap_or_default();
 
-			// No info for title
-			info.push((Paragraph::new(Text { lines: Vec::new() }), Node::None));
-
-			// Channels
-			for channel in personal_section.items.into_iter() {
-				// Item foreground colour
-				let style = channel.user.style();
-
-				titles.push(ListItem::new(spaced(channel.user.displayName.clone())).style(style));
-				info.push((
-					Paragraph::new(Text {
-						lines: vec![
-							channel
-								.user
-								.broadcastSettings
-								.as_ref()
-								.expect("Should be a broadcast")
-								.title
-								.clone()
-								.into(),
-							"".into(),
-							channel.user.displayName.clone().into(),
-							["Viewers: ", &channel.content.viewersCount.to_string()]
-								.concat()
-								.into(),
-							[
-								"Game: ",
-								&channel
-									.content
-									.game
-									.displayName
-									.unwrap_or(channel.content.game.name),
-							]
-							.concat()
-							.into(),
-						],
-					})
+		// Live status
+		if let Some(stream) = &channel.stream {
+			titles.push(ListItem::new(spaced(["Live: ", &title].concat())).style(style));
+			info.push((
+				Paragraph::new(stream_lines(stream))
 					.style(style)
 					.wrap(Wrap { trim: false }),
-					channel.user.as_node(),
-				));
-			}
+				Node::stream(&channel),
+			));
+		} else {
+			titles.push(ListItem::new(spaced("Offline")).style(style));
+			info.push((
+				Paragraph::new(vec![
+					title.into(),
+					"".into(),
+					[
+						"Last live: ",
+						&channel
+							.last_live
+							.as_ref()
+							.map_or("Never".to_owned(), format_date),
+					]
+					.concat()
+					.into(),
+				])
+				.wrap(Wrap { trim: false }),
+				Node::None,
+			));
 		}
 
-		(
-			List::new(titles).highlight_style(Style {
-				add_modifier: Modifier::REVERSED,
-				..Style::default()
-			}),
-			info,
-		)
-	}
-}
-
-#[derive(Deserialize)]
-pub struct ShelvesData {
-	shelves: ShelfConnection,
-}
-impl Data for ShelvesData {
-	fn into_widgets<'a>(self) -> (List<'a>, Vec<(Paragraph<'a>, Node)>) {
-		let mut titles = Vec::new();
-		let mut info = Vec::new();
+		// Videos, selecting a section's header shows all of them
+		for (kind, videos) in [
+			(VideoType::Archive, self.archives),
+			(VideoType::Highlight, self.highlights),
+			(VideoType::Upload, self.uploads),
+		] {
+			if videos.is_empty() {
+				continue;
+			}
 
-		for edge in self.shelves.edges {
-			// Category title
-			// Use fallback title if any tokens are null
-			titles.push(
-				if edge
-					.node
-					.title
-					.localizedTitleTokens
-					.iter()
-					.any(|x| matches!(x.node, TextToken::None))
-				{
-					// Fallback string
-					ListItem::new(spaced(header(edge.node.title.fallbackLocalizedTitle)))
-				} else {
-					ListItem::new(spaced(Spans(
-						edge.node
-							.title
-							.localizedTitleTokens
-							.into_iter()
-							.map(|token| {
-								match token.node {
-									TextToken::BrowsableCollection {
-										collectionName:
-											BrowsableCollectionTitle {
-												fallbackLocalizedTitle,
-											},
-									} => header(fallbackLocalizedTitle),
-									TextToken::Game(Game {
-										displayName, name, ..
-									}) => header(displayName.unwrap_or(name)),
-									TextToken::TextToken { text, hasEmphasis } => Span {
-										content: text.into(),
-										style: Style {
-											add_modifier: if hasEmphasis {
-												Modifier::BOLD
-											} else {
-												Modifier::empty()
-											} | Modifier::UNDERLINED,
-											..Style::default()
-										},
-									},
-									// We already filtered this out
-									TextToken::None => unreachable!(),
-								}
-							})
-							.collect::<Vec<Span>>(),
-					)))
+			titles.push(ListItem::new(spaced(header(kind.name()))));
+			info.push((
+				Paragraph::new(
+					["Select to see all of their ", &kind.name().to_lowercase()].concat(),
+				),
+				Node::Videos {
+					login: channel.login.clone(),
+					kind,
 				},
-			);
+			));
 
-			info.push((Paragraph::new(Text { lines: Vec::new() }), Node::None));
+			for video in videos {
+				add_video(video, &mut titles, &mut info);
+			}
+		}
 
-			for edge in edge.node.content.edges {
-				// Category items
-				let (title, lines) = match &edge.node {
-					Node::Clip {
-						clipTitle,
-						clipViewCount,
-						curator: User {
-							displayName: curator_display_name,
-							..
-						},
-						game:
-							Game {
-								displayName: game_display_name,
-								name: game_name,
-								..
-							},
-						broadcaster:
-							User {
-								displayName: broadcaster_display_name,
-								..
-							},
-						clipCreatedAt,
-						durationSeconds,
-						language,
-						..
-					} => (
-						clipTitle.clone(),
-						vec![
-							clipTitle.clone().into(),
-							"".into(),
-							["Views: ", &clipViewCount.to_string()].concat().into(),
-							["Curator: ", curator_display_name].concat().into(),
-							[
-								"Game: ",
-								&game_display_name.clone().unwrap_or(game_name.clone()),
-							]
-							.concat()
-							.into(),
-							["Broadcaster: ", broadcaster_display_name].concat().into(),
-							["Clip created: ", &format_date(clipCreatedAt)]
-								.concat()
-								.into(),
-							["Duration: ", &durationSeconds.to_string(), "s"]
-								.concat()
-								.into(),
-							["Language: ", language].concat().into(),
-						],
-					),
-					Node::Game(Game {
-						viewersCount,
-						displayName,
-						name,
-						gameTags,
-						originalReleaseDate,
-						..
-					}) => {
-						let mut lines = vec![
-							displayName.clone().unwrap_or(name.clone()).into(),
-							"".into(),
-						];
-
-						if let Some(viewers_count) = viewersCount {
-							lines.push(["Viewers: ", &viewers_count.to_string()].concat().into());
-						}
+		// Clips, selecting the header shows all of them
+		if !self.clips.is_empty() {
+			titles.push(ListItem::new(spaced(header("Clips"))));
+			info.push((
+				Paragraph::new("Select to see all of their clips"),
+				Node::Clips(ClipSource::Channel(channel.login.clone())),
+			));
+		}
 
-						if let Some(game_tags) = gameTags {
-							lines.push(
-								[
-									"Tags: ",
-									&game_tags
-										.iter()
-										.map(|tag| tag.localizedName.clone())
-										.collect::<Vec<String>>()
-										.join(", "),
-								]
-								.concat()
-								.into(),
-							)
-						}
+		for clip in self.clips {
+			add_clip(clip, &mut titles, &mut info);
+		}
 
-						if let Some(original_release_date) = originalReleaseDate {
-							lines.push(
-								["Released: ", &format_date(original_release_date)]
-									.concat()
-									.into(),
-							)
-						}
+		// Only the first few segments of the schedule, like the rest of the page
+		if !channel.schedule.is_empty() {
+			titles.push(ListItem::new(spaced(header("Next scheduled streams"))));
+			info.push((Paragraph::new(Text { lines: Vec::new() }), Node::None));
+		}
 
-						(displayName.clone().unwrap_or(name.clone()).clone(), lines)
-					}
-					Node::Stream {
-						broadcaster:
-							User {
-								displayName: broadcaster_display_name,
-								broadcastSettings: Some(BroadcastSettings { title }),
-								..
-							},
-						game,
-						freeformTags,
-						viewersCount,
-						createdAt,
-					} => {
-						let mut infos = vec![
-							title.clone().into(),
-							"".into(),
-							broadcaster_display_name.clone().into(),
-							[
-								"Tags: ",
-								&freeformTags
-									.iter()
-									.map(|tag| tag.name.clone())
-									.collect::<Vec<String>>()
-									.join(", "),
-							]
-							.concat()
-							.into(),
-							["Viewers: ", &viewersCount.to_string()].concat().into(),
-						];
-
-						if let Some(
-							Game {
-								displayName: Some(name),
-								..
-							}
-							| Game { name, .. },
-						) = game
-						{
-							infos.push(["Game: ", name].concat().into());
-						}
+		for segment in &channel.schedule {
+			titles.push(ListItem::new(spaced(
+				[
+					&segment
+						.starts_at
+						.as_ref()
+						.map_or("tbd".to_owned(), format_date),
+					": ",
+					&segment.title,
+				]
+				.concat(),
+			)));
 
-						if let Some(created_at) = createdAt {
-							infos.push(["Created: ", &format_date(created_at)].concat().into());
-						}
+			let mut lines = vec![segment.title.clone().into(), "".into()];
+			lines.extend(segment_times(segment));
 
-						(broadcaster_display_name.clone(), infos)
-					}
-					_ => continue,
-				};
-
-				titles.push(ListItem::new(spaced(title)));
-				info.push((
-					Paragraph::new(Text { lines }).wrap(Wrap { trim: false }),
-					edge.node,
-				));
-			}
+			info.push((
+				Paragraph::new(Text { lines }).wrap(Wrap { trim: false }),
+				// Can't watch the future (yet)
+				Node::None,
+			));
 		}
 
-		(
-			List::new(titles).highlight_style(Style {
-				add_modifier: Modifier::REVERSED,
-				..Style::default()
-			}),
-			info,
-		)
-	}
-}
+		// About
+		titles.push(ListItem::new(spaced(header("About"))));
+		info.push((Paragraph::new(Text { lines: Vec::new() }), Node::None));
 
-#[derive(Deserialize)]
-pub struct GameData {
-	game: Category,
-}
-impl Data for GameData {
-	fn into_widgets<'a>(self) -> (List<'a>, Vec<(Paragraph<'a>, Node)>) {
-		let mut titles = Vec::new();
-		let mut info = Vec::new();
+		titles.push(ListItem::new(spaced(channel.display_name.clone())).style(style));
 
-		for edge in self.game.streams.edges {
-			let style = edge.node.broadcaster.style();
+		let mut lines = vec![channel.display_name.into(), "".into()];
 
-			titles.push(
-				ListItem::new(spaced(edge.node.broadcaster.displayName.clone())).style(style),
-			);
-			info.push((
-				Paragraph::new(Text {
-					lines: vec![
-						edge.node.title.into(),
-						"".into(),
-						edge.node.broadcaster.displayName.clone().into(),
-						["Viewers: ", &edge.node.viewersCount.to_string()]
-							.concat()
-							.into(),
-						[
-							"Game: ",
-							&edge.node.game.displayName.unwrap_or(edge.node.game.name),
-						]
-						.concat()
-						.into(),
-						["Created: ", &format_date(&edge.node.createdAt)]
-							.concat()
-							.into(),
-						[
-							"Tags: ",
-							&edge
-								.node
-								.freeformTags
-								.iter()
-								.map(|tag| tag.name.clone())
-								.collect::<Vec<String>>()
-								.join(", "),
-						]
-						.concat()
-						.into(),
-					],
-				})
+		if let Some(followers) = channel.followers {
+			lines.push(["Followers: ", &followers.to_string()].concat().into());
+		}
+
+		lines.push(["Partner: ", yes_no(channel.partner)].concat().into());
+
+		if let Some(description) = channel.description {
+			lines.extend(["".into(), description.into()]);
+		}
+
+		info.push((
+			Paragraph::new(Text { lines })
 				.style(style)
 				.wrap(Wrap { trim: false }),
-				edge.node.broadcaster.as_node(),
-			));
-		}
+			Node::None,
+		));
 
-		(
-			List::new(titles).highlight_style(Style {
-				add_modifier: Modifier::REVERSED,
-				..Style::default()
-			}),
+		Widgets {
+			items: titles,
 			info,
-		)
+			more: Vec::new(),
+		}
 	}
 }
 
-#[derive(Deserialize)]
-pub struct SearchForData {
-	searchFor: SearchFor,
-}
-impl Data for SearchForData {
-	fn into_widgets<'a>(self) -> (List<'a>, Vec<(Paragraph<'a>, Node)>) {
-		let mut titles = Vec::new();
-		let mut info = Vec::new();
+#[cfg(test)]
+mod tests {
+	use std::path::Path;
 
-		// We need to add these later in the right order, based on score
-		// We can't do `[_; 5]` because tuples don't implement `Copy`
-		let mut items_to_add = [
-			(Vec::new(), Vec::new()),
-			(Vec::new(), Vec::new()),
-			(Vec::new(), Vec::new()),
-			(Vec::new(), Vec::new()),
-			(Vec::new(), Vec::new()),
-		];
-
-		if !self.searchFor.channels.edges.is_empty() {
-			items_to_add[self.searchFor.channels.score - 1]
-				.0
-				.push(header("Channels"));
-
-			items_to_add[self.searchFor.channels.score - 1].1.push((
-				Paragraph::new(
-					[
-						"Total matches: ",
-						&self.searchFor.channels.totalMatches.to_string(),
-					]
-					.concat(),
-				),
-				Node::None,
-			));
+	use twitch_curses::mock::Options;
+	use twitch_curses::{gql, ClientOptions};
 
-			for edge in self.searchFor.channels.edges {
-				edge.item
-					.add_items_to(&mut items_to_add[self.searchFor.channels.score - 1]);
-			}
-		}
-		if !self.searchFor.channelsWithTag.edges.is_empty() {
-			items_to_add[self.searchFor.channelsWithTag.score - 1]
-				.0
-				.push(header("Live channels with tag"));
-
-			items_to_add[self.searchFor.channelsWithTag.score - 1]
-				.1
-				.push((
-					Paragraph::new(
-						[
-							"Total matches: ",
-							&self.searchFor.channelsWithTag.totalMatches.to_string(),
-						]
-						.concat(),
-					),
-					Node::None,
-				));
-
-			for edge in self.searchFor.channelsWithTag.edges {
-				edge.item
-					.add_items_to(&mut items_to_add[self.searchFor.channelsWithTag.score - 1]);
-			}
-		}
-		if !self.searchFor.games.edges.is_empty() {
-			items_to_add[self.searchFor.games.score - 1]
-				.0
-				.push(header("Categories"));
+	use super::*;
+	use crate::fixtures::{with_mode, Mode};
+	use crate::request::parse_response;
 
-			items_to_add[self.searchFor.games.score - 1].1.push((
-				Paragraph::new(
-					[
-						"Total matches: ",
-						&self.searchFor.games.totalMatches.to_string(),
-					]
-					.concat(),
-				),
-				Node::None,
-			));
+	/// Replays `request` from the recordings in `tests/fixtures`, and makes it's widgets.
+	fn replay<'a, T: Data, F: FnOnce(&mut Client) -> Result<T, Error>>(request: F) -> Widgets<'a> {
+		let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+		let mut client =
+			Client::new(ClientOptions::default()).expect("Should be able to make a client");
 
-			for edge in self.searchFor.games.edges {
-				items_to_add[self.searchFor.games.score - 1].0.push(
-					edge.item
-						.displayName
-						.unwrap_or(edge.item.name.clone())
-						.into(),
-				);
+		with_mode(Mode::Replay(dir), || request(&mut client))
+			.expect("Should have a recording that parses")
+			.into_widgets()
+	}
 
-				let mut lines = Vec::new();
+	/// Logins of the streams in `widgets`, in order
+	fn stream_logins(widgets: &Widgets) -> Vec<String> {
+		widgets
+			.info
+			.iter()
+			.filter_map(|(_, node)| match node {
+				Node::Stream { login, .. } => Some(login.clone()),
+				_ => None,
+			})
+			.collect()
+	}
 
-				if let Some(viewers_count) = edge.item.viewersCount {
-					lines.push(["Viewers: ", &viewers_count.to_string()].concat().into());
-				}
+	/// Each line's text, without it's style
+	fn text(lines: &[Spans]) -> Vec<String> {
+		lines
+			.iter()
+			.map(|line| line.0.iter().map(|span| span.content.as_ref()).collect())
+			.collect()
+	}
 
-				if let Some(tags) = edge.item.gameTags {
-					lines.push(
-						[
-							"Tags: ",
-							&tags
-								.into_iter()
-								.map(|tag| tag.localizedName)
-								.collect::<Vec<String>>()
-								.join(", "),
-						]
-						.concat()
-						.into(),
-					);
-				}
+	#[test]
+	fn clips_without_curator_or_game() {
+		let mut body = include_bytes!("mock/ClipsPage.more.json").to_vec();
+		let data = parse_response::<gql::ClipsPageData>("ClipsPage", &mut body)
+			.expect("Should parse clips without a curator or game");
+		let clips: Page<Clip> = data.user.expect("Should be a channel").clips.into();
+
+		let clip = &clips.items[0];
+		assert!(clip.curator.is_none());
+		assert!(clip.category.is_none());
+
+		let lines = text(&clip_lines(clip));
+		assert!(lines.contains(&"Curator: deleted account".to_owned()));
+		assert!(lines.contains(&"Game: none".to_owned()));
+
+		let widgets = clips.into_widgets();
+		assert_eq!(widgets.info.len(), 1);
+		assert!(matches!(
+			&widgets.info[0].1,
+			Node::Clip(clip) if clip.slug == "MockClipSlug3"
+		));
+		assert_eq!(widgets.info[0].1.channel(), None);
+	}
 
-				items_to_add[self.searchFor.games.score - 1].1.push((
-					Paragraph::new(lines).wrap(Wrap { trim: false }),
-					Node::Game(Game {
-						viewersCount: None,
-						name: edge.item.name,
-						displayName: None,
-						gameTags: None,
-						originalReleaseDate: None,
-					}),
-				));
-			}
-		}
-		if !self.searchFor.videos.edges.is_empty() {
-			items_to_add[self.searchFor.videos.score - 1]
-				.0
-				.push(header("Past videos"));
+	#[test]
+	fn replays_personal_sections() {
+		let widgets = replay(|client| {
+			client.personal_sections(
+				&config().requests.personal_sections,
+				config().requests.context_channel_name.as_deref(),
+			)
+		});
+
+		assert_eq!(widgets.items.len(), 3);
+		assert_eq!(stream_logins(&widgets), ["mockstreamer", "quietstreamer"]);
+	}
 
-			items_to_add[self.searchFor.videos.score - 1].1.push((
-				Paragraph::new(
-					[
-						"Total matches: ",
-						&self.searchFor.videos.totalMatches.to_string(),
-					]
-					.concat(),
-				),
-				Node::None,
-			));
+	#[test]
+	fn replays_shelves() {
+		let widgets = replay(Client::shelves);
+
+		assert_eq!(widgets.items.len(), 7);
+		assert_eq!(stream_logins(&widgets), ["mockstreamer"]);
+		assert!(widgets.info.iter().any(|(_, node)| matches!(
+			node,
+			Node::Clip(clip) if clip.slug == "MockClipSlug"
+				&& clip.curator.as_ref().is_some_and(|curator| curator.login == "clipper")
+		)));
+
+		let games: Vec<_> = widgets
+			.info
+			.iter()
+			.filter_map(|(_, node)| node.game())
+			.collect();
+		assert_eq!(games, ["Just Chatting", "Minecraft"]);
+	}
 
-			for edge in self.searchFor.videos.edges {
-				items_to_add[self.searchFor.videos.score - 1]
-					.0
-					.push(edge.item.title.into());
+	#[test]
+	fn replays_game() {
+		let widgets = replay(|client| {
+			client.category_streams("Just Chatting", &config().requests.stream_options(), None)
+		});
 
-				let mut lines = vec![
-					edge.item.owner.displayName.into(),
-					"".into(),
-					["Created: ", &format_date(&edge.item.createdAt)]
-						.concat()
-						.into(),
-					[
-						"Game: ",
-						&edge.item.game.displayName.unwrap_or(edge.item.game.name),
-					]
-					.concat()
-					.into(),
-					["Length: ", &edge.item.lengthSeconds.to_string(), " s"]
-						.concat()
-						.into(),
-					["Views: ", &edge.item.viewCount.to_string()]
-						.concat()
-						.into(),
-				];
-
-				if let Some(roles) = edge.item.owner.roles {
-					lines.push(
-						["Partner: ", if roles.isPartner { "Yes" } else { "No" }]
-							.concat()
-							.into(),
-					);
-				}
+		let logins = stream_logins(&widgets);
+		assert_eq!(logins.len(), 10);
+		assert_eq!(logins[0], "mockstreamer1");
 
-				items_to_add[self.searchFor.videos.score - 1].1.push((
-					Paragraph::new(lines).wrap(Wrap { trim: false }),
-					Node::Video(edge.item.id),
-				));
-			}
-		}
+		// There's another page after the last stream
+		assert!(matches!(widgets.more[..], [(10, More::Streams(_))]));
+	}
 
-		if !self.searchFor.relatedLiveChannels.edges.is_empty() {
-			items_to_add[self.searchFor.relatedLiveChannels.score - 1]
-				.0
-				.push(header("People searching also watch:"));
+	#[test]
+	fn replays_search() {
+		let widgets = replay(|client| client.search("mock"));
+
+		assert_eq!(widgets.items.len(), 11);
+		assert_eq!(stream_logins(&widgets), ["mockstreamer", "quietstreamer"]);
+		assert!(widgets
+			.info
+			.iter()
+			.any(|(_, node)| matches!(node, Node::Channel(login) if login == "quietstreamer")));
+		assert!(widgets
+			.info
+			.iter()
+			.any(|(_, node)| matches!(node, Node::Video(id) if id == "6001")));
+		assert!(widgets
+			.info
+			.iter()
+			.any(|(_, node)| node.game() == Some("Just Chatting")));
+	}
 
-			items_to_add[self.searchFor.relatedLiveChannels.score - 1]
-				.1
-				.push((Paragraph::new(""), Node::None));
+	/// A client for new mock servers that behave normally
+	fn mock_client() -> Client {
+		client_for(&mock(Options::default()))
+	}
 
-			for edge in self.searchFor.relatedLiveChannels.edges {
-				let style = edge.item.stream.broadcaster.style();
-				let node = edge.item.stream.broadcaster.as_node();
+	/// Resolves `node` against new mock servers.
+	fn resolve(node: &Node, qualities: &[&str]) -> Result<Selected, Error> {
+		node.resolve(&mut mock_client(), &Progress::default(), qualities)
+	}
 
-				items_to_add[self.searchFor.relatedLiveChannels.score - 1]
-					.0
-					.push(Span {
-						content: edge.item.stream.broadcaster.displayName.clone().into(),
-						style,
-					});
+	#[test]
+	fn resolves_clips() {
+		let widgets = mock_client()
+			.channel_clips("mockstreamer", None)
+			.expect("Should be able to list clips")
+			.into_widgets();
+		let (_, clip) = widgets
+			.info
+			.iter()
+			.find(|(_, node)| matches!(node, Node::Clip(_)))
+			.expect("Should have a clip");
+
+		let Ok(Selected::Play(url)) = resolve(clip, &["480p"]) else {
+			panic!("The clip should be playable");
+		};
+		assert!(url.contains("/media/clip-480.mp4?sig=mocksignature&token="));
 
-				let mut lines = Vec::new();
+		// Best quality is the default
+		let Ok(Selected::Play(url)) = resolve(clip, &["360p"]) else {
+			panic!("The clip should be playable");
+		};
+		assert!(url.contains("/media/clip-1080.mp4?"));
+	}
 
-				if let Some(broadcast_settings) = edge.item.stream.broadcaster.broadcastSettings {
-					lines.extend([broadcast_settings.title.into(), "".into()]);
-				}
+	#[test]
+	fn resolves_videos() {
+		let video = Node::Video("6001".to_owned());
 
-				lines.extend([
-					["Viewers: ", &edge.item.stream.viewersCount.to_string()]
-						.concat()
-						.into(),
-					["Game: ", &edge.item.stream.game.name].concat().into(),
-				]);
-
-				if let Some(roles) = edge.item.stream.broadcaster.roles {
-					lines.push(
-						["Partner: ", if roles.isPartner { "Yes" } else { "No" }]
-							.concat()
-							.into(),
-					);
-				}
+		let Ok(Selected::Play(url)) = resolve(&video, &["720p"]) else {
+			panic!("The VOD should be playable");
+		};
+		assert!(url.ends_with("/media/720p60.m3u8"));
 
-				items_to_add[self.searchFor.relatedLiveChannels.score - 1]
-					.1
-					.push((
-						Paragraph::new(lines)
-							.style(style)
-							.wrap(Wrap { trim: false }),
-						node,
-					));
-			}
-		}
+		let Ok(Selected::Play(url)) = resolve(&video, &["worst"]) else {
+			panic!("The VOD should be playable");
+		};
+		assert!(url.ends_with("/media/audio_only.m3u8"));
+	}
 
-		// Add the sections in score order
-		for items in items_to_add {
-			titles.extend(items.0.into_iter().map(|span| {
-				ListItem::new(spaced(span.clone())).style(Style {
-					fg: span.style.fg,
-					..Style::default()
-				})
-			}));
-			info.extend(items.1);
-		}
+	#[test]
+	fn resolve_reports_errors() {
+		let Err(error) = resolve(&Node::Video("error".to_owned()), &["best"]) else {
+			panic!("The mock should fail this request");
+		};
+		assert_eq!(error.operation, "PlaybackAccessToken");
+		assert!(matches!(error.kind, ErrorKind::Gql(_)));
 
-		(
-			List::new(titles).highlight_style(Style {
-				add_modifier: Modifier::REVERSED,
-				..Style::default()
-			}),
-			info,
-		)
+		let Err(error) = resolve(&Node::Video("http-error".to_owned()), &["best"]) else {
+			panic!("The mock should fail this request");
+		};
+		assert_eq!(error.status, Some(500));
 	}
-}
 
-/// Response from the `PersonalSections` API call.
-#[derive(Deserialize, Debug)]
-pub struct TwitchResponse<D: Data> {
-	pub data: D,
-	// Ignore `extensions`
+	#[test]
+	fn resolves_pages_without_requests() {
+		// The client has nowhere to send requests, so these would fail if they sent anything
+		let mut client = Client::new(ClientOptions {
+			gql: String::new(),
+			usher: String::new(),
+			..ClientOptions::default()
+		})
+		.expect("Should be able to make a client");
+		let mut resolve = |node: Node| node.resolve(&mut client, &Progress::default(), &[]);
+
+		assert!(matches!(
+			resolve(Node::Channel("mockstreamer".to_owned())),
+			Ok(Selected::Channel(login)) if login == "mockstreamer"
+		));
+		assert!(matches!(
+			resolve(Node::Clips(ClipSource::Category("Minecraft".to_owned()))),
+			Ok(Selected::Clips(ClipSource::Category(name))) if name == "Minecraft"
+		));
+		assert!(matches!(resolve(Node::None), Ok(Selected::None)));
+	}
 }
@@ -0,0 +1,353 @@
+//! Colours and styles used by the UI.
+//!
+//! A theme is one of the built in ones (`dark` or `light`), with any of it's styles overridden by
+//! the `theme` section of the config. The current theme can be accessed from anywhere with
+//! [`theme()`].
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+use crate::config::config;
+
+/// The theme built from the config, see [`theme()`]
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// Get the current theme.
+pub fn theme() -> &'static Theme {
+	THEME.get_or_init(|| {
+		Theme::from_config(&config().theme)
+			.expect("Theme should have been validated with the config")
+	})
+}
+
+/// Theme section of the config.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ThemeConfig {
+	/// Built in theme to start from, `"dark"` or `"light"`
+	pub name: String,
+	/// Styles to change, by name, i.e. `{"highlight": {"fg": "black", "bg": "#ffaa00"}}`.
+	/// Badges are under `badges.`, i.e. `badges.moderator`.
+	pub styles: HashMap<String, StyleConfig>,
+}
+impl Default for ThemeConfig {
+	fn default() -> Self {
+		Self {
+			name: "dark".to_owned(),
+			styles: HashMap::new(),
+		}
+	}
+}
+
+/// A style from the config. Anything that isn't given is left the same as the built in theme.
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct StyleConfig {
+	/// Foreground colour, a name (`"red"`, `"lightblue"`), a hex code (`"#ff0000"`) or a
+	/// 256-colour index (`"123"`)
+	pub fg: Option<String>,
+	/// Background colour, same as `fg`
+	pub bg: Option<String>,
+	/// Modifiers to add, i.e. `["bold", "underlined"]`
+	pub modifiers: Vec<String>,
+	/// Modifiers to remove from the built in style
+	pub remove_modifiers: Vec<String>,
+}
+impl StyleConfig {
+	/// Converts this to a [`Style`] that can be patched over the built in one.
+	fn to_style(&self) -> Result<Style, String> {
+		Ok(Style {
+			fg: self.fg.as_deref().map(parse_colour_name).transpose()?,
+			bg: self.bg.as_deref().map(parse_colour_name).transpose()?,
+			add_modifier: parse_modifiers(&self.modifiers)?,
+			sub_modifier: parse_modifiers(&self.remove_modifiers)?,
+			..Style::default()
+		})
+	}
+}
+
+/// Parses a colour from the config.
+fn parse_colour_name(string: &str) -> Result<Color, String> {
+	Ok(match &*string.to_lowercase() {
+		"reset" => Color::Reset,
+		"black" => Color::Black,
+		"red" => Color::Red,
+		"green" => Color::Green,
+		"yellow" => Color::Yellow,
+		"blue" => Color::Blue,
+		"magenta" => Color::Magenta,
+		"cyan" => Color::Cyan,
+		"gray" | "grey" => Color::Gray,
+		"darkgray" | "darkgrey" => Color::DarkGray,
+		"lightred" => Color::LightRed,
+		"lightgreen" => Color::LightGreen,
+		"lightyellow" => Color::LightYellow,
+		"lightblue" => Color::LightBlue,
+		"lightmagenta" => Color::LightMagenta,
+		"lightcyan" => Color::LightCyan,
+		"white" => Color::White,
+		hex if hex.len() == 7 && hex.starts_with('#') => {
+			let parsed = u32::from_str_radix(&hex[1..], 16)
+				.map_err(|_| ["Invalid hex colour `", string, "`"].concat())?;
+
+			Color::Rgb((parsed >> 16) as u8, (parsed >> 8) as u8, parsed as u8)
+		}
+		index => Color::Indexed(
+			index
+				.parse()
+				.map_err(|_| ["Unknown colour `", string, "`"].concat())?,
+		),
+	})
+}
+
+/// Parses a list of modifier names from the config.
+fn parse_modifiers(names: &[String]) -> Result<Modifier, String> {
+	let mut modifiers = Modifier::empty();
+
+	for name in names {
+		modifiers |= match &*name.to_lowercase() {
+			"bold" => Modifier::BOLD,
+			"dim" => Modifier::DIM,
+			"italic" => Modifier::ITALIC,
+			"underlined" => Modifier::UNDERLINED,
+			"slow_blink" => Modifier::SLOW_BLINK,
+			"rapid_blink" => Modifier::RAPID_BLINK,
+			"reversed" => Modifier::REVERSED,
+			"hidden" => Modifier::HIDDEN,
+			"crossed_out" => Modifier::CROSSED_OUT,
+			_ => return Err(["Unknown modifier `", name, "`"].concat()),
+		}
+	}
+
+	Ok(modifiers)
+}
+
+/// Shorthand for a style with just colours
+fn colours(fg: Color, bg: Color) -> Style {
+	Style {
+		fg: Some(fg),
+		bg: Some(bg),
+		..Style::default()
+	}
+}
+
+/// Shorthand for a style with just a foreground colour
+fn fg(fg: Color) -> Style {
+	Style {
+		fg: Some(fg),
+		..Style::default()
+	}
+}
+
+/// Shorthand for a style with just modifiers
+fn modifiers(add_modifier: Modifier) -> Style {
+	Style {
+		add_modifier,
+		..Style::default()
+	}
+}
+
+/// Styles for chat badges
+pub struct BadgeTheme {
+	/// Verified, ✓
+	pub partner: Style,
+	/// Blue prediction
+	pub predictions_blue: Style,
+	/// Pink prediction
+	pub predictions_pink: Style,
+	/// Twitch premium, 👑
+	pub premium: Style,
+	/// 🗡️
+	pub moderator: Style,
+	/// 📷, for under 20, 60, 120, 200 and over 200 moments
+	pub moments: [Style; 5],
+	/// Watching without audio, 🔇
+	pub no_audio: Style,
+	/// Listening without video, 👁
+	pub no_video: Style,
+	/// 🎁, for 1, 5, 10, 25, 50, 100 and more gifted subs
+	pub sub_gifter: [Style; 7],
+	/// `sub/{months}`
+	pub subscriber: Style,
+	/// 💎
+	pub vip: Style,
+}
+
+/// Every style used by the UI
+pub struct Theme {
+	/// Selected list item and chat tab
+	pub highlight: Style,
+	/// Section headers in lists, from `header()`
+	pub header: Style,
+	/// Borders around panels
+	pub border: Style,
+	/// Panel titles
+	pub title: Style,
+	/// Help text in the bottom right
+	pub help: Style,
+	/// Viewer counter in chat
+	pub viewers: Style,
+	/// Chat notices, i.e. bans and subscriptions
+	pub notice: Style,
+	/// Lines in the chat log tab
+	pub log: Style,
+	/// Border and title of error popups
+	pub error: Style,
+	/// Filled part of the status bar gauge, while something loads
+	pub progress: Style,
+	pub badges: BadgeTheme,
+}
+impl Theme {
+	/// The default theme, for dark terminals
+	pub fn dark() -> Self {
+		Self {
+			highlight: modifiers(Modifier::REVERSED),
+			header: modifiers(Modifier::UNDERLINED),
+			border: Style::default(),
+			title: Style::default(),
+			help: Style::default(),
+			viewers: fg(Color::Red),
+			notice: Style::default(),
+			log: Style::default(),
+			error: fg(Color::Red),
+			progress: fg(Color::Magenta),
+			badges: BadgeTheme {
+				partner: colours(Color::White, Color::Magenta),
+				predictions_blue: fg(Color::Blue),
+				predictions_pink: fg(Color::Magenta),
+				premium: colours(Color::White, Color::Blue),
+				moderator: colours(Color::White, Color::Green),
+				moments: [
+					// Bronze (#CD7F32)
+					colours(Color::White, Color::Rgb(0xCD, 0x7F, 0x32)),
+					// Silver (#C0C0C0)
+					colours(Color::White, Color::Rgb(0xC0, 0xC0, 0xC0)),
+					// Gold (#FFD700)
+					colours(Color::White, Color::Rgb(0xFF, 0xD7, 0x00)),
+					// Diamond (#B9F2FF)
+					colours(Color::White, Color::Rgb(0xB9, 0xF2, 0xFF)),
+					// Purple (#800080)
+					colours(Color::White, Color::Rgb(0x80, 0x00, 0x80)),
+				],
+				no_audio: colours(Color::White, Color::Black),
+				no_video: Style {
+					add_modifier: Modifier::CROSSED_OUT,
+					..colours(Color::White, Color::Black)
+				},
+				// These are meant to be the same as the official colours
+				sub_gifter: [
+					fg(Color::Magenta),
+					fg(Color::Cyan),
+					fg(Color::Blue),
+					fg(Color::Red),
+					fg(Color::LightMagenta),
+					fg(Color::Green),
+					fg(Color::Yellow),
+				],
+				subscriber: Style::default(),
+				vip: colours(Color::White, Color::LightMagenta),
+			},
+		}
+	}
+
+	/// For light terminals, mostly the same but with darker text where the background would be
+	/// too close to it
+	pub fn light() -> Self {
+		let dark = Self::dark();
+
+		Self {
+			header: modifiers(Modifier::UNDERLINED | Modifier::BOLD),
+			notice: fg(Color::DarkGray),
+			log: fg(Color::DarkGray),
+			badges: BadgeTheme {
+				moments: [
+					dark.badges.moments[0],
+					// Silver and diamond are too light for white text
+					colours(Color::Black, Color::Rgb(0xC0, 0xC0, 0xC0)),
+					colours(Color::Black, Color::Rgb(0xFF, 0xD7, 0x00)),
+					colours(Color::Black, Color::Rgb(0xB9, 0xF2, 0xFF)),
+					dark.badges.moments[4],
+				],
+				no_audio: colours(Color::Black, Color::Gray),
+				no_video: Style {
+					add_modifier: Modifier::CROSSED_OUT,
+					..colours(Color::Black, Color::Gray)
+				},
+				sub_gifter: [
+					dark.badges.sub_gifter[0],
+					fg(Color::Blue),
+					dark.badges.sub_gifter[2],
+					dark.badges.sub_gifter[3],
+					fg(Color::Magenta),
+					dark.badges.sub_gifter[5],
+					// Yellow is hard to read on white
+					fg(Color::Rgb(0xB0, 0x8A, 0x00)),
+				],
+				..dark.badges
+			},
+			..dark
+		}
+	}
+
+	/// Builds the theme described by the config.
+	pub fn from_config(theme_config: &ThemeConfig) -> Result<Self, String> {
+		let mut theme = match &*theme_config.name {
+			"dark" => Self::dark(),
+			"light" => Self::light(),
+			name => return Err(["Unknown theme `", name, "`"].concat()),
+		};
+
+		for (name, style_config) in &theme_config.styles {
+			let style = theme
+				.style_mut(name)
+				.ok_or_else(|| ["Unknown style `", name, "` in `theme.styles`"].concat())?;
+
+			*style = style.patch(
+				style_config
+					.to_style()
+					.map_err(|e| [&e, " in `theme.styles.", name, "`"].concat())?,
+			);
+		}
+
+		Ok(theme)
+	}
+
+	/// Gets a style by it's name in the config
+	fn style_mut(&mut self, name: &str) -> Option<&mut Style> {
+		Some(match name {
+			"highlight" => &mut self.highlight,
+			"header" => &mut self.header,
+			"border" => &mut self.border,
+			"title" => &mut self.title,
+			"help" => &mut self.help,
+			"viewers" => &mut self.viewers,
+			"notice" => &mut self.notice,
+			"log" => &mut self.log,
+			"error" => &mut self.error,
+			"progress" => &mut self.progress,
+			"badges.partner" => &mut self.badges.partner,
+			"badges.predictions_blue" => &mut self.badges.predictions_blue,
+			"badges.predictions_pink" => &mut self.badges.predictions_pink,
+			"badges.premium" => &mut self.badges.premium,
+			"badges.moderator" => &mut self.badges.moderator,
+			"badges.no_audio" => &mut self.badges.no_audio,
+			"badges.no_video" => &mut self.badges.no_video,
+			"badges.subscriber" => &mut self.badges.subscriber,
+			"badges.vip" => &mut self.badges.vip,
+			_ => {
+				// Tiered badges, i.e. `badges.moments.0`
+				let (badge, tier) = name.strip_prefix("badges.")?.split_once('.')?;
+				let tier = tier.parse::<usize>().ok()?;
+
+				match badge {
+					"moments" => self.badges.moments.get_mut(tier)?,
+					"sub_gifter" => self.badges.sub_gifter.get_mut(tier)?,
+					_ => return None,
+				}
+			}
+		})
+	}
+}
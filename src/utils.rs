@@ -1,33 +1,213 @@
 //! Useful functions that are used in multiple files in the program
 
-use std::io::Read;
+use std::io::stdout;
+use std::path::PathBuf;
+use std::process::Command;
+use std::{env, fs, io};
 
+use crossterm::event::{read, Event, KeyCode, KeyEvent};
+use crossterm::execute;
+use crossterm::terminal::{
+	disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
 use curl::easy::Easy;
+use ratatui::backend::Backend;
+use ratatui::layout::Rect;
 use ratatui::style::Color;
+use ratatui::text::Span;
+use ratatui::widgets::{Block, Borders, Paragraph};
+use ratatui::Terminal;
+use serde::de::DeserializeOwned;
 use serde::Serialize;
-use simd_json::to_vec;
+use simd_json::{from_slice, to_vec_pretty};
+use twitch_curses::{Client, ClientOptions};
 
-/// Send a request and return it as a `Vec<u8>`.
-pub fn request<J: Serialize + ?Sized>(easy: &mut Easy, json: &J) -> Vec<u8> {
-	let mut data = &*to_vec(json).expect("Should be able to serialize POST data");
+use crate::auth::token;
+use crate::config::{config, Config};
+use crate::error::{Error, ErrorKind};
+use crate::fixtures;
+use crate::log::warn;
+use crate::request::{check_status, response_code};
+use crate::theme::theme;
+use crate::worker::Progress;
 
-	let mut vec = Vec::new();
+/// Creates a client with the endpoints, headers and page sizes from the config and the login
+/// token.
+pub fn client() -> Client {
+	client_for(config())
+}
+
+/// Creates a client with the endpoints, headers and page sizes from `config` and the login token.
+pub fn client_for(config: &Config) -> Client {
+	Client::new(ClientOptions {
+		gql: config.endpoints.gql.clone(),
+		usher: config.endpoints.usher.clone(),
+		headers: config.headers.clone(),
+		token: token().map(|token| token.access_token.clone()),
+		page_size: config.requests.game_limit,
+		video_page_size: config.requests.video_limit,
+		clip_page_size: config.requests.clip_limit,
+	})
+	.expect("Should be able to set up a curl handle")
+}
+
+/// Send a GET request to `url` and return the body, reporting to `progress` while it downloads.
+pub fn get(url: &str, operation: &str, progress: &Progress) -> Result<Vec<u8>, Error> {
+	let (status, body) = fixtures::serve(operation, url.as_bytes(), || {
+		let mut easy = Easy::new();
+		easy.url(url)
+			.map_err(|e| Error::new(operation, ErrorKind::Curl(e)))?;
+		progress.track(&mut easy);
+
+		// Make sure `vec` lives longer than `transfer`
+		let mut vec = Vec::new();
+
+		{
+			let mut transfer = easy.transfer();
+
+			let _ = transfer.write_function(|slice| {
+				vec.extend_from_slice(slice);
+				Ok(slice.len())
+			});
+
+			transfer
+				.perform()
+				.map_err(|e| Error::new(operation, ErrorKind::Curl(e)))?;
+		}
+
+		Ok((response_code(&mut easy, operation)?, vec))
+	})?;
+
+	check_status(operation, status, body).map(|(_, body)| body)
+}
+
+/// Runs a program in the normal terminal, going back to the UI when it exits.
+pub fn run_outside_ui(command: &mut Command) -> Result<(), Error> {
+	let _ = disable_raw_mode();
+	// We want to be in a normal terminal
+	let _ = execute!(stdout(), LeaveAlternateScreen);
+
+	let result = command.spawn().and_then(|mut child| child.wait());
+
+	let _ = enable_raw_mode();
+	let _ = execute!(stdout(), EnterAlternateScreen);
+
+	result.map(|_| ()).map_err(|e| {
+		Error::new(
+			["Starting ", &command.get_program().to_string_lossy()].concat(),
+			ErrorKind::Io(e),
+		)
+	})
+}
+
+/// Where things the program saves go, `$XDG_DATA_HOME/twitch-tui` (or
+/// `~/.local/share/twitch-tui`). `None` if neither variable is set.
+pub fn data_dir() -> Option<PathBuf> {
+	env::var_os("XDG_DATA_HOME")
+		.filter(|dir| !dir.is_empty())
+		.map(PathBuf::from)
+		.or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+		.map(|dir| dir.join("twitch-tui"))
+}
 
-	// Make sure `transfer` is dropped before we use can `vec` again
-	{
-		let mut transfer = easy.transfer();
+/// Reads the file called `name` in [`data_dir`]. `None` if it isn't there, or if it can't be read
+/// (the reason is in the log). `what` is what's in it, for the log.
+pub fn load_data<T: DeserializeOwned>(name: &str, what: &str) -> Option<T> {
+	let mut bytes = match fs::read(data_dir()?.join(name)) {
+		Ok(bytes) => bytes,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+		Err(e) => {
+			warn(["Couldn't read ", what, ": ", &e.to_string()].concat());
+			return None;
+		}
+	};
 
-		let _ = transfer.read_function(|slice| Ok(data.read(slice).unwrap_or(0)));
-		let _ = transfer.write_function(|slice| {
-			// Copy the packet to the buffer
-			vec.extend_from_slice(slice);
-			Ok(slice.len())
+	from_slice(&mut bytes)
+		.map_err(|e| warn(["Couldn't read ", what, ": ", &e.to_string()].concat()))
+		.ok()
+}
+
+/// Saves `value` as JSON to the file called `name` in [`data_dir`], making the directory if it
+/// needs to. `what` is what's in it, for the error.
+pub fn save_data<T: Serialize>(name: &str, what: &str, value: &T) -> Result<(), Error> {
+	let operation = ["Saving ", what].concat();
+	let io_error = |e| Error::new(&*operation, ErrorKind::Io(e));
+
+	let dir = data_dir().ok_or_else(|| {
+		Error::new(
+			&*operation,
+			ErrorKind::Other("Nowhere to save it, set `HOME` or `XDG_DATA_HOME`".to_owned()),
+		)
+	})?;
+
+	fs::create_dir_all(&dir).map_err(io_error)?;
+
+	let json = to_vec_pretty(value).map_err(|e| Error::new(&*operation, ErrorKind::Json(e)))?;
+
+	fs::write(dir.join(name), json).map_err(io_error)
+}
+
+/// Asks for some text in a box over the page, until enter or esc is pressed. `None` if it's
+/// cancelled with esc or nothing was typed.
+pub fn prompt<B: Backend>(terminal: &mut Terminal<B>, title: &str) -> Option<String> {
+	// Show cursor
+	let _ = terminal.show_cursor();
+
+	let mut text = String::new();
+
+	let entered = loop {
+		// Columns the text takes up, not it's length in bytes
+		let text_width = Span::raw(text.as_str()).width() as u16;
+
+		let _ = terminal.draw(|frame| {
+			// Width of the input box, wide enough for the title
+			let width = (text_width.max(Span::raw(title).width() as u16 + 1) + 3)
+				.clamp(20, frame.size().width);
+			let area = Rect {
+				x: (frame.size().width - width) / 2,
+				y: frame.size().height / 2 - 1,
+				width,
+				height: 3,
+			};
+
+			frame.render_widget(
+				Paragraph::new(text.clone()).block(
+					Block::default()
+						.borders(Borders::ALL)
+						.title(Span::styled(title, theme().title))
+						.title_alignment(config().title_alignment)
+						.border_type(config().border_type)
+						.border_style(theme().border),
+				),
+				area,
+			);
+
+			// Kept in the box if the text is wider than the screen
+			frame.set_cursor(
+				(area.x + 1 + text_width).min(area.x + area.width - 2),
+				area.y + 1,
+			);
 		});
 
-		let _ = transfer.perform();
-	}
+		if let Event::Key(KeyEvent { code, .. }) = read().expect("Should be able to read input") {
+			match code {
+				KeyCode::Char(c) => text.push(c),
+				KeyCode::Backspace => {
+					text.pop();
+				}
+				KeyCode::Enter => break true,
+				KeyCode::Esc => break false,
+				_ => (),
+			}
+		}
+	};
 
-	vec
+	let _ = terminal.clear();
+
+	// Hide the cursor again
+	let _ = terminal.hide_cursor();
+
+	Some(text).filter(|text| entered && !text.trim().is_empty())
 }
 
 /// Formats a number of seconds in a human-readable format, i.e. "18 hours"
@@ -49,9 +229,28 @@ pub fn format_seconds(seconds: i64) -> String {
 	}
 }
 
+/// Formats a video's length as `h:mm:ss`, i.e. "1:02:03"
+pub fn format_length(seconds: u32) -> String {
+	format!(
+		"{}:{:02}:{:02}",
+		seconds / 3600,
+		seconds / 60 % 60,
+		seconds % 60
+	)
+}
+
 /// Parses a colour string
-pub fn parse_colour(string: &str) -> Color {
-	let parsed = i32::from_str_radix(string, 16).expect("Server sent an invalid hex colour");
+pub fn parse_colour(string: &str) -> Result<Color, Error> {
+	let parsed = i32::from_str_radix(string, 16).map_err(|_| {
+		Error::new(
+			"Parsing a colour",
+			ErrorKind::Other(["Server sent an invalid hex colour: ", string].concat()),
+		)
+	})?;
 
-	Color::Rgb((parsed >> 16) as u8, (parsed >> 8) as u8, parsed as u8)
+	Ok(Color::Rgb(
+		(parsed >> 16) as u8,
+		(parsed >> 8) as u8,
+		parsed as u8,
+	))
 }
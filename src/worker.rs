@@ -0,0 +1,189 @@
+//! Runs requests on a background thread, so the UI keeps responding while they download.
+//!
+//! The [`Worker`] owns a [`Client`] and runs one job at a time with it. Only the result of the
+//! latest job is kept, anything started before it or cancelled is thrown away. Each job's transfers
+//! report to it's [`Progress`], which the UI shows in the status bar.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Arc;
+use std::thread::spawn;
+use std::time::{Duration, Instant};
+
+use curl::easy::Easy;
+use twitch_curses::Client;
+
+/// How a job's transfers are going, shared between the worker thread and the UI
+#[derive(Clone, Default)]
+pub struct Progress {
+	/// Bytes received by the running transfer
+	downloaded: Arc<AtomicU64>,
+	/// Size of the running transfer, 0 if the server didn't say
+	total: Arc<AtomicU64>,
+	/// Set to stop the job's transfers
+	cancel: Arc<AtomicBool>,
+}
+impl Progress {
+	/// Reports `easy`'s transfers to this, and aborts them if the job is cancelled.
+	pub fn track(&self, easy: &mut Easy) {
+		// Needed for the progress function to be called
+		let _ = easy.progress(true);
+
+		let progress = self.clone();
+		let _ = easy.progress_function(move |total_bytes, downloaded_bytes, _, _| {
+			progress
+				.downloaded
+				.store(downloaded_bytes as u64, Ordering::Relaxed);
+			progress.total.store(total_bytes as u64, Ordering::Relaxed);
+
+			// Returning `false` aborts the transfer
+			!progress.cancel.load(Ordering::Relaxed)
+		});
+	}
+
+	/// Bytes received so far by the running transfer
+	pub fn downloaded(&self) -> u64 {
+		self.downloaded.load(Ordering::Relaxed)
+	}
+
+	/// How much of the running transfer is done, from 0 to 1. `None` if we don't know it's size,
+	/// which is always the case for GQL requests since they don't return `Content-Length`.
+	pub fn fraction(&self) -> Option<f64> {
+		match self.total.load(Ordering::Relaxed) {
+			0 => None,
+			total => Some((self.downloaded() as f64 / total as f64).min(1.0)),
+		}
+	}
+
+	fn is_cancelled(&self) -> bool {
+		self.cancel.load(Ordering::Relaxed)
+	}
+}
+
+/// Something for the worker to do
+struct Job<T> {
+	id: u64,
+	progress: Progress,
+	run: Box<dyn FnOnce(&mut Client, &Progress) -> T + Send>,
+}
+
+/// The job we're waiting for
+struct Current {
+	id: u64,
+	/// What it's doing, for the status bar
+	description: String,
+	progress: Progress,
+	started: Instant,
+}
+
+/// A background thread that runs requests, returning results of type `T`.
+pub struct Worker<T> {
+	jobs: Sender<Job<T>>,
+	results: Receiver<(u64, T)>,
+	current: Option<Current>,
+	next_id: u64,
+}
+impl<T: Send + 'static> Worker<T> {
+	/// Starts the worker thread, which uses `client` for it's requests.
+	pub fn new(mut client: Client) -> Self {
+		let (jobs, job_receiver) = channel::<Job<T>>();
+		let (result_sender, results) = channel();
+
+		spawn(move || {
+			// Stops when the `Worker` is dropped
+			for Job { id, progress, run } in job_receiver {
+				// Cancelled before it started
+				if progress.is_cancelled() {
+					continue;
+				}
+
+				progress.track(client.handle());
+
+				if result_sender
+					.send((id, run(&mut client, &progress)))
+					.is_err()
+				{
+					break;
+				}
+			}
+		});
+
+		Self {
+			jobs,
+			results,
+			current: None,
+			next_id: 0,
+		}
+	}
+
+	/// Runs `job` on the worker thread, replacing whatever was loading before. Transfers on
+	/// handles other than the client's should be passed to [`Progress::track`].
+	pub fn start<F: FnOnce(&mut Client, &Progress) -> T + Send + 'static>(
+		&mut self,
+		description: String,
+		job: F,
+	) {
+		self.cancel();
+
+		let progress = Progress::default();
+
+		let _ = self.jobs.send(Job {
+			id: self.next_id,
+			progress: progress.clone(),
+			run: Box::new(job),
+		});
+
+		self.current = Some(Current {
+			id: self.next_id,
+			description,
+			progress,
+			started: Instant::now(),
+		});
+
+		self.next_id += 1;
+	}
+
+	/// Stops the current job, it's result won't be returned.
+	pub fn cancel(&mut self) {
+		if let Some(current) = self.current.take() {
+			current.progress.cancel.store(true, Ordering::Relaxed);
+		}
+	}
+
+	/// Is a job running?
+	pub fn is_loading(&self) -> bool {
+		self.current.is_some()
+	}
+
+	/// What the current job is doing
+	pub fn description(&self) -> Option<&str> {
+		self.current.as_ref().map(|current| &*current.description)
+	}
+
+	/// Returns the current job's result, if it's finished.
+	pub fn poll(&mut self) -> Option<T> {
+		let current_id = self.current.as_ref()?.id;
+
+		// Skip results from older jobs
+		while let Ok((id, result)) = self.results.try_recv() {
+			if id == current_id {
+				self.current = None;
+				return Some(result);
+			}
+		}
+
+		None
+	}
+
+	/// How the current job's transfers are going
+	pub fn progress(&self) -> Option<&Progress> {
+		self.current.as_ref().map(|current| &current.progress)
+	}
+
+	/// How long the current job has been running
+	pub fn elapsed(&self) -> Duration {
+		self.current
+			.as_ref()
+			.map_or(Duration::ZERO, |current| current.started.elapsed())
+	}
+}